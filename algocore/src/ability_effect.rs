@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// What a triggered ability does when it resolves, for the abilities the engine understands.
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(tag = "type")]
+pub enum AbilityEffect {
+    /// 'create a wisp', 'create two fireball 1'
+    /// `token_std_name` is the std_name of the token's prototype.
+    CreateToken { token_std_name: String, amount: u32 },
+
+    /// 'sacrifice me'
+    SacrificeMe,
+
+    /// 'i deal 1 damage to each opponent'
+    DamageEachOpponent { amount: u32 },
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::{Faction};
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Affinity {
     pub faction: Faction,
     pub quantity: u32,
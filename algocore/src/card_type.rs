@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::{ResourceType, Timing};
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum CardType {
     Resource(ResourceType),
     UnitToken,
@@ -18,7 +19,7 @@ impl CardType {
     }
 }
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum MetaCardType {
     Trigger,
     StolenCard
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::ResourceType;
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum Faction {
     Fire,
     Earth,
@@ -4,6 +4,9 @@ mod card_type;
 mod timing;
 mod cost;
 mod affinity;
+mod mod_type;
+mod trigger;
+mod ability_effect;
 
 // re-export types
 pub use faction::Faction;
@@ -12,3 +15,6 @@ pub use card_type::{CardType, MetaCardType};
 pub use timing::Timing;
 pub use cost::Cost;
 pub use affinity::Affinity;
+pub use mod_type::ModType;
+pub use trigger::{Trigger, TriggeredAbility};
+pub use ability_effect::AbilityEffect;
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// Describes how a card can be attached to a unit as a 'mod' instead of being played normally.
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(tag = "type")]
+pub enum ModType {
+    /// `[Augment]` cards are applied from their owner's discard pile onto a unit.
+    /// `once` is set when the card is marked `[Augment][once]`.
+    Augment { once: bool },
+
+    /// `[Graft]` cards are applied from their owner's hand onto a unit.
+    /// `limit` is the optional amount in `[Graft N]`, capping how many grafts a unit can hold.
+    Graft { limit: Option<u32> },
+}
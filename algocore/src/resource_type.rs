@@ -1,7 +1,8 @@
 use crate::Faction;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 pub enum ResourceType {
     Fire,
     Earth,
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::AbilityEffect;
+
+/// The condition that causes a triggered ability to go on the stack.
+/// These are named after the phrases the card grammar uses, where 'I' or 'me' is the permanent
+/// that has the ability.
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(tag = "type")]
+pub enum Trigger {
+    /// 'when I spawn'
+    SelfSpawns,
+
+    /// 'when I die'
+    SelfDies,
+
+    /// 'when I am modded or applied as a mod'
+    SelfModded,
+
+    /// 'when I am dealt damage'
+    SelfDealtDamage,
+
+    /// 'whenever a unit dies', 'whenever another nontoken unit dies', etc.
+    UnitDies { another: bool, nontoken: bool },
+
+    /// 'whenever an ally spawns', 'whenever another nontoken ally spawns', etc.
+    AllySpawns { another: bool, nontoken: bool },
+
+    /// 'whenever a player loses life'
+    PlayerLosesLife,
+
+    /// 'after combat'
+    AfterCombat,
+
+    /// 'at the end of the turn'
+    EndOfTurn,
+}
+
+/// A triggered ability printed on a card, e.g. 'When I die, draw a card.'
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct TriggeredAbility {
+    pub trigger: Trigger,
+
+    /// the text of the ability that follows the trigger, e.g. 'draw a card'
+    pub effect_text: String,
+
+    /// the parsed effect_text, if the engine knows how to resolve it
+    #[serde(default)]
+    pub effect: Option<AbilityEffect>,
+}
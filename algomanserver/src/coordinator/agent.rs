@@ -1,9 +1,10 @@
 
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use algomacros::impl_u64_key_wrapper;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Agent {
     pub id: AgentId,
     pub key: AgentKey,
@@ -20,11 +21,11 @@ impl Agent {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq,Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq,Serialize, Deserialize, Hash, JsonSchema)]
 pub struct AgentId(pub u64);
 impl_u64_key_wrapper!(AgentId);
 
 
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, JsonSchema)]
 pub struct AgentKey(pub u64);
 impl_u64_key_wrapper!(AgentKey);
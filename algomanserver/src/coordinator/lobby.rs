@@ -2,15 +2,18 @@ use std::collections::HashMap;
 
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use algomacros::impl_u64_key_wrapper;
-use algomancer_gre::game::GameOptions;
+use algomancer_gre::game::fairness::{Contribution, SeedCommitment, SeedReveal, ServerSeed};
+use algomancer_gre::game::state::GameMode;
+use algomancer_gre::game::state::player::PlayerId;
 
 use crate::coordinator::agent::AgentId;
 use crate::coordinator::Error;
 use crate::coordinator::Error::{NotListening, SendEventError};
 use crate::runner::MigrationInfo;
 
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct LobbyId(pub u64);
 impl_u64_key_wrapper!(LobbyId);
 
@@ -21,19 +24,53 @@ pub struct Lobby {
     pub host_agent_id: AgentId,
     pub name: String,
 
-    pub game_options: GameOptions,
+    pub game_mode: GameMode,
+
+    /// the server's part of the game's seed, which is kept secret until the game is over
+    pub(crate) server_seed: ServerSeed,
+
+    /// the hash of the server seed, which the agents can check it against once it's revealed
+    pub seed_commitment: SeedCommitment,
+
+    /// the entropy each agent has contributed to the game's seed
+    pub(crate) entropy: HashMap<AgentId, Vec<u8>>,
+
+    /// how the agents are given the players of the game once it starts
+    pub seating: Seating,
 
     pub agent_ids: Vec<AgentId>,
 
     pub event_sender: HashMap<AgentId, tokio::sync::mpsc::Sender<LobbyEvent>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+/// Which player of the game each agent in the lobby plays as.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
+pub enum Seating {
+    /// the agents are given players at random
+    #[default]
+    Random,
+
+    /// the host has picked the players of these agents, the rest are seated at random
+    Chosen(HashMap<AgentId, PlayerId>),
+}
+
+/// Changes to a lobby's settings, what's left out stays as it is (see [crate::Coordinator::update_settings]).
+#[derive(Debug, Clone, Default)]
+pub struct LobbySettingsUpdate {
+    pub game_mode: Option<GameMode>,
+    pub capacity: Option<u8>,
+    pub seating: Option<Seating>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub enum LobbyEvent {
     AgentJoined(AgentId),
     AgentLeft(AgentId),
     NewHost(AgentId),
 
+    /// the host changed the lobby's capacity, game options or seating
+    SettingsChanged,
+
     Migrate(AgentId, MigrationInfo),
     Whisper(AgentId, AgentId, String)
 }
@@ -53,6 +90,19 @@ impl Lobby {
         self.agent_ids.is_empty()
     }
 
+    /// what the game's seed is made from, the contributions in the order of the agents' ids
+    pub(crate) fn seed_reveal(&self) -> SeedReveal {
+        let mut contributions: Vec<Contribution> = self.entropy.iter()
+            .map(|(agent_id, entropy)| Contribution { agent_id: agent_id.0, entropy: entropy.clone() })
+            .collect();
+        contributions.sort_by_key(|contribution| contribution.agent_id);
+
+        SeedReveal {
+            server_seed: self.server_seed,
+            contributions,
+        }
+    }
+
     pub fn add_agent_by_id(&mut self, agent_id: AgentId) {
         self.agent_ids.push(agent_id);
     }
@@ -66,6 +116,19 @@ impl Lobby {
         if let Some(agent_idx) = agent_idx {
             self.agent_ids.remove(agent_idx.0);
             self.event_sender.remove(&remove_agent_id);
+            self.entropy.remove(&remove_agent_id);
+
+            if let Seating::Chosen(seats) = &mut self.seating {
+                seats.remove(&remove_agent_id);
+            }
+        }
+    }
+
+    /// the event for everyone listening to the lobby, to be sent later (see [PendingLobbyEvent])
+    pub fn pending_broadcast(&self, lobby_event: LobbyEvent) -> PendingLobbyEvent {
+        PendingLobbyEvent {
+            event: lobby_event,
+            senders: self.event_sender.values().cloned().collect(),
         }
     }
 
@@ -74,17 +137,9 @@ impl Lobby {
             // publicly broadcasted events
             LobbyEvent::AgentJoined(_) |
             LobbyEvent::AgentLeft(_) |
-            LobbyEvent::NewHost(_) => {
-                for (_, rx) in &self.event_sender {
-                    match rx.send(lobby_event.clone()).await {
-                        Ok(_) => {}
-                        Err(err) => {
-                            return Err(SendEventError(err));
-                        }
-                    }
-                }
-
-                Ok(())
+            LobbyEvent::NewHost(_) |
+            LobbyEvent::SettingsChanged => {
+                self.pending_broadcast(lobby_event).send().await
             }
 
             // privately sent events
@@ -108,4 +163,24 @@ impl Lobby {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// A lobby event which has yet to be sent to the agents listening to the lobby. Sending waits while
+/// an agent's queue of events is full, and what empties the queue may be waiting on the coordinator,
+/// so the event is meant to be sent once the coordinator has been released.
+#[must_use = "the event isn't sent until send is called"]
+#[derive(Debug)]
+pub struct PendingLobbyEvent {
+    event: LobbyEvent,
+    senders: Vec<tokio::sync::mpsc::Sender<LobbyEvent>>,
+}
+
+impl PendingLobbyEvent {
+    pub async fn send(self) -> Result<(), Error> {
+        for tx in &self.senders {
+            tx.send(self.event.clone()).await.map_err(SendEventError)?;
+        }
+
+        Ok(())
+    }
+}
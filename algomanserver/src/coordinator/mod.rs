@@ -1,13 +1,15 @@
 pub mod agent;
 pub mod lobby;
 
+use std::collections::HashSet;
 use std::fmt::{Debug, Display, Formatter};
 use tokio::sync::mpsc::error::SendError;
-use algomancer_gre::game::{GameOptions};
+use algomancer_gre::game::fairness;
+use algomancer_gre::game::fairness::SeedCommitment;
 use algomancer_gre::game::state::GameMode;
-use algomancer_gre::game::state::rng::AlgomancerRngSeed;
+use algomancer_gre::game::state::player::PlayerId;
 use crate::coordinator::agent::{Agent, AgentId, AgentKey};
-use crate::coordinator::lobby::{Lobby, LobbyEvent, LobbyId};
+use crate::coordinator::lobby::{Lobby, LobbyEvent, LobbyId, LobbySettingsUpdate, PendingLobbyEvent, Seating};
 use crate::runner::Runner;
 
 #[derive(Debug)]
@@ -31,8 +33,26 @@ pub enum Error {
     CannotRunError(crate::runner::Error),
     NotListening(AgentId),
     SendEventError(SendError<LobbyEvent>),
+
+    /// only the host can change the lobby's settings
+    NotLobbyHost(AgentId),
+
+    /// the player doesn't exist in the lobby's game
+    InvalidSeat(PlayerId),
+
+    /// more than one agent was given the same player
+    SeatTaken(PlayerId),
+
+    /// a lobby has room for at least the agents already in it, and at most one agent per player
+    InvalidCapacity { capacity: u8, agents: usize, players: u8 },
+
+    /// an agent's contribution to the seed has to be between 1 and [MAX_ENTROPY_LEN] bytes
+    InvalidEntropy(usize),
 }
 
+/// the most entropy an agent can contribute to a game's seed, in bytes
+pub const MAX_ENTROPY_LEN: usize = 64;
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -55,7 +75,7 @@ impl Display for Error {
                 write!(f, "agent {agent_id} is already in lobby {lobby_id}")
             }
             Error::CannotRunError(err) => {
-                write!(f, "cannot run the game: {:?}", err)
+                write!(f, "cannot run the game: {err}")
             }
             Error::NotListening(agent_id) => {
                 write!(f, "agent {agent_id} is not listening")
@@ -69,6 +89,21 @@ impl Display for Error {
             Error::LobbyIsFull(lobby_id) => {
                 write!(f, "lobby {lobby_id} is full")
             }
+            Error::NotLobbyHost(agent_id) => {
+                write!(f, "agent {agent_id} is not the host of the lobby")
+            }
+            Error::InvalidSeat(player_id) => {
+                write!(f, "there is no player {player_id} in the lobby's game")
+            }
+            Error::SeatTaken(player_id) => {
+                write!(f, "player {player_id} is given to more than one agent")
+            }
+            Error::InvalidCapacity { capacity, agents, players } => {
+                write!(f, "a capacity of {capacity} is invalid, the lobby has {agents} agents and its game has {players} players")
+            }
+            Error::InvalidEntropy(len) => {
+                write!(f, "{len} bytes of entropy can't be contributed, it must be between 1 and {MAX_ENTROPY_LEN} bytes")
+            }
         }
     }
 }
@@ -109,24 +144,27 @@ impl Coordinator {
     }
 
     pub async fn create_lobby_with_host(&mut self, host_agent_key: AgentKey, name: &str) -> Result<LobbyId, Error> {
-        let _ = self.leave_current_lobby(host_agent_key);
+        let _ = self.leave_current_lobby(host_agent_key).await;
 
         let host_agent_id = self.get_agent_id_by_key(host_agent_key)?;
 
         let agent = self.get_agent_by_id(host_agent_id)?;
 
-        let options = GameOptions {
-            seed: AlgomancerRngSeed::from([0; 16]),
-            game_mode: GameMode::new_player_mode(),
-        };
+        let game_mode = GameMode::new_player_mode();
+        let capacity = game_mode.team_configuration().num_players();
+        let server_seed = fairness::draw_server_seed();
 
         let lobby_id = self.next_game_id();
 
         let lobby = Lobby {
             id: lobby_id,
             name: name.to_string(),
-            game_options: options,
-            capacity: 4,
+            game_mode,
+            server_seed,
+            seed_commitment: SeedCommitment::of(&server_seed),
+            entropy: Default::default(),
+            seating: Seating::Random,
+            capacity,
             host_agent_id: agent.id,
             agent_ids: vec![host_agent_id],
             event_sender: Default::default(),
@@ -228,6 +266,131 @@ impl Coordinator {
         Ok(())
     }
 
+    /// Changes the lobby's settings, which only the host can do. Every change is checked before
+    /// any is made, so the update is applied entirely or not at all, and the lobby's agents are told
+    /// of it with a single event.
+    ///
+    /// - The game mode is applied first. The capacity becomes the number of players in its team
+    ///   configuration, which has to have room for the agents already in the lobby, and chosen seats
+    ///   for players no longer in the game are dropped. The game's seed isn't the host's to choose,
+    ///   it's drawn by the coordinator.
+    /// - The capacity has to be the number of players in the game, as every player needs an agent
+    ///   to play as them before the game can start.
+    /// - Chosen seats must be for agents in the lobby and players in its game, one agent per player.
+    ///
+    /// The event is returned rather than sent, as sending it waits on the agents' listeners, which
+    /// may themselves be waiting on the coordinator (see [PendingLobbyEvent]).
+    pub fn update_settings(&mut self, host_agent_key: AgentKey, lobby_id: LobbyId, update: LobbySettingsUpdate) -> Result<PendingLobbyEvent, Error> {
+        let lobby = self.try_get_hosted_lobby_mut(host_agent_key, lobby_id)?;
+
+        let mut game_mode = lobby.game_mode.clone();
+        let mut capacity = lobby.capacity;
+        let mut seating = lobby.seating.clone();
+
+        if let Some(new_game_mode) = update.game_mode {
+            let num_players = new_game_mode.team_configuration().num_players();
+            if num_players == 0 || lobby.agent_ids.len() > num_players as usize {
+                return Err(Error::InvalidCapacity { capacity: num_players, agents: lobby.agent_ids.len(), players: num_players });
+            }
+
+            if let Seating::Chosen(seats) = &mut seating {
+                seats.retain(|_, player_id| player_id.0 <= num_players);
+            }
+
+            game_mode = new_game_mode;
+            capacity = num_players;
+        }
+
+        let num_players = game_mode.team_configuration().num_players();
+
+        if let Some(new_capacity) = update.capacity {
+            if new_capacity != num_players {
+                return Err(Error::InvalidCapacity { capacity: new_capacity, agents: lobby.agent_ids.len(), players: num_players });
+            }
+
+            capacity = new_capacity;
+        }
+
+        if let Some(new_seating) = update.seating {
+            if let Seating::Chosen(seats) = &new_seating {
+                let mut taken = HashSet::new();
+
+                for (agent_id, player_id) in seats {
+                    if !lobby.contains_agent(*agent_id) {
+                        return Err(Error::AgentNotInCorrectLobby(*agent_id));
+                    }
+
+                    if player_id.0 == 0 || player_id.0 > num_players {
+                        return Err(Error::InvalidSeat(*player_id));
+                    }
+
+                    if !taken.insert(*player_id) {
+                        return Err(Error::SeatTaken(*player_id));
+                    }
+                }
+            }
+
+            seating = new_seating;
+        }
+
+        lobby.game_mode = game_mode;
+        lobby.capacity = capacity;
+        lobby.seating = seating;
+
+        Ok(lobby.pending_broadcast(LobbyEvent::SettingsChanged))
+    }
+
+    /// Sets how the lobby's agents are given the players of the game, see [Coordinator::update_settings].
+    pub async fn set_seating(&mut self, host_agent_key: AgentKey, lobby_id: LobbyId, seating: Seating) -> Result<(), Error> {
+        let update = LobbySettingsUpdate { seating: Some(seating), ..Default::default() };
+        self.update_settings(host_agent_key, lobby_id, update)?.send().await
+    }
+
+    /// Sets the mode the lobby's game is created in, see [Coordinator::update_settings].
+    pub async fn set_game_mode(&mut self, host_agent_key: AgentKey, lobby_id: LobbyId, game_mode: GameMode) -> Result<(), Error> {
+        let update = LobbySettingsUpdate { game_mode: Some(game_mode), ..Default::default() };
+        self.update_settings(host_agent_key, lobby_id, update)?.send().await
+    }
+
+    /// Sets how many agents can join the lobby, see [Coordinator::update_settings].
+    pub async fn set_capacity(&mut self, host_agent_key: AgentKey, lobby_id: LobbyId, capacity: u8) -> Result<(), Error> {
+        let update = LobbySettingsUpdate { capacity: Some(capacity), ..Default::default() };
+        self.update_settings(host_agent_key, lobby_id, update)?.send().await
+    }
+
+    /// Adds the agent's own entropy to the game's seed, replacing what it contributed before. With
+    /// every agent contributing, the seed can't be picked by the coordinator or any one of them.
+    pub fn contribute_entropy(&mut self, agent_key: AgentKey, lobby_id: LobbyId, entropy: Vec<u8>) -> Result<(), Error> {
+        let agent_id = self.get_agent_id_by_key(agent_key)?;
+
+        let lobby = self.try_get_lobby_mut(lobby_id)?;
+
+        if !lobby.contains_agent(agent_id) {
+            return Err(Error::AgentNotInCorrectLobby(agent_id));
+        }
+
+        if entropy.is_empty() || entropy.len() > MAX_ENTROPY_LEN {
+            return Err(Error::InvalidEntropy(entropy.len()));
+        }
+
+        lobby.entropy.insert(agent_id, entropy);
+
+        Ok(())
+    }
+
+    /// the lobby, as long as the agent is its host
+    fn try_get_hosted_lobby_mut(&mut self, host_agent_key: AgentKey, lobby_id: LobbyId) -> Result<&mut Lobby, Error> {
+        let host_agent_id = self.get_agent_id_by_key(host_agent_key)?;
+
+        let lobby = self.try_get_lobby_mut(lobby_id)?;
+
+        if lobby.host_agent_id != host_agent_id {
+            return Err(Error::NotLobbyHost(host_agent_id));
+        }
+
+        Ok(lobby)
+    }
+
     pub fn try_get_agent(&self, agent_id: AgentId) -> Result<&Agent, Error> {
         if let Some(agent) = self.agents.iter().find(|a| a.id == agent_id) {
             Ok(agent)
@@ -255,7 +418,7 @@ impl Coordinator {
     pub fn get_current_lobby_mut(&mut self, agent_id: AgentId) -> Option<&mut Lobby> {
         self.lobbies
             .iter_mut()
-            .find(|l| l.agent_ids.iter().any(|a| *a == agent_id))
+            .find(|l| l.agent_ids.contains(&agent_id))
     }
 
     pub fn get_agent_by_id(&self, agent_id: AgentId) -> Result<&Agent, Error> {
@@ -330,15 +493,30 @@ impl Coordinator {
     pub async fn start_game(&mut self, _agent_key: AgentKey, lobby_id: LobbyId) -> Result<Runner, Error> {
         let lobby = self.try_get_lobby(lobby_id)?;
 
-        let agent_keys = lobby.agent_ids.iter().map(|a_id| (*a_id, self.agents.iter().find(|a| a.id == *a_id).unwrap().key)).collect();
+        let runner = match Runner::from_lobby(lobby, self.lobby_agent_keys(lobby)).await {
+            Ok(runner) => runner,
+            Err(err) => return Err(Error::CannotRunError(err))
+        };
+
+        Ok(runner)
+    }
+
+    /// like [Coordinator::start_game], but resumes a saved game (a serialized game) rather than
+    /// starting a new one. Saved games from a newer version of the server can't be resumed.
+    pub async fn resume_game(&mut self, _agent_key: AgentKey, lobby_id: LobbyId, saved_game: &str) -> Result<Runner, Error> {
+        let lobby = self.try_get_lobby(lobby_id)?;
 
-        let runner = match Runner::from_lobby(lobby, agent_keys).await {
+        let runner = match Runner::from_saved_game(lobby, self.lobby_agent_keys(lobby), saved_game).await {
             Ok(runner) => runner,
             Err(err) => return Err(Error::CannotRunError(err))
         };
 
         Ok(runner)
     }
+
+    fn lobby_agent_keys(&self, lobby: &Lobby) -> Vec<(AgentId, AgentKey)> {
+        lobby.agent_ids.iter().map(|a_id| (*a_id, self.agents.iter().find(|a| a.id == *a_id).unwrap().key)).collect()
+    }
 }
 
 #[cfg(test)]
@@ -347,20 +525,32 @@ mod tests {
     use tokio::sync::mpsc::Receiver;
     use tokio::task::{JoinHandle};
     use tokio::time::sleep;
-    use crate::coordinator::{Coordinator, Error};
-    use crate::{LobbyEvent};
+    use std::collections::HashMap;
+    use algomancer_gre::game::state::GameMode;
+    use algomancer_gre::game::state::player::PlayerId;
+    use algomancer_gre::game::state::team_configuration::TeamConfiguration;
+    use algomancer_gre::game::{Game, GameOptions};
+    use algomancer_gre::game::fairness::Contribution;
+    use algomancer_gre::game::schema::{SCHEMA_VERSION, SchemaError};
+    use crate::coordinator::{Coordinator, Error, MAX_ENTROPY_LEN};
+    use crate::runner;
+    use crate::{LobbyEvent, LobbySettingsUpdate, Seating};
     
 
+    fn two_v_two() -> GameMode {
+        let mut game_mode = GameMode::new_player_mode();
+        if let GameMode::LiveDraft { team_configuration, .. } = &mut game_mode {
+            *team_configuration = TeamConfiguration::two_v_two();
+        }
+
+        game_mode
+    }
+
     fn expect_events(mut rx: Receiver<LobbyEvent>, expected_events: Vec<LobbyEvent>) -> JoinHandle<()> {
         tokio::spawn(async move {
             for expected_event in expected_events {
-                match rx.recv().await {
-                    None => {}
-                    Some(event) => {
-                        if event != expected_event {
-                            panic!("received unexpected event\n{:?}\nExpected\n{:?}", event, expected_event)
-                        }
-                    }
+                if let Some(event) = rx.recv().await {
+                    assert_eq!(event, expected_event, "received unexpected event");
                 }
             }
         })
@@ -392,8 +582,9 @@ mod tests {
         let (dwight_agent_id, dwight_agent_key) = coordinator.create_new_agent("Dwight").await.unwrap();
 
 
-        // jim makes lobby
+        // jim makes lobby, for a game of four
         let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.set_game_mode(jim_agent_key, lobby_id, two_v_two()).await.unwrap();
 
         // jim listens to lobby
         let jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
@@ -480,6 +671,7 @@ mod tests {
         let (_, agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
 
         let lobby_id = coordinator.create_lobby_with_host(agent_key, "Lobby").await.unwrap();
+        coordinator.set_game_mode(agent_key, lobby_id, two_v_two()).await.unwrap();
 
         let (_, agent_2_key) = coordinator.create_new_agent("Pam").await.unwrap();
 
@@ -517,8 +709,8 @@ mod tests {
         let (_, agent_key) = coordinator.create_new_agent("Denis").await.unwrap();
         let lobby_id_1 = coordinator.create_lobby_with_host(agent_key, "Lobby").await.unwrap();
 
-        let (_, _agent_2_key) = coordinator.create_new_agent("Greg").await.unwrap();
-        let lobby_id_2 = coordinator.create_lobby_with_host(agent_key, "Lobby").await.unwrap();
+        let (_, agent_2_key) = coordinator.create_new_agent("Greg").await.unwrap();
+        let lobby_id_2 = coordinator.create_lobby_with_host(agent_2_key, "Lobby").await.unwrap();
 
         let (_, agent_3_key) = coordinator.create_new_agent("Evil").await.unwrap();
 
@@ -563,6 +755,59 @@ mod tests {
         };
     }
 
+    #[tokio::test]
+    async fn test_coordinator_needs_an_agent_per_player() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+        coordinator.set_game_mode(jim_agent_key, lobby_id, two_v_two()).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let _pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        // two of the four players would have no one to play them, so the game isn't started
+        assert!(matches!(
+            coordinator.start_game(jim_agent_key, lobby_id).await,
+            Err(Error::CannotRunError(runner::Error::EmptySeats(2)))
+        ));
+        assert!(jim_rx.try_recv().is_err(), "no agent is migrated");
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_resume_game() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+        let _jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let _pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        let game = Game::new(&GameOptions { seed: [7; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let saved_game = serde_json::to_string(&game).unwrap();
+
+        let runner = coordinator.resume_game(jim_agent_key, lobby_id, &saved_game).await.unwrap();
+        assert_eq!(serde_json::to_string(&runner.game().state).unwrap(), serde_json::to_string(&game.state).unwrap());
+        assert_eq!(runner.seed_commitment(), None);
+
+        // a game saved by a newer server is reported as such, rather than failing to parse
+        let newer_game = saved_game.replacen(
+            &format!(r#""schema_version":{}"#, SCHEMA_VERSION),
+            &format!(r#""schema_version":{}"#, SCHEMA_VERSION + 1),
+            1,
+        );
+        match coordinator.resume_game(jim_agent_key, lobby_id, &newer_game).await {
+            Err(err @ Error::CannotRunError(runner::Error::SavedGameError(SchemaError::TooNew { .. }))) => {
+                assert!(err.to_string().contains("newer"), "{}", err);
+            }
+            result => panic!("expected the saved game to be too new, got {:?}", result.map(|_| ())),
+        }
+    }
+
     #[tokio::test]
     async fn test_coordinator_duplicate_agent() {
         let mut coordinator = Coordinator::new();
@@ -582,4 +827,155 @@ mod tests {
         }
 
     }
+
+    #[tokio::test]
+    async fn test_coordinator_seating() {
+        let mut coordinator = Coordinator::new();
+
+        let (jim_agent_id, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (pam_agent_id, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        let seating = |seats: &[(_, u8)]| Seating::Chosen(seats.iter().map(|(a, p)| (*a, PlayerId(*p))).collect::<HashMap<_, _>>());
+
+        assert!(matches!(coordinator.set_seating(pam_agent_key, lobby_id, seating(&[(pam_agent_id, 1)])).await, Err(Error::NotLobbyHost(_))));
+        assert!(matches!(coordinator.set_seating(jim_agent_key, lobby_id, seating(&[(pam_agent_id, 3)])).await, Err(Error::InvalidSeat(_))));
+        assert!(matches!(coordinator.set_seating(jim_agent_key, lobby_id, seating(&[(jim_agent_id, 2), (pam_agent_id, 2)])).await, Err(Error::SeatTaken(_))));
+
+        coordinator.set_seating(jim_agent_key, lobby_id, seating(&[(pam_agent_id, 1), (jim_agent_id, 2)])).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let mut pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+        let runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+
+        for (rx, player_id) in [(&mut jim_rx, PlayerId(2)), (&mut pam_rx, PlayerId(1))] {
+            match rx.recv().await {
+                Some(LobbyEvent::Migrate(_, info)) => {
+                    assert_eq!(info.player_id, player_id);
+                    assert_eq!(runner.client_player(info.client_key).unwrap(), player_id);
+                }
+                event => panic!("expected a migrate event, got {:?}", event),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_lobby_options() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (pam_agent_id, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let (_, dwight_agent_key) = coordinator.create_new_agent("Dwight").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        // a lobby fits one agent per player of its game
+        assert_eq!(coordinator.try_get_lobby(lobby_id).unwrap().capacity, 2);
+        assert!(matches!(coordinator.join_lobby(dwight_agent_key, lobby_id).await, Err(Error::LobbyIsFull(_))));
+
+        let rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+        let listener_handle = expect_events(rx, vec![LobbyEvent::SettingsChanged; 5]);
+        let one_v_one = GameMode::new_player_mode();
+
+        assert!(matches!(coordinator.set_game_mode(pam_agent_key, lobby_id, two_v_two()).await, Err(Error::NotLobbyHost(_))));
+        coordinator.set_game_mode(jim_agent_key, lobby_id, two_v_two()).await.unwrap();
+        assert_eq!(coordinator.try_get_lobby(lobby_id).unwrap().capacity, 4);
+
+        // the seats of players who aren't in the new game are dropped
+        coordinator.set_seating(jim_agent_key, lobby_id, Seating::Chosen(HashMap::from([(pam_agent_id, PlayerId(4))]))).await.unwrap();
+        coordinator.set_game_mode(jim_agent_key, lobby_id, one_v_one.clone()).await.unwrap();
+        assert_eq!(coordinator.try_get_lobby(lobby_id).unwrap().seating, Seating::Chosen(HashMap::new()));
+        coordinator.set_game_mode(jim_agent_key, lobby_id, two_v_two()).await.unwrap();
+
+        // a game can't start with empty seats, so the capacity has to fit every player
+        for capacity in [0, 1, 3, 5] {
+            assert!(matches!(coordinator.set_capacity(jim_agent_key, lobby_id, capacity).await, Err(Error::InvalidCapacity { .. })));
+        }
+        coordinator.set_capacity(jim_agent_key, lobby_id, 4).await.unwrap();
+        coordinator.join_lobby(dwight_agent_key, lobby_id).await.unwrap();
+
+        // going back to a game of two doesn't fit the three agents
+        assert!(matches!(coordinator.set_game_mode(jim_agent_key, lobby_id, one_v_one).await, Err(Error::InvalidCapacity { .. })));
+
+        listener_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_update_settings() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (pam_agent_id, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+        let mut rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        // nothing is changed when any part of the update is invalid, even the parts that were valid
+        let update = LobbySettingsUpdate { game_mode: Some(two_v_two()), capacity: Some(5), seating: None };
+        assert!(matches!(coordinator.update_settings(jim_agent_key, lobby_id, update), Err(Error::InvalidCapacity { .. })));
+        let lobby = coordinator.try_get_lobby(lobby_id).unwrap();
+        assert_eq!((lobby.game_mode.team_configuration().num_players(), lobby.capacity), (2, 2));
+
+        // the seats are checked against the new game mode
+        let update = LobbySettingsUpdate {
+            game_mode: Some(two_v_two()),
+            capacity: Some(4),
+            seating: Some(Seating::Chosen(HashMap::from([(pam_agent_id, PlayerId(4))]))),
+        };
+        let event = coordinator.update_settings(jim_agent_key, lobby_id, update).unwrap();
+        let lobby = coordinator.try_get_lobby(lobby_id).unwrap();
+        assert_eq!((lobby.game_mode.team_configuration().num_players(), lobby.capacity), (4, 4));
+        assert_eq!(lobby.seating, Seating::Chosen(HashMap::from([(pam_agent_id, PlayerId(4))])));
+
+        // the agents are told of the whole update at once, when the event is sent
+        assert!(rx.try_recv().is_err());
+        event.send().await.unwrap();
+        assert_eq!(rx.try_recv(), Ok(LobbyEvent::SettingsChanged));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_seed_commitment() {
+        let mut coordinator = Coordinator::new();
+
+        let (jim_agent_id, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (pam_agent_id, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        let other_lobby_id = coordinator.create_lobby_with_host(pam_agent_key, "Lobby").await.unwrap();
+
+        // every lobby has a seed of its own
+        let seed_commitment = coordinator.try_get_lobby(lobby_id).unwrap().seed_commitment.clone();
+        assert_ne!(seed_commitment, coordinator.try_get_lobby(other_lobby_id).unwrap().seed_commitment);
+
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+        for entropy in [vec![], vec![0; MAX_ENTROPY_LEN + 1]] {
+            assert!(matches!(coordinator.contribute_entropy(pam_agent_key, lobby_id, entropy), Err(Error::InvalidEntropy(_))));
+        }
+        coordinator.contribute_entropy(pam_agent_key, lobby_id, vec![1, 2, 3]).unwrap();
+        coordinator.contribute_entropy(jim_agent_key, lobby_id, vec![4]).unwrap();
+
+        let seed_reveal = coordinator.try_get_lobby(lobby_id).unwrap().seed_reveal();
+        // each agent can find their own contribution
+        assert_eq!(seed_reveal.contributions, vec![
+            Contribution { agent_id: jim_agent_id.0, entropy: vec![4] },
+            Contribution { agent_id: pam_agent_id.0, entropy: vec![1, 2, 3] },
+        ]);
+
+        let mut rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let _pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+        let runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+
+        match rx.recv().await {
+            Some(LobbyEvent::Migrate(_, info)) => {
+                assert_eq!(info.seed_commitment, Some(seed_commitment.clone()));
+                seed_reveal.verify(&seed_commitment, &info.seed_inputs_commitment.unwrap()).unwrap();
+            }
+            event => panic!("expected a migrate event, got {:?}", event),
+        }
+
+        // the game is the one the revealed seed gives
+        let game = Game::new(&GameOptions { seed: seed_reveal.seed(), game_mode: GameMode::new_player_mode() }).unwrap();
+        assert_eq!(serde_json::to_string(&runner.game().state).unwrap(), serde_json::to_string(&game.state).unwrap());
+    }
 }
@@ -14,12 +14,18 @@ pub mod runner;
 pub use coordinator::{
     Coordinator,
     agent::{AgentId, AgentKey, Agent},
-    lobby::{LobbyId, LobbyEvent, Lobby},
+    lobby::{LobbyId, LobbyEvent, LobbySettingsUpdate, Lobby, PendingLobbyEvent, Seating},
 };
 
 pub use runner::{
     Runner,
     RunnerId,
+    RunnerEvent,
+    ClientView,
+    ClientConnection,
+    GameOver,
+    SpectatorEvent,
+    Seat,
     client
 };
 
@@ -1,7 +1,8 @@
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use algomacros::impl_u64_key_wrapper;
 
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, JsonSchema)]
 pub struct ClientKey(pub u64);
 impl_u64_key_wrapper!(ClientKey);
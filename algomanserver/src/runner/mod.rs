@@ -2,20 +2,82 @@ pub mod client;
 
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use rand::RngCore;
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use algomancer_gre::game::{Game};
+use schemars::JsonSchema;
+use tokio::sync::broadcast;
+use algomancer_gre::game::{Game, GameOptions};
+use algomancer_gre::game::fairness::{SeedCommitment, SeedReveal};
+use algomancer_gre::game::action::Action;
+use algomancer_gre::game::state::error::StateError;
+use algomancer_gre::game::state::game_result::GameResult;
+use algomancer_gre::game::state::mutation::StaticStateMutation;
+use algomancer_gre::game::state::player::PlayerId;
+use algomancer_gre::game::state::State;
 use algomancer_gre::game::game_builder::NewGameError;
-use crate::{AgentId, AgentKey, Lobby, LobbyEvent};
+use algomancer_gre::game::schema;
+use algomancer_gre::game::schema::SchemaError;
+use crate::{AgentId, AgentKey, Lobby, LobbyEvent, Seating};
 use crate::runner::client::ClientKey;
 
 #[derive(Debug)]
 pub enum Error {
     NewGameError(NewGameError),
+
+    /// the saved game couldn't be loaded, e.g. because it was saved by a newer version
+    SavedGameError(SchemaError),
     CouldNotMigrate(AgentId, Box<crate::coordinator::Error>),
+
+    /// the agent was seated as a player who isn't in the game, or who was already taken
+    InvalidSeat { agent_id: AgentId, player_id: PlayerId },
+
+    /// there are more agents in the lobby than players in the game
+    NoSeatLeft(AgentId),
+
+    /// there are this many fewer agents in the lobby than players in the game, nothing would act
+    /// for the players left over, so the game would wait on them forever
+    EmptySeats(usize),
+
+    /// the client isn't one of the clients migrated to this runner
+    UnknownClient(ClientKey),
+
+    /// the runner hasn't sent the lobby's agents their migration info yet
+    NotMigrated,
+
+    /// the client is already connected, it can only connect again once it has disconnected
+    AlreadyConnected(ClientKey),
+    NotConnected(ClientKey),
+
+    /// clients can only act as the player they were given
+    NotTheClientsPlayer { player_id: PlayerId, issuer_player_id: PlayerId },
+    InvalidAction(Action),
+    ActionFailed(StateError),
+    GameIsOver,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NewGameError(err) => write!(f, "could not create the game: {err}"),
+            Error::SavedGameError(err) => write!(f, "could not load the saved game: {err}"),
+            Error::CouldNotMigrate(agent_id, err) => write!(f, "could not migrate agent {agent_id:?} to the runner: {err:?}"),
+            Error::InvalidSeat { agent_id, player_id } => write!(f, "agent {agent_id} can't be seated as player {player_id}, who isn't a free player of the game"),
+            Error::NoSeatLeft(agent_id) => write!(f, "there is no player left for agent {agent_id} to play as"),
+            Error::EmptySeats(count) => write!(f, "{count} player(s) would have no agent to play as them, the game needs an agent for every player"),
+            Error::UnknownClient(client_key) => write!(f, "client {client_key} is not part of this game"),
+            Error::NotMigrated => write!(f, "the lobby hasn't been migrated to the runner"),
+            Error::AlreadyConnected(client_key) => write!(f, "client {client_key} is already connected"),
+            Error::NotConnected(client_key) => write!(f, "client {client_key} is not connected"),
+            Error::NotTheClientsPlayer { player_id, issuer_player_id } => write!(f, "the action is issued by player {issuer_player_id}, but the client plays as player {player_id}"),
+            Error::InvalidAction(action) => write!(f, "the action {action:?} can't be taken right now"),
+            Error::ActionFailed(err) => write!(f, "the action failed: {err}"),
+            Error::GameIsOver => write!(f, "the game is over, no more actions can be taken"),
+        }
+    }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Hash, JsonSchema)]
 pub struct RunnerId(pub u64);
 
 impl Display for RunnerId {
@@ -31,25 +93,134 @@ impl From<u64> for  RunnerId {
 }
 
 
+/// What happens in a runner's game, broadcast to everything serving its clients.
+#[derive(Debug, Clone)]
+pub enum RunnerEvent {
+    /// every client has connected, and the game has started
+    Started,
+
+    /// an action was applied, changing the state with these mutations, as each seated player is
+    /// allowed to see them (see [StaticStateMutation::visible_to])
+    Mutations(HashMap<PlayerId, Vec<StaticStateMutation>>),
+
+    /// the player's client lost its connection, the game waits for the player to act until it's back
+    PlayerAway(PlayerId),
+
+    /// the player's client has reconnected
+    PlayerReturned(PlayerId),
+
+    /// the game has ended, nothing is broadcast after this. What the game's seed was made from is
+    /// revealed, unless the game was resumed from a save rather than started by the runner
+    GameOver(GameResult, Option<SeedReveal>),
+}
+
+/// What spectators are sent of a runner's game.
+#[derive(Debug, Clone)]
+pub enum SpectatorEvent {
+    /// an action was applied, changing the state with these mutations, and this is the state after
+    /// it, both as spectators see them (see [State::visible_to_spectators])
+    Update { mutations: Vec<StaticStateMutation>, state: State },
+
+    /// the game has ended, with every action taken in it and what its seed was made from
+    GameOver { result: GameResult, actions: Vec<Action>, seed_reveal: Option<SeedReveal> },
+}
+
+/// The game as a client's player sees it.
+#[derive(Debug, Clone)]
+pub struct ClientView {
+    pub player_id: PlayerId,
+
+    /// the state, with what the player isn't allowed to see hidden (see [State::visible_to])
+    pub state: State,
+
+    /// the actions the player can take, in order
+    pub valid_actions: Vec<Action>,
+}
+
+/// A client's connection to the runner.
+#[derive(Debug)]
+pub struct ClientConnection {
+    /// the player the client plays as
+    pub player_id: PlayerId,
+    pub events_rx: broadcast::Receiver<RunnerEvent>,
+
+    /// the mutations of the actions taken while a reconnecting client was away, in order and as its
+    /// player sees them, which the state it's sent next already includes
+    pub missed_mutations: Vec<StaticStateMutation>,
+
+    /// how the game ended, for a client connecting after it's over, as nothing more will be broadcast
+    pub game_over: Option<GameOver>,
+}
+
+/// How a runner's game ended, with every action taken in it and what its seed was made from.
+#[derive(Debug, Clone)]
+pub struct GameOver {
+    pub result: GameResult,
+    pub actions: Vec<Action>,
+    pub seed_reveal: Option<SeedReveal>,
+}
+
 #[derive(Debug)]
 pub struct Runner {
     pub runner_id: RunnerId,
     migration_state: Option<MigrationState>,
-    run_tx: Option<tokio::sync::broadcast::Sender<()>>,
-    game: Game
+    events_tx: broadcast::Sender<RunnerEvent>,
+    spectators_tx: broadcast::Sender<SpectatorEvent>,
+    game: Game,
+
+    /// what the game's seed was made from, kept secret until the game is over
+    seed_reveal: Option<SeedReveal>,
+
+    /// the mutations of each action applied so far, for catching up reconnecting clients
+    mutation_history: Vec<Vec<StaticStateMutation>>,
+    started: bool,
+
+    /// the span everything to do with this runner's game is logged under
+    span: tracing::Span,
 }
 
 #[derive(Debug)]
 pub struct MigrationState {
     migration_keys: HashMap<AgentKey, ClientKey>,
-    clients: HashMap<ClientKey, bool> // connected
+
+    /// the clients which have connected at least once
+    clients: HashMap<ClientKey, Connection>,
+
+    /// the player each agent's client plays as
+    seats: Vec<Seat>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+/// An agent's place in the game, the player its client plays as and is only allowed to act for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seat {
+    pub agent_id: AgentId,
+    pub client_key: ClientKey,
+    pub player_id: PlayerId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connection {
+    Connected,
+
+    /// the client lost its connection after having seen this many actions
+    Disconnected { actions_seen: usize },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub struct MigrationInfo {
     pub runner_id: RunnerId,
     pub agent_key: AgentKey,
     pub client_key: ClientKey,
+
+    /// the player the agent plays as
+    pub player_id: PlayerId,
+
+    /// the commitment to the server's part of the game's seed, revealed once the game is over
+    pub seed_commitment: Option<SeedCommitment>,
+
+    /// the commitment to everything the game's seed is made from, including each agent's
+    /// contribution, which the reveal is checked against too
+    pub seed_inputs_commitment: Option<SeedCommitment>,
 }
 
 impl Runner {
@@ -61,16 +232,53 @@ impl Runner {
     /// once all agents connect, the runner can start. And the lobby can be cleaned up.
     pub async fn from_lobby(lobby: &Lobby, lobby_agent_keys: Vec<(AgentId, AgentKey)>) -> Result<Self, Error> {
 
-        let game = match Game::new(&lobby.game_options) {
+        let seed_reveal = lobby.seed_reveal();
+        let options = GameOptions {
+            seed: seed_reveal.seed(),
+            game_mode: lobby.game_mode.clone(),
+        };
+
+        let game = match Game::new(&options) {
             Ok(game) => game,
             Err(err) => return Err(Error::NewGameError(err))
         };
 
+        Self::with_game(lobby, lobby_agent_keys, game, Some(seed_reveal)).await
+    }
+
+    /// creates a game runner instance from a coordinator lobby, like [Runner::from_lobby], but
+    /// resumes a saved game (a serialized [Game]) rather than starting a new one. Games saved by
+    /// older versions are upgraded as they're loaded, games saved by newer versions are rejected.
+    pub async fn from_saved_game(lobby: &Lobby, lobby_agent_keys: Vec<(AgentId, AgentKey)>, saved_game: &str) -> Result<Self, Error> {
+
+        let game = match schema::game_from_json(saved_game) {
+            Ok(game) => game,
+            Err(err) => return Err(Error::SavedGameError(err))
+        };
+
+        Self::with_game(lobby, lobby_agent_keys, game, None).await
+    }
+
+    async fn with_game(lobby: &Lobby, lobby_agent_keys: Vec<(AgentId, AgentKey)>, game: Game, seed_reveal: Option<SeedReveal>) -> Result<Self, Error> {
+        let runner_id = RunnerId(rand::thread_rng().next_u64());
+        let span = tracing::info_span!("game", %runner_id, lobby_id = %lobby.id);
+        span.in_scope(|| tracing::info!(game_mode = %lobby.game_mode, "created a runner from the lobby"));
+
+        // a client that falls this far behind is sent the whole state again rather than the
+        // mutations it missed
+        let (events_tx, _) = broadcast::channel(64);
+        let (spectators_tx, _) = broadcast::channel(64);
+
         let mut runner = Self {
-            runner_id: RunnerId(rand::thread_rng().next_u64()),
+            runner_id,
             migration_state: None,
-            run_tx: Default::default(),
+            events_tx,
+            spectators_tx,
             game,
+            seed_reveal,
+            mutation_history: Vec::new(),
+            started: false,
+            span,
         };
 
         runner.migration_state = match runner.begin_migration(lobby, lobby_agent_keys).await {
@@ -83,53 +291,207 @@ impl Runner {
         Ok(runner)
     }
 
-    pub fn run(&self) {
-        println!("run game server!");
-        self.run_tx.as_ref().unwrap().send(()).unwrap();
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// the commitment to the server's part of the game's seed, if the runner started the game
+    pub fn seed_commitment(&self) -> Option<SeedCommitment> {
+        self.seed_reveal.as_ref().map(|seed_reveal| SeedCommitment::of(&seed_reveal.server_seed))
+    }
+
+    /// the commitment to everything the game's seed was made from, if the runner started the game
+    pub fn seed_inputs_commitment(&self) -> Option<SeedCommitment> {
+        self.seed_reveal.as_ref().map(|seed_reveal| seed_reveal.inputs_commitment())
+    }
+
+    /// whether every client has connected, after which the game runs until it's over
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// whether the game is being played, and so can be watched
+    pub fn is_spectatable(&self) -> bool {
+        self.started && !self.game.is_over()
+    }
+
+    /// Starts watching the game, returning the state as spectators see it right now along with
+    /// what happens next. Spectators can't take actions, they have no client key to act with.
+    pub fn spectate(&self) -> (State, broadcast::Receiver<SpectatorEvent>) {
+        self.span.in_scope(|| tracing::info!(spectators = self.spectators_tx.receiver_count() + 1, "a spectator joined"));
+        (self.game.state.visible_to_spectators(), self.spectators_tx.subscribe())
+    }
+
+    pub fn run(&mut self) {
+        self.started = true;
+        let _span = self.span.enter();
+        tracing::info!("all clients connected, running the game");
+        self.events_tx.send(RunnerEvent::Started).ok();
+    }
+
+    /// the agents' seats, empty until the lobby has been migrated
+    pub fn seats(&self) -> &[Seat] {
+        self.migration_state.as_ref()
+            .map(|migration_state| migration_state.seats.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// the player the client plays as
+    pub fn client_player(&self, client_key: ClientKey) -> Result<PlayerId, Error> {
+        self.seats().iter()
+            .find(|seat| seat.client_key == client_key)
+            .map(|seat| seat.player_id)
+            .ok_or(Error::UnknownClient(client_key))
+    }
+
+    /// the game as the client's player sees it, which is what gets sent to the client
+    pub fn client_view(&self, client_key: ClientKey) -> Result<ClientView, Error> {
+        let player_id = self.client_player(client_key)?;
+
+        let mut valid_actions: Vec<Action> = self.game.valid_actions()
+            .into_iter()
+            .filter(|a| a.issuer_player_id() == player_id)
+            .collect();
+        valid_actions.sort();
+
+        Ok(ClientView {
+            player_id,
+            state: self.game.state.visible_to(player_id),
+            valid_actions,
+        })
+    }
+
+    /// Applies an action submitted by a client, as long as it's issued by the client's own player
+    /// and is one of the valid actions. The mutations it caused are broadcast to every client, as
+    /// its player sees them, along with the result if it ended the game.
+    pub fn apply_client_action(&mut self, client_key: ClientKey, action: Action) -> Result<Vec<StaticStateMutation>, Error> {
+        let _span = self.span.enter();
+        let player_id = self.client_player(client_key)?;
+
+        if action.issuer_player_id() != player_id {
+            return Err(Error::NotTheClientsPlayer { player_id, issuer_player_id: action.issuer_player_id() });
+        }
+
+        if self.game.is_over() {
+            return Err(Error::GameIsOver);
+        }
+
+        if !self.game.valid_actions().contains(&action) {
+            return Err(Error::InvalidAction(action));
+        }
+
+        let mutations = self.game.apply_action(action).map_err(Error::ActionFailed)?;
+        tracing::debug!(%client_key, %player_id, mutations = mutations.len(), "applied a client's action");
+        self.mutation_history.push(mutations.clone());
+
+        // card ids would tell the other players which cards were moved where they can't see them
+        let visible_mutations = self.seats().iter()
+            .map(|seat| (seat.player_id, mutations.iter().map(|m| m.visible_to(seat.player_id)).collect()))
+            .collect();
+        self.events_tx.send(RunnerEvent::Mutations(visible_mutations)).ok();
+
+        // the state is only filtered for spectators when there are any
+        if self.spectators_tx.receiver_count() > 0 {
+            let state = self.game.state.visible_to_spectators();
+            let mutations = mutations.iter().map(|m| m.visible_to_spectators()).collect();
+            self.spectators_tx.send(SpectatorEvent::Update { mutations, state }).ok();
+        }
+
+        if let Some(result) = &self.game.state.result {
+            tracing::info!(%result, "the game is over");
+            self.events_tx.send(RunnerEvent::GameOver(result.clone(), self.seed_reveal.clone())).ok();
+            self.spectators_tx.send(SpectatorEvent::GameOver {
+                result: result.clone(),
+                actions: self.game.action_history.clone(),
+                seed_reveal: self.seed_reveal.clone(),
+            }).ok();
+        }
+
+        Ok(mutations)
     }
 
     pub fn ready(&self) -> bool {
         if let Some(migration_state) = &self.migration_state {
-            let connected_clients_count = migration_state.clients.iter().filter(|(_, connected)| **connected).count();
+            let connected_clients_count = migration_state.clients.values().filter(|c| **c == Connection::Connected).count();
             connected_clients_count == migration_state.migration_keys.len()
         } else {
             false
         }
     }
 
-    pub fn connect_client(&mut self, client_key: ClientKey) -> tokio::sync::broadcast::Receiver<()> {
-        if let Some(migration_state) = &mut self.migration_state {
-            if let Some((_, client_key)) = migration_state.migration_keys.iter().find(|(_, k)| **k == client_key) {
-                migration_state.clients.insert(*client_key, true);
-
-                let rx = if let Some(ready_tx) = &mut self.run_tx {
-                    ready_tx.subscribe()
-                } else {
-                    let (tx, rx) = tokio::sync::broadcast::channel(1);
-                    self.run_tx = Some(tx);
-                    rx
-                };
+    /// Connects one of the migrated clients, which can be done again after it has disconnected for
+    /// as long as the runner is around. The game starts once every client has connected. A client
+    /// reconnecting to a started game is given the mutations it missed while it was away. Once the
+    /// game is over, clients can connect as often as they like to be told how it ended.
+    pub fn connect_client(&mut self, client_key: ClientKey) -> Result<ClientConnection, Error> {
+        let player_id = self.client_player(client_key)?;
+        let game_over = self.game_over();
+        let migration_state = self.migration_state.as_mut().ok_or(Error::NotMigrated)?;
 
-                if self.ready() {
-                    self.run();
-                }
+        let previous = migration_state.clients.get(&client_key).copied();
+        if game_over.is_none() {
+            if previous == Some(Connection::Connected) {
+                return Err(Error::AlreadyConnected(client_key));
+            }
+            migration_state.clients.insert(client_key, Connection::Connected);
+        }
 
-                return rx;
+        let missed_mutations = match previous {
+            Some(Connection::Disconnected { actions_seen }) => {
+                self.mutation_history[actions_seen..].iter()
+                    .flatten()
+                    .map(|m| m.visible_to(player_id))
+                    .collect()
             }
+            _ => Vec::new(),
+        };
 
-            panic!("invalid client");
+        self.span.in_scope(|| tracing::info!(%client_key, %player_id, missed_mutations = missed_mutations.len(), "a client connected"));
 
-        } else {
-            panic!("no migration state")
+        let events_rx = self.events_tx.subscribe();
+
+        // once the game is over, there's nothing left to start or to tell the other clients
+        if game_over.is_none() {
+            if self.started {
+                self.events_tx.send(RunnerEvent::PlayerReturned(player_id)).ok();
+            } else if self.ready() {
+                self.run();
+            }
         }
+
+        Ok(ClientConnection { player_id, events_rx, missed_mutations, game_over })
     }
 
-    pub fn disconnect_client(&mut self, client_key: ClientKey) {
-        if let Some(migration_state) = &mut self.migration_state {
-            migration_state.clients.remove(&client_key).expect("a removed client");
-        } else {
-            panic!("no migration state")
+    /// how the game ended, if it's over
+    pub fn game_over(&self) -> Option<GameOver> {
+        let result = self.game.state.result.as_ref()?;
+        Some(GameOver {
+            result: result.clone(),
+            actions: self.game.action_history.clone(),
+            seed_reveal: self.seed_reveal.clone(),
+        })
+    }
+
+    /// Marks the client as disconnected, it keeps its seat and can connect again later. Nothing
+    /// is done for its player in the meantime, so when it's the player's turn to act, the game
+    /// waits for the client to come back, and the other clients are told the player is away.
+    pub fn disconnect_client(&mut self, client_key: ClientKey) -> Result<(), Error> {
+        let player_id = self.client_player(client_key)?;
+        let migration_state = self.migration_state.as_mut().ok_or(Error::NotMigrated)?;
+
+        let actions_seen = self.mutation_history.len();
+        match migration_state.clients.get_mut(&client_key) {
+            Some(connection @ Connection::Connected) => *connection = Connection::Disconnected { actions_seen },
+            _ => return Err(Error::NotConnected(client_key)),
         }
+
+        self.span.in_scope(|| tracing::info!(%client_key, %player_id, "a client disconnected"));
+
+        if self.started {
+            self.events_tx.send(RunnerEvent::PlayerAway(player_id)).ok();
+        }
+
+        Ok(())
     }
 
 
@@ -140,11 +502,29 @@ impl Runner {
             map
         });
 
-        for (agent_id, agent_key) in &lobby_agent_keys {
+        let agent_ids: Vec<AgentId> = lobby_agent_keys.iter().map(|(agent_id, _)| *agent_id).collect();
+        let player_ids = assign_seats(&self.game.state, &lobby.seating, &agent_ids, &mut rand::thread_rng())?;
+
+        let seats: Vec<Seat> = lobby_agent_keys.iter()
+            .map(|(agent_id, agent_key)| Seat {
+                agent_id: *agent_id,
+                client_key: *migration_keys.get(agent_key).expect("a migration key for this agent"),
+                player_id: player_ids[agent_id],
+            })
+            .collect();
+
+        for seat in &seats {
+            self.span.in_scope(|| tracing::debug!(agent_id = %seat.agent_id, player_id = %seat.player_id, "seated an agent"));
+        }
+
+        for ((agent_id, agent_key), seat) in lobby_agent_keys.iter().zip(&seats) {
             let info = MigrationInfo {
                 runner_id: self.runner_id,
                 agent_key: *agent_key,
-                client_key: *migration_keys.get(agent_key).expect("a migration key for this agent")
+                client_key: seat.client_key,
+                player_id: seat.player_id,
+                seed_commitment: self.seed_commitment(),
+                seed_inputs_commitment: self.seed_inputs_commitment(),
             };
 
             match lobby.send_event(LobbyEvent::Migrate(*agent_id, info)).await {
@@ -157,10 +537,329 @@ impl Runner {
 
         let migration_state = MigrationState {
             migration_keys,
-            clients: Default::default()
+            clients: Default::default(),
+            seats,
         };
 
         Ok(migration_state)
     }
 
-}
\ No newline at end of file
+}
+
+/// Gives each agent a player of the game. The seats the host chose are taken first, then the rest
+/// of the agents are seated on the players left, in a random order. Every player needs an agent,
+/// as the game can't go on without its players acting.
+fn assign_seats(state: &State, seating: &Seating, agent_ids: &[AgentId], rng: &mut impl Rng) -> Result<HashMap<AgentId, PlayerId>, Error> {
+    let mut free_players: Vec<PlayerId> = state.players().map(|p| p.id).collect();
+    free_players.sort_by_key(|player_id| player_id.0);
+
+    let mut seats = HashMap::new();
+
+    let mut unseated = Vec::new();
+    for agent_id in agent_ids {
+        let chosen = match seating {
+            Seating::Chosen(chosen) => chosen.get(agent_id),
+            Seating::Random => None,
+        };
+
+        let Some(player_id) = chosen else {
+            unseated.push(*agent_id);
+            continue;
+        };
+
+        let idx = free_players.iter().position(|p| p == player_id)
+            .ok_or(Error::InvalidSeat { agent_id: *agent_id, player_id: *player_id })?;
+        seats.insert(*agent_id, free_players.remove(idx));
+    }
+
+    if let Some(agent_id) = unseated.get(free_players.len()) {
+        return Err(Error::NoSeatLeft(*agent_id));
+    }
+
+    if unseated.len() < free_players.len() {
+        return Err(Error::EmptySeats(free_players.len() - unseated.len()));
+    }
+
+    unseated.shuffle(rng);
+    seats.extend(unseated.into_iter().zip(free_players));
+
+    Ok(seats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use tokio::sync::mpsc::Receiver;
+    use algomancer_gre::game::{Game, GameOptions};
+    use algomancer_gre::game::action::ActionType;
+    use algomancer_gre::game::state::card::CardId;
+    use algomancer_gre::game::state::GameMode;
+    use algomancer_gre::game::state::mutation::StaticStateMutation;
+    use algomancer_gre::game::state::player::PlayerId;
+    use algomancer_gre::game::state::team_configuration::TeamConfiguration;
+    use crate::{AgentId, Coordinator, LobbyEvent, Seating};
+    use crate::client::ClientKey;
+    use crate::runner::{assign_seats, Error, RunnerEvent, SpectatorEvent};
+
+    async fn client_key(rx: &mut Receiver<LobbyEvent>) -> ClientKey {
+        loop {
+            if let Some(LobbyEvent::Migrate(_, info)) = rx.recv().await {
+                return info.client_key;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runner_plays_client_actions() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let mut pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        let mut runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+        let jim = client_key(&mut jim_rx).await;
+        let pam = client_key(&mut pam_rx).await;
+
+        let mut jim_events = runner.connect_client(jim).unwrap().events_rx;
+        assert!(!runner.ready());
+        let mut pam_events = runner.connect_client(pam).unwrap().events_rx;
+        assert!(matches!(jim_events.recv().await, Ok(RunnerEvent::Started)));
+        assert!(matches!(pam_events.recv().await, Ok(RunnerEvent::Started)));
+
+        let jim_view = runner.client_view(jim).unwrap();
+        let pam_view = runner.client_view(pam).unwrap();
+        assert_ne!(jim_view.player_id, pam_view.player_id);
+        assert!(jim_view.valid_actions.iter().all(|a| a.issuer_player_id() == jim_view.player_id));
+
+        // a client can't act for another player
+        let pam_action = pam_view.valid_actions[0].clone();
+        assert!(matches!(
+            runner.apply_client_action(jim, pam_action.clone()),
+            Err(Error::NotTheClientsPlayer { .. })
+        ));
+
+        let mutations = runner.apply_client_action(pam, pam_action.clone()).unwrap();
+        assert!(matches!(jim_events.recv().await, Ok(RunnerEvent::Mutations(m)) if m[&jim_view.player_id].len() == mutations.len()));
+
+        // the action has been taken, so it isn't valid anymore
+        assert!(matches!(runner.apply_client_action(pam, pam_action), Err(Error::InvalidAction(_))));
+        assert!(matches!(runner.client_view(ClientKey(0)), Err(Error::UnknownClient(_))));
+    }
+
+    #[tokio::test]
+    async fn test_runner_hides_opponents_cards() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let mut pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        let mut runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+        let jim = client_key(&mut jim_rx).await;
+        let pam = client_key(&mut pam_rx).await;
+        runner.connect_client(jim).unwrap();
+        runner.connect_client(pam).unwrap();
+
+        // pass priority until pam can draft
+        let draft = loop {
+            let pam_view = runner.client_view(pam).unwrap();
+            if let Some(draft) = pam_view.valid_actions.iter().find(|a| matches!(a.action_type(), ActionType::Draft(_))) {
+                break draft.clone();
+            }
+
+            let (client, action) = [jim, pam].into_iter()
+                .find_map(|c| runner.client_view(c).unwrap().valid_actions.into_iter().find(|a| matches!(a.action_type(), ActionType::PassPriority(_))).map(|a| (c, a)))
+                .unwrap();
+            runner.apply_client_action(client, action).unwrap();
+        };
+
+        // jim loses the connection while pam drafts, and catches up after
+        runner.disconnect_client(jim).unwrap();
+        let mut jim_events = runner.events_tx.subscribe();
+        runner.apply_client_action(pam, draft).unwrap();
+
+        let moved_card_ids = |mutations: &[StaticStateMutation]| -> Vec<CardId> {
+            mutations.iter()
+                .filter_map(|m| match m {
+                    StaticStateMutation::MoveCard(m) => Some(m.card_id),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // the drafted cards move between pam's hand and pack, which jim can't see
+        let drafted = match jim_events.recv().await {
+            Ok(RunnerEvent::Mutations(mutations)) => {
+                let pam_moved = moved_card_ids(&mutations[&runner.client_player(pam).unwrap()]);
+                let jim_moved = moved_card_ids(&mutations[&runner.client_player(jim).unwrap()]);
+                assert!(!pam_moved.is_empty() && !pam_moved.contains(&CardId(0)));
+                assert_eq!(jim_moved, vec![CardId(0); pam_moved.len()]);
+                pam_moved.len()
+            }
+            event => panic!("expected the draft's mutations, got {:?}", event),
+        };
+
+        // nor are they given away by what jim missed
+        let missed_mutations = runner.connect_client(jim).unwrap().missed_mutations;
+        assert_eq!(moved_card_ids(&missed_mutations), vec![CardId(0); drafted]);
+    }
+
+    #[test]
+    fn test_assign_seats() {
+        let mut game_mode = GameMode::new_player_mode();
+        if let GameMode::LiveDraft { team_configuration, .. } = &mut game_mode {
+            *team_configuration = TeamConfiguration::two_v_two();
+        }
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode }).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // two agents can't play a game for four, nothing would act for the other two players
+        let agent_ids = [AgentId(1), AgentId(2)];
+        assert!(matches!(assign_seats(&game.state, &Seating::Random, &agent_ids, &mut rng), Err(Error::EmptySeats(2))));
+
+        // the chosen seats are kept, and the rest of the agents get the players left
+        let agent_ids = [AgentId(1), AgentId(2), AgentId(3), AgentId(4)];
+        let chosen = Seating::Chosen(HashMap::from([(AgentId(3), PlayerId(1)), (AgentId(4), PlayerId(3))]));
+        let seats = assign_seats(&game.state, &chosen, &agent_ids, &mut rng).unwrap();
+        assert_eq!(seats[&AgentId(3)], PlayerId(1));
+        assert_eq!(seats[&AgentId(4)], PlayerId(3));
+        assert_eq!(seats.values().collect::<HashSet<_>>().len(), 4);
+
+        let chosen = Seating::Chosen(HashMap::from([(AgentId(1), PlayerId(5))]));
+        assert!(matches!(assign_seats(&game.state, &chosen, &agent_ids, &mut rng), Err(Error::InvalidSeat { .. })));
+
+        let agent_ids = [AgentId(1), AgentId(2), AgentId(3), AgentId(4), AgentId(5)];
+        assert!(matches!(assign_seats(&game.state, &Seating::Random, &agent_ids, &mut rng), Err(Error::NoSeatLeft(_))));
+    }
+
+    #[tokio::test]
+    async fn test_runner_reconnect() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let mut pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        let mut runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+        let jim = client_key(&mut jim_rx).await;
+        let pam = client_key(&mut pam_rx).await;
+
+        assert!(matches!(runner.disconnect_client(jim), Err(Error::NotConnected(_))));
+        let mut jim_events = runner.connect_client(jim).unwrap().events_rx;
+        assert!(matches!(runner.connect_client(jim), Err(Error::AlreadyConnected(_))));
+        runner.connect_client(pam).unwrap();
+        assert!(runner.has_started());
+
+        // pam drops, and jim is told
+        let pam_player_id = runner.client_player(pam).unwrap();
+        runner.disconnect_client(pam).unwrap();
+        assert!(matches!(jim_events.recv().await, Ok(RunnerEvent::Started)));
+        assert!(matches!(jim_events.recv().await, Ok(RunnerEvent::PlayerAway(p)) if p == pam_player_id));
+
+        // the game goes on without pam, who is given what was missed on coming back
+        let jim_action = runner.client_view(jim).unwrap().valid_actions[0].clone();
+        let mutations = runner.apply_client_action(jim, jim_action).unwrap();
+
+        let connection = runner.connect_client(pam).unwrap();
+        let visible_mutations: Vec<StaticStateMutation> = mutations.iter().map(|m| m.visible_to(pam_player_id)).collect();
+        assert_eq!(serde_json::to_string(&connection.missed_mutations).unwrap(), serde_json::to_string(&visible_mutations).unwrap());
+        assert!(runner.has_started());
+        assert!(matches!(jim_events.recv().await, Ok(RunnerEvent::Mutations(_))));
+        assert!(matches!(jim_events.recv().await, Ok(RunnerEvent::PlayerReturned(p)) if p == pam_player_id));
+
+        assert!(matches!(runner.connect_client(ClientKey(0)), Err(Error::UnknownClient(_))));
+    }
+
+    #[tokio::test]
+    async fn test_runner_connect_after_game_over() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let mut pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        let mut runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+        let jim = client_key(&mut jim_rx).await;
+        let pam = client_key(&mut pam_rx).await;
+
+        assert!(runner.connect_client(jim).unwrap().game_over.is_none());
+        runner.connect_client(pam).unwrap();
+        runner.disconnect_client(pam).unwrap();
+
+        let concede = runner.client_view(jim).unwrap().valid_actions.into_iter()
+            .find(|a| matches!(a.action_type(), ActionType::Concede(_)))
+            .expect("a concession");
+        let mutations = runner.apply_client_action(jim, concede).unwrap();
+
+        // pam was away when the game ended, and is told how it did on coming back, as often as asked
+        for _ in 0..2 {
+            let connection = runner.connect_client(pam).unwrap();
+            assert_eq!(connection.missed_mutations.len(), mutations.len());
+
+            let game_over = connection.game_over.expect("the game to be over");
+            assert_eq!(Some(&game_over.result), runner.game().state.result.as_ref());
+            assert_eq!(game_over.actions, runner.game().action_history);
+            assert!(game_over.seed_reveal.is_some());
+        }
+
+        // jim was still connected when the game ended, and was closed on
+        assert!(runner.connect_client(jim).unwrap().game_over.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_runner_spectate() {
+        let mut coordinator = Coordinator::new();
+
+        let (_, jim_agent_key) = coordinator.create_new_agent("Jim").await.unwrap();
+        let (_, pam_agent_key) = coordinator.create_new_agent("Pam").await.unwrap();
+        let lobby_id = coordinator.create_lobby_with_host(jim_agent_key, "Lobby").await.unwrap();
+        coordinator.join_lobby(pam_agent_key, lobby_id).await.unwrap();
+
+        let mut jim_rx = coordinator.lobby_listen(jim_agent_key, lobby_id).unwrap();
+        let mut pam_rx = coordinator.lobby_listen(pam_agent_key, lobby_id).unwrap();
+
+        let mut runner = coordinator.start_game(jim_agent_key, lobby_id).await.unwrap();
+        let jim = client_key(&mut jim_rx).await;
+        let pam = client_key(&mut pam_rx).await;
+
+        assert!(!runner.is_spectatable());
+        runner.connect_client(jim).unwrap();
+        runner.connect_client(pam).unwrap();
+        assert!(runner.is_spectatable());
+
+        let (state, mut spectator_events) = runner.spectate();
+        assert!(state.players().all(|p| p.hand.iter().all(|c| c.is_hidden())));
+
+        let jim_action = runner.client_view(jim).unwrap().valid_actions[0].clone();
+        let mutations = runner.apply_client_action(jim, jim_action).unwrap();
+
+        match spectator_events.recv().await {
+            Ok(SpectatorEvent::Update { mutations: m, state }) => {
+                let visible_mutations: Vec<StaticStateMutation> = mutations.iter().map(|m| m.visible_to_spectators()).collect();
+                assert_eq!(serde_json::to_string(&m).unwrap(), serde_json::to_string(&visible_mutations).unwrap());
+                assert_eq!(state.depth, runner.game().state.depth);
+                assert!(state.players().all(|p| p.hand.iter().all(|c| c.is_hidden())));
+            }
+            event => panic!("expected an update, got {:?}", event),
+        }
+    }
+}
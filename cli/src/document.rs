@@ -0,0 +1,105 @@
+use algomancer_gre::game::Game;
+use algomancer_gre::game::encoding;
+use algomancer_gre::game::encoding::EncodingError;
+use algomancer_gre::game::schema;
+use algomancer_gre::game::schema::SchemaError;
+use algomancer_gre::game::state::State;
+use database::CardPrototypeDatabase;
+
+/// A game as read by the CLI. Normally only the state is passed around, with the cards database
+/// loaded separately, but a complete game (as written with `--include all`) is accepted as well.
+#[derive(Debug, Clone)]
+pub enum GameDocument {
+    Game(Box<Game>),
+    State(Box<State>),
+}
+
+impl GameDocument {
+    /// Parses either kind of document, upgrading documents saved by older versions. This can't be
+    /// an untagged serde enum, as buffering the content loses the precision of the rng's u128 state.
+    pub fn from_json(json: &str) -> Result<GameDocument, SchemaError> {
+        match schema::game_from_json(json) {
+            Ok(game) => return Ok(GameDocument::Game(Box::new(game))),
+            // a game from a newer version is still a game, it's not worth trying it as a state
+            Err(err @ SchemaError::TooNew { .. }) => return Err(err),
+            Err(_) => {}
+        }
+
+        let state = schema::state_from_json(json)?;
+        Ok(GameDocument::State(Box::new(state)))
+    }
+
+    /// Parses either kind of document from the binary format.
+    pub fn from_binary(bytes: &[u8]) -> Result<GameDocument, EncodingError> {
+        if let Ok(game) = encoding::from_binary::<Game>(bytes) {
+            return Ok(GameDocument::Game(Box::new(game)));
+        }
+
+        let state = encoding::from_binary::<State>(bytes)?;
+        Ok(GameDocument::State(Box::new(state)))
+    }
+
+    /// Produces the game described by this document, the database is only used if the document
+    /// doesn't contain one.
+    pub fn into_game<E>(self, cards_db: impl FnOnce() -> Result<CardPrototypeDatabase, E>) -> Result<Game, E> {
+        match self {
+            GameDocument::Game(game) => Ok(*game),
+            GameDocument::State(state) => {
+                Ok(Game {
+                    state: *state,
+                    action_history: Vec::new(),
+                    cards_db: cards_db()?,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use algomancer_gre::game::{encoding, Game, GameOptions};
+    use algomancer_gre::game::schema::{SCHEMA_VERSION, SchemaError};
+    use algomancer_gre::game::state::GameMode;
+    use database::CardPrototypeDatabase;
+    use crate::document::GameDocument;
+
+    #[test]
+    fn test_state_document_round_trip() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        let state_json = serde_json::to_string(&game.state).unwrap();
+        let document = GameDocument::from_json(&state_json).unwrap();
+        assert!(matches!(document, GameDocument::State(_)));
+
+        // the reloaded database has to agree with the state on prototype ids
+        let reloaded = document.into_game(CardPrototypeDatabase::core).unwrap();
+        assert_eq!(game.valid_actions(), reloaded.valid_actions());
+
+        let game_json = serde_json::to_string(&game).unwrap();
+        assert!(matches!(GameDocument::from_json(&game_json).unwrap(), GameDocument::Game(_)));
+    }
+
+    #[test]
+    fn test_newer_document_is_rejected() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        let current = format!(r#""schema_version":{}"#, SCHEMA_VERSION);
+        let newer = format!(r#""schema_version":{}"#, SCHEMA_VERSION + 1);
+
+        for json in [serde_json::to_string(&game).unwrap(), serde_json::to_string(&game.state).unwrap()] {
+            let result = GameDocument::from_json(&json.replacen(&current, &newer, 1));
+            assert!(matches!(result, Err(SchemaError::TooNew { .. })));
+        }
+    }
+
+    #[test]
+    fn test_binary_document() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        let state_bytes = encoding::to_binary(&game.state).unwrap();
+        assert!(matches!(GameDocument::from_binary(&state_bytes).unwrap(), GameDocument::State(_)));
+
+        let game_bytes = encoding::to_binary(&game).unwrap();
+        assert!(matches!(GameDocument::from_binary(&game_bytes).unwrap(), GameDocument::Game(_)));
+    }
+}
@@ -1,9 +1,15 @@
 use std::collections::HashSet;
 use std::{fs, io};
+use std::fs::OpenOptions;
+use std::io::Write;
+use serde::Serialize;
 
 use algomancer_gre::game::{Game, GameOptions};
 use algomancer_gre::game::action::{Action};
+use algomancer_gre::game::encoding;
+use algomancer_gre::game::encoding::EncodingError;
 use algomancer_gre::game::game_builder::NewGameError;
+use algomancer_gre::game::schema::SchemaError;
 use algomancer_gre::game::state::error::StateError;
 use algomancer_gre::game::state::{GameMode};
 use algocore::Faction;
@@ -11,37 +17,69 @@ use algomancer_gre::game::state::team_configuration::TeamConfiguration;
 use clap::Parser;
 use algomancer_gre::game::state::mutation::StaticStateMutation;
 use thiserror::Error;
-use crate::parser::{Cli, Commands, Include};
+use tracing_subscriber::EnvFilter;
+use database::{CardPrototypeDatabase, DbError};
+use crate::document::GameDocument;
+use crate::parser::{Cli, Commands, Format, Include};
+use crate::parser::show::ShowArgs;
+use crate::parser::diff::DiffArgs;
+use crate::parser::validate::ValidateArgs;
+use algomancer_gre::game::state::player::PlayerId;
 use crate::parser::actions::{ActionsCommand, ApplyActionArgs, ListActionsArgs};
 use crate::parser::new::{FactionArg, GameModeCommand, LiveDraftArgs, Mode, NewArgs};
 
 mod parser;
 mod json_value_parser;
+mod document;
+mod play;
 
 fn main() -> Result<(), CLIError> {
+    // the engine's logs go to stderr, quiet unless asked for with RUST_LOG, e.g. RUST_LOG=debug
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")))
+        .init();
+
     let args = Cli::parse();
+    let format = args.format;
 
     match args.command {
         Commands::New(args) => {
             match args.output_file {
                 None => {
-                    print_new_game(&args)?;
+                    print_new_game(&args, format)?;
                     Ok(())
                 }
                 Some(_) => {
-                    write_new_game_to_file(&args)?;
+                    write_new_game_to_file(&args, format)?;
                     Ok(())
                 }
             }
         }
+        Commands::Show(args) => {
+            show_game(&args)?;
+            Ok(())
+        }
+        Commands::Play(args) => {
+            play::play(&args, format)?;
+            Ok(())
+        }
+        Commands::Diff(args) => {
+            diff_games(&args)?;
+            Ok(())
+        }
+        Commands::Validate(args) => {
+            validate_game(&args)?;
+            Ok(())
+        }
         Commands::Action(args) => {
             match args.command {
-                ActionsCommand::List(args) => {
-                    list_actions(&args)?;
+                ActionsCommand::List(list_args) => {
+                    list_actions(&list_args, &args.db, format)?;
                     Ok(())
                 }
                 ActionsCommand::Apply(apply_args) => {
-                    apply_action(apply_args, &args.include)?;
+                    apply_action(apply_args, &args.include, &args.db, format)?;
                     Ok(())
                 }
             }
@@ -55,11 +93,20 @@ pub enum CLIError {
     FailedToSerializeGame(serde_json::Error),
 
     #[error("Failed to deserialize game data: {0}")]
-    FailedToDeserializeGame(serde_json::Error),
+    FailedToDeserializeGame(SchemaError),
+
+    #[error("Failed to encode game data: {0}")]
+    FailedToEncode(EncodingError),
+
+    #[error("Failed to decode game data: {0}")]
+    FailedToDecode(EncodingError),
 
     #[error("Failed to initialize new game: {0}")]
     FailedToInitializeGame(NewGameError),
 
+    #[error("Failed to load the cards database: {0:?}")]
+    FailedToLoadDatabase(DbError),
+
     #[error("Failed to serialize actions: {0}")]
     FailedToSerializeActions(serde_json::Error),
 
@@ -72,64 +119,100 @@ pub enum CLIError {
     #[error("Invalid arguments: {0}")]
     InvalidArgs(&'static str),
 
+    #[error("The state is inconsistent, with {0} invariant violation(s)")]
+    InvalidState(usize),
+
     #[error("Feature not implemented")]
     NotImplemented,
 }
 
 
 /// creates a new game instance, serializes it, and prints it to stdout
-fn print_new_game(args: &NewArgs) -> Result<(), CLIError> {
-    let options = game_options_from_new_args(args)?;
-    let game = Game::new(&options);
+fn print_new_game(args: &NewArgs, format: Format) -> Result<(), CLIError> {
+    let game = new_game(args)?;
+    let game_serialized = serialize_game(&game, &args.include, format)?;
+    print_serialized(&game_serialized, format)
+}
 
-    match game {
-        Ok(game) => {
-            let game_json = serialize_game(&game, &args.include)?;
-            println!("{}", game_json);
-            Ok(())
-        }
-        Err(err) => {
-            Err(CLIError::FailedToInitializeGame(err))
-        }
+fn write_mutations_to_file(mutations: &Vec<StaticStateMutation>, path: &String, format: Format) -> Result<(), CLIError> {
+    let mutations_serialized = serialize_mutations(mutations, format)?;
+    match fs::write(path, mutations_serialized) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(CLIError::IoError(err))
     }
 }
 
-fn write_mutations_to_file(mutations: &Vec<StaticStateMutation>, path: &String) -> Result<(), CLIError> {
-    let game_json = serialize_mutations(mutations)?;
-    match fs::write(path, game_json) {
+fn write_game_to_file(game: &Game, path: &String, include: &Include, format: Format) -> Result<(), CLIError> {
+    let game_serialized = serialize_game(game, include, format)?;
+    match fs::write(path, game_serialized) {
         Ok(_) => Ok(()),
         Err(err) => Err(CLIError::IoError(err))
     }
 }
 
-fn write_game_to_file(game: &Game, path: &String, include: &Include) -> Result<(), CLIError> {
-    let game_json = serialize_game(game, include)?;
-    match fs::write(path, game_json) {
+fn write_new_game_to_file(args: &NewArgs, format: Format) -> Result<(), CLIError> {
+    let game = new_game(args)?;
+    let out_file = args.output_file.as_ref().expect("an output file");
+    write_game_to_file(&game, out_file, &args.include, format)
+}
+
+/// writes serialized data to stdout, json gets a line of its own, binary data is written as is
+fn print_serialized(serialized: &[u8], format: Format) -> Result<(), CLIError> {
+    let mut stdout = io::stdout().lock();
+    let result = stdout.write_all(serialized).and_then(|_| match format {
+        Format::Json => writeln!(stdout),
+        Format::Binary => Ok(()),
+    });
+
+    match result {
         Ok(_) => Ok(()),
         Err(err) => Err(CLIError::IoError(err))
     }
 }
 
-fn write_new_game_to_file(args: &NewArgs) -> Result<(), CLIError> {
+fn new_game(args: &NewArgs) -> Result<Game, CLIError> {
     let options = game_options_from_new_args(args)?;
-    let game = Game::new(&options);
+    let cards_db = load_cards_db(&args.db)?;
 
-    match game {
-        Ok(game) => {
-            let out_file = args.output_file.as_ref().expect("an output file");
-            write_game_to_file(&game, out_file, &args.include)
-        }
+    let game = match Game::new_with_db(&options, cards_db) {
+        Ok(game) => game,
         Err(err) => {
-            Err(CLIError::FailedToInitializeGame(err))
+            return Err(CLIError::FailedToInitializeGame(err));
         }
+    };
+
+    // start the history off empty, actions get appended to it as they're applied
+    if let Some(path) = &args.history_file {
+        if let Err(err) = fs::write(path, "") {
+            return Err(CLIError::IoError(err));
+        }
+    }
+
+    Ok(game)
+}
+
+/// loads the cards database from the given path, or the bundled core set database if there is none
+fn load_cards_db(path: &Option<String>) -> Result<CardPrototypeDatabase, CLIError> {
+    let result = match path {
+        None => CardPrototypeDatabase::core(),
+        Some(path) => CardPrototypeDatabase::from_path(path),
+    };
+
+    match result {
+        Ok(cards_db) => Ok(cards_db),
+        Err(err) => Err(CLIError::FailedToLoadDatabase(err)),
     }
 }
 
 /// converts the args from the 'new' command into a GameOptions instance
 fn game_options_from_new_args(args: &NewArgs) -> Result<GameOptions, CLIError> {
-    let seed_bytes = args.seed.to_be_bytes();
+    game_options(args.seed, &args.game_mode)
+}
+
+fn game_options(seed: u128, game_mode: &GameModeCommand) -> Result<GameOptions, CLIError> {
+    let seed_bytes = seed.to_be_bytes();
 
-    match &args.game_mode {
+    match game_mode {
         GameModeCommand::LiveDraft(args) => {
             let LiveDraftArgs { factions: faction_args, mode } = args;
             let game_options = GameOptions {
@@ -167,39 +250,37 @@ fn game_options_from_new_args(args: &NewArgs) -> Result<GameOptions, CLIError> {
     }
 }
 
-fn serialize_game(game: &Game, _output: &Include) -> Result<String, CLIError> {
-    let game_serialized = serde_json::to_string(game);
-    match game_serialized {
-        Ok(game_serialized) => Ok(game_serialized),
-        Err(err) => Err(CLIError::FailedToSerializeGame(err)),
+fn serialize_game(game: &Game, include: &Include, format: Format) -> Result<Vec<u8>, CLIError> {
+    match include {
+        Include::All => serialize(game, format, CLIError::FailedToSerializeGame),
+        Include::State => serialize(&game.state, format, CLIError::FailedToSerializeGame),
+        Include::Database => serialize(&game.cards_db, format, CLIError::FailedToSerializeGame),
+        Include::History => serialize(&game.action_history, format, CLIError::FailedToSerializeGame),
     }
 }
 
-fn serialize_actions(actions: &HashSet<Action>) -> Result<String, CLIError> {
-    let action_serialized: Result<String, serde_json::Error> = serde_json::to_string(actions);
-    match action_serialized {
-        Ok(action_serialized) => Ok(action_serialized),
-        Err(err) => Err(CLIError::FailedToSerializeActions(err)),
-    }
+fn serialize_actions(actions: &HashSet<Action>, format: Format) -> Result<Vec<u8>, CLIError> {
+    serialize(actions, format, CLIError::FailedToSerializeActions)
 }
 
-fn serialize_mutations(mutations: &Vec<StaticStateMutation>) -> Result<String, CLIError> {
-    let mutations_serialized = serde_json::to_string(mutations);
-    match mutations_serialized {
-        Ok(mutations_serialized) => Ok(mutations_serialized),
-        Err(err) => Err(CLIError::FailedToSerializeGame(err)),
+fn serialize_mutations(mutations: &Vec<StaticStateMutation>, format: Format) -> Result<Vec<u8>, CLIError> {
+    serialize(mutations, format, CLIError::FailedToSerializeGame)
+}
+
+/// serializes the value in the given format, json errors are reported with `json_error`
+fn serialize<T: Serialize + ?Sized>(value: &T, format: Format, json_error: fn(serde_json::Error) -> CLIError) -> Result<Vec<u8>, CLIError> {
+    match format {
+        Format::Json => serde_json::to_vec(value).map_err(json_error),
+        Format::Binary => encoding::to_binary(value).map_err(CLIError::FailedToEncode),
     }
 }
 
-fn list_actions(args: &ListActionsArgs) -> Result<(), CLIError> {
-    let write = |actions_json| {
+fn list_actions(args: &ListActionsArgs, db: &Option<String>, format: Format) -> Result<(), CLIError> {
+    let write = |actions_serialized: Vec<u8>, format: Format| {
         match &args.output_file {
-            None => {
-                println!("{}", actions_json);
-                Ok(())
-            }
+            None => print_serialized(&actions_serialized, format),
             Some(path) => {
-                match fs::write(path, actions_json) {
+                match fs::write(path, actions_serialized) {
                     Ok(_) => Ok(()),
                     Err(err) => Err(CLIError::IoError(err))
                 }
@@ -207,22 +288,28 @@ fn list_actions(args: &ListActionsArgs) -> Result<(), CLIError> {
         }
     };
 
-    match &args.state {
-        None => {
-            let state = read_state_file(&args.state_file)?;
-            let actions = state.valid_actions();
-            let actions_json = serialize_actions(&actions)?;
-            write(actions_json)
-        }
-        Some(state) => {
-            let actions = state.valid_actions();
-            let actions_json = serialize_actions(&actions)?;
-            write(actions_json)
-        }
+    let game = read_game(args.state.clone(), &args.state_file, db)?;
+    let actions = game.valid_actions();
+    if args.pretty {
+        let lines: Vec<String> = actions.iter().map(|a| a.describe(&game.state, &game.cards_db)).collect();
+        return write(lines.join("\n").into_bytes(), Format::Json);
     }
+
+    let actions_serialized = serialize_actions(&actions, format)?;
+    write(actions_serialized, format)
+}
+
+/// reads the game from the state passed in directly, or from the state file
+fn read_game(state: Option<GameDocument>, state_file: &Option<String>, db: &Option<String>) -> Result<Game, CLIError> {
+    let document = match state {
+        Some(document) => document,
+        None => read_state_file(state_file)?,
+    };
+
+    document.into_game(|| load_cards_db(db))
 }
 
-fn read_state_file(path: &Option<String>) -> Result<Game, CLIError> {
+fn read_state_file(path: &Option<String>) -> Result<GameDocument, CLIError> {
     // this is required to be set if state is not
     let state_file_path = match path.as_ref() {
         Some(path) => path,
@@ -232,17 +319,22 @@ fn read_state_file(path: &Option<String>) -> Result<Game, CLIError> {
         }
     };
 
-    let file_contents = match fs::read_to_string(state_file_path) {
+    let file_contents = match fs::read(state_file_path) {
         Ok(contents) => contents,
         Err(err) => {
             return Err(CLIError::IoError(err));
         }
     };
 
-    let game: Result<Game, serde_json::Error> = serde_json::from_str(&file_contents);
-    match game {
-        Ok(game) => {
-            Ok(game)
+    // files are read in whichever format they were written in
+    if encoding::is_binary(&file_contents) {
+        return GameDocument::from_binary(&file_contents).map_err(CLIError::FailedToDecode);
+    }
+
+    let document = GameDocument::from_json(&String::from_utf8_lossy(&file_contents));
+    match document {
+        Ok(document) => {
+            Ok(document)
         }
         Err(err) => {
             Err(CLIError::FailedToDeserializeGame(err))
@@ -262,19 +354,23 @@ fn read_action_file(path: &Option<String>) -> Result<Action, CLIError> {
         }
     };
 
-    let file_contents = match fs::read_to_string(action_file_path) {
+    let file_contents = match fs::read(action_file_path) {
         Ok(contents) => contents,
         Err(err) => {
             return Err(CLIError::IoError(err));
         }
     };
 
-    let action: Action = serde_json::from_str(&file_contents).unwrap();
+    if encoding::is_binary(&file_contents) {
+        return encoding::from_binary(&file_contents).map_err(CLIError::FailedToDecode);
+    }
+
+    let action: Action = serde_json::from_slice(&file_contents).unwrap();
 
     Ok(action)
 }
 
-fn apply_action(args: ApplyActionArgs, include: &Include) -> Result<(), CLIError> {
+fn apply_action(args: ApplyActionArgs, include: &Include, db: &Option<String>, format: Format) -> Result<(), CLIError> {
     let action = match args.action {
         None => {
             read_action_file(&args.action_file)?
@@ -282,54 +378,131 @@ fn apply_action(args: ApplyActionArgs, include: &Include) -> Result<(), CLIError
         Some(action) => action
     };
 
-    match args.state {
-        None => {
-            let mut state = read_state_file(&args.state_file)?;
-            let result = state.apply_action(action.clone());
-            match result {
-                Ok(mutations) => {
-                    if let Some(path) = args.mutations_output_file {
-                        write_mutations_to_file(&mutations, &path)?
-                    };
-
-                    match args.state_output_file {
-                        None => {
-                            let game_serialized = serialize_game(&state, include)?;
-                            println!("{}", game_serialized);
-                        }
-                        Some(file) => {
-                            write_game_to_file(&state, &file, include)?;
-                        }
-                    }
+    let mut game = read_game(args.state, &args.state_file, db)?;
+    let mutations = match game.apply_action(action.clone()) {
+        Ok(mutations) => mutations,
+        Err(err) => {
+            return Err(CLIError::InvalidAction(action, err));
+        }
+    };
 
-                    Ok(())
-                }
-                Err(err) => Err(CLIError::InvalidAction(action, err)),
-            }
+    if let Some(path) = args.mutations_output_file {
+        write_mutations_to_file(&mutations, &path, format)?
+    };
+
+    if let Some(path) = args.history_file {
+        append_action_to_history(&action, &path)?
+    };
+
+    print_game_result(&game);
+
+    match args.state_output_file {
+        None => {
+            let game_serialized = serialize_game(&game, include, format)?;
+            print_serialized(&game_serialized, format)?;
+        }
+        Some(file) => {
+            write_game_to_file(&game, &file, include, format)?;
         }
-        Some(mut state) => {
-            let result = state.apply_action(action.clone());
-            match result {
-                Ok(mutations) => {
-                    if let Some(path) = args.mutations_output_file {
-                        write_mutations_to_file(&mutations, &path)?
-                    };
+    }
 
-                    let game_serialized = serialize_game(&state, include)?;
-                    println!("{}", game_serialized);
+    Ok(())
+}
 
-                    Ok(())
-                }
-                Err(err) => Err(CLIError::InvalidAction(action, err)),
-            }
+/// appends the action to the history file as a single line of json
+fn append_action_to_history(action: &Action, path: &String) -> Result<(), CLIError> {
+    let action_json = match serde_json::to_string(action) {
+        Ok(action_json) => action_json,
+        Err(err) => {
+            return Err(CLIError::FailedToSerializeActions(err));
         }
+    };
+
+    let file = OpenOptions::new().create(true).append(true).open(path);
+    let result = file.and_then(|mut file| writeln!(file, "{}", action_json));
+    match result {
+        Ok(_) => Ok(()),
+        Err(err) => Err(CLIError::IoError(err))
     }
 }
 
-/// get the unique elements of the faction args by converting to hash set and then back to vec
+/// renders the game's state as text to stdout
+fn show_game(args: &ShowArgs) -> Result<(), CLIError> {
+    let game = read_game(args.state.clone(), &args.state_file, &args.db)?;
+    let perspective = args.player.map(PlayerId);
+
+    print!("{}", game.state.render(&game.cards_db, perspective));
+
+    Ok(())
+}
+
+fn diff_games(args: &DiffArgs) -> Result<(), CLIError> {
+    let from = read_game(None, &Some(args.from_file.clone()), &args.db)?;
+    let to = read_game(None, &Some(args.to_file.clone()), &args.db)?;
+
+    let diff = from.state.diff(&to.state);
+    let mutations = diff.mutations();
+
+    if args.json {
+        let json = serde_json::json!({
+            "differences": diff.differences,
+            "mutations": mutations,
+        });
+        println!("{}", json);
+        return Ok(());
+    }
+
+    if diff.is_empty() {
+        println!("no differences");
+        return Ok(());
+    }
+
+    for difference in &diff.differences {
+        println!("{}", difference.describe(&from.cards_db));
+    }
+
+    println!();
+    println!("as mutations:");
+    for m in &mutations {
+        println!("{}", serde_json::to_string(m).map_err(CLIError::FailedToSerializeGame)?);
+    }
+
+    Ok(())
+}
+
+/// lists the state's invariant violations, failing if there are any
+fn validate_game(args: &ValidateArgs) -> Result<(), CLIError> {
+    let game = read_game(args.state.clone(), &args.state_file, &args.db)?;
+
+    let violations = game.state.validate(&game.cards_db);
+    if violations.is_empty() {
+        println!("the state is valid");
+        return Ok(());
+    }
+
+    for violation in &violations {
+        println!("{}", violation);
+    }
+
+    Err(CLIError::InvalidState(violations.len()))
+}
+
+/// reports the result to stderr once the game is over, stdout is reserved for the game state
+fn print_game_result(game: &Game) {
+    if let Some(result) = &game.state.result {
+        eprintln!("Game over: {}", result);
+    }
+}
+
+/// get the unique elements of the faction args, in the order they were first given
 fn unique_factions(factions: &[FactionArg]) -> Vec<Faction> {
-    let factions_set: HashSet<Faction> = HashSet::from_iter(factions.iter().map(|f_a| f_a.to_faction()));
-    factions_set.into_iter().collect()
+    let mut unique: Vec<Faction> = Vec::new();
+    for faction in factions.iter().map(|f_a| f_a.to_faction()) {
+        if !unique.contains(&faction) {
+            unique.push(faction);
+        }
+    }
+    unique
 }
 
 #[cfg(test)]
@@ -338,7 +511,7 @@ mod tests {
     use rand::prelude::SliceRandom;
     use rand::thread_rng;
     use algomancer_gre::game::{Game, GameOptions};
-    use algomancer_gre::game::action::{Action};
+    use algomancer_gre::game::action::{Action, ActionType};
     use algomancer_gre::game::state::GameMode;
     use algocore::Faction::{Fire, Wood};
     use algomancer_gre::game::state::team_configuration::TeamConfiguration;
@@ -368,7 +541,8 @@ mod tests {
             let actions = game.valid_actions();
             let _get_valid_duration = start.elapsed();
 
-            let mut actions_vec: Vec<Action> = actions.into_iter().collect();
+            // leave concessions out, so that the game actually gets played
+            let mut actions_vec: Vec<Action> = actions.into_iter().filter(|a| !matches!(a.action_type(), ActionType::Concede(_))).collect();
             actions_vec.sort();
 
             if actions_vec.is_empty() {
@@ -381,6 +555,11 @@ mod tests {
             let _mutations = game.apply_action(action).unwrap();
 
             let _apply_duration = start.elapsed();
+
+            if let Some(result) = &game.state.result {
+                eprintln!("{}", result);
+                break;
+            }
             //eprintln!("t_get {:?} | t_apply: {:?} | mutations {:?}\n", get_valid_duration, apply_duration, mutations)
         }
     }
@@ -1,16 +1,28 @@
 use clap::{Parser, Subcommand, ValueEnum};
 
 use crate::parser::actions::ActionsArgs;
+use crate::parser::diff::DiffArgs;
+use crate::parser::validate::ValidateArgs;
 use crate::parser::new::NewArgs;
+use crate::parser::play::PlayArgs;
+use crate::parser::show::ShowArgs;
 
 pub mod new;
 pub mod actions;
+pub mod play;
+pub mod show;
+pub mod diff;
+pub mod validate;
 
 #[derive(Debug, Parser)] // requires `derive` feature
 #[command(name = "algomancer")]
 #[command(about = "The Algomancy Game Rules Engine", long_about = None, version)]
-
 pub struct Cli {
+    /// The format games, actions and mutations are written out in. Files in either format are
+    /// read, whatever this is set to
+    #[arg(long, global = true, default_value = "json")]
+    pub format: Format,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -25,12 +37,38 @@ pub enum Commands {
     /// List actions, Apply an Action
     #[command(arg_required_else_help = true)]
     Action(ActionsArgs),
+
+    /// Play a game interactively, holding it in memory between actions
+    Play(PlayArgs),
+
+    /// Print a game's state as text
+    #[command(arg_required_else_help = true)]
+    Show(ShowArgs),
+
+    /// Compare two game states, listing what differs and the mutations that would get from one to the other
+    #[command(arg_required_else_help = true)]
+    Diff(DiffArgs),
+
+    /// Check a game's state for inconsistencies, such as duplicate card ids
+    #[command(arg_required_else_help = true)]
+    Validate(ValidateArgs),
 }
 
+/// The parts of a game written out by a command. The state is all that's needed to continue a
+/// game, the database is loaded separately and the history is kept in its own file.
 #[derive(Debug, ValueEnum, Clone)]
 pub enum Include {
+    /// The state, database and history together
     All,
     State,
     Database,
     History,
 }
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+
+    /// A compact binary format (MessagePack with a version header)
+    Binary,
+}
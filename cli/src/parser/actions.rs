@@ -1,16 +1,20 @@
 use clap::{Args, Subcommand};
 use algomancer_gre::game::action::{Action};
-use algomancer_gre::game::Game;
+use crate::document::GameDocument;
 use crate::parser::Include;
 
 #[derive(Debug, Args)]
 #[command(rename_all = "snake_case")]
 pub struct ActionsArgs {
 
-
-    #[arg(short, long, default_value="all")]
+    /// What to write out after applying an action
+    #[arg(short, long, default_value="state")]
     pub include: Include,
 
+    /// A cards database to use instead of the bundled one, when the input is state only
+    #[arg(long)]
+    pub db: Option<String>,
+
     #[command(subcommand)]
     pub command: ActionsCommand,
 }
@@ -35,11 +39,15 @@ pub struct ListActionsArgs {
     #[arg(short='o', long="out_file")]
     pub output_file: Option<String>,
 
+    /// Describe each action in a line of text instead of writing them out as json
+    #[arg(short, long)]
+    pub pretty: bool,
+
     #[arg(short='f', long, required_unless_present = "state")]
     pub state_file: Option<String>,
 
-    #[arg(required_unless_present = "state_file", value_parser = crate::json_value_parser::json_value_parser::<Game>)]
-    pub state: Option<Game>,
+    #[arg(required_unless_present = "state_file", value_parser = GameDocument::from_json)]
+    pub state: Option<GameDocument>,
 }
 
 #[derive(Debug, Args)]
@@ -51,11 +59,15 @@ pub struct ApplyActionArgs {
     #[arg(short='o', long="out_file")]
     pub state_output_file: Option<String>,
 
+    /// A file the applied action is appended to, one json action per line
+    #[arg(long="history_file")]
+    pub history_file: Option<String>,
+
     #[arg(short='f', long, required_unless_present = "state")]
     pub state_file: Option<String>,
 
-    #[arg(required_unless_present = "state_file", value_parser = crate::json_value_parser::json_value_parser::<Game>)]
-    pub state: Option<Game>,
+    #[arg(required_unless_present = "state_file", value_parser = GameDocument::from_json)]
+    pub state: Option<GameDocument>,
 
     #[arg(short='a', long, required_unless_present = "action")]
     pub action_file: Option<String>,
@@ -0,0 +1,19 @@
+use clap::Args;
+
+#[derive(Debug, Args)]
+#[command(rename_all = "snake_case")]
+pub struct DiffArgs {
+    /// The state to compare from
+    pub from_file: String,
+
+    /// The state to compare to
+    pub to_file: String,
+
+    /// A cards database to use instead of the bundled one, when the inputs are state only
+    #[arg(long)]
+    pub db: Option<String>,
+
+    /// Write the differences and the mutations they map onto as json
+    #[arg(long)]
+    pub json: bool,
+}
@@ -9,12 +9,21 @@ pub struct NewArgs {
     #[arg(short, long, default_value = "0")]
     pub seed: u128,
 
-    #[arg(short, long, default_value="all")]
+    /// What to write out for the new game
+    #[arg(short, long, default_value="state")]
     pub include: Include,
 
+    /// A cards database to use instead of the bundled one
+    #[arg(long)]
+    pub db: Option<String>,
+
     #[arg(short='o', long="out_file")]
     pub output_file: Option<String>,
 
+    /// A file to start the game's action history in, it is created empty
+    #[arg(long="history_file")]
+    pub history_file: Option<String>,
+
     /// A game mode to initialize the game with
     #[command(subcommand)]
     pub game_mode: GameModeCommand,
@@ -0,0 +1,26 @@
+use clap::Args;
+use crate::parser::new::GameModeCommand;
+
+#[derive(Debug, Args)]
+#[command(rename_all = "snake_case")]
+pub struct PlayArgs {
+    /// A saved game to continue, instead of starting a new one
+    #[arg(short='f', long)]
+    pub state_file: Option<String>,
+
+    /// A cards database to use instead of the bundled one
+    #[arg(long)]
+    pub db: Option<String>,
+
+    /// A player to seat a built-in bot for, bots play random valid actions. Can be repeated
+    #[arg(short, long = "bot")]
+    pub bots: Vec<u8>,
+
+    /// a 128 bit unsigned integer used as seed for the new game, and for the bots
+    #[arg(short, long, default_value = "0")]
+    pub seed: u128,
+
+    /// A game mode to start a new game with, when not continuing a saved game
+    #[command(subcommand)]
+    pub game_mode: Option<GameModeCommand>,
+}
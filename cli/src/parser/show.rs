@@ -0,0 +1,20 @@
+use clap::Args;
+use crate::document::GameDocument;
+
+#[derive(Debug, Args)]
+#[command(rename_all = "snake_case")]
+pub struct ShowArgs {
+    /// Render from this player's perspective, hiding the other players' hands
+    #[arg(short, long)]
+    pub player: Option<u8>,
+
+    /// A cards database to use instead of the bundled one, when the input is state only
+    #[arg(long)]
+    pub db: Option<String>,
+
+    #[arg(short='f', long, required_unless_present = "state")]
+    pub state_file: Option<String>,
+
+    #[arg(required_unless_present = "state_file", value_parser = GameDocument::from_json)]
+    pub state: Option<GameDocument>,
+}
@@ -0,0 +1,16 @@
+use clap::Args;
+use crate::document::GameDocument;
+
+#[derive(Debug, Args)]
+#[command(rename_all = "snake_case")]
+pub struct ValidateArgs {
+    /// A cards database to use instead of the bundled one, when the input is state only
+    #[arg(long)]
+    pub db: Option<String>,
+
+    #[arg(short='f', long, required_unless_present = "state")]
+    pub state_file: Option<String>,
+
+    #[arg(required_unless_present = "state_file", value_parser = GameDocument::from_json)]
+    pub state: Option<GameDocument>,
+}
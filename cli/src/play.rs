@@ -0,0 +1,307 @@
+use std::io;
+use std::io::{BufRead, Write};
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use algomancer_gre::game::Game;
+use algomancer_gre::game::action::{Action, ActionType};
+use algomancer_gre::game::state::player::PlayerId;
+use algomancer_gre::game::state::stack::Next;
+use crate::{CLIError, game_options, load_cards_db, read_state_file, serialize_game};
+use crate::parser::{Format, Include};
+use crate::parser::play::PlayArgs;
+
+const HELP: &str = "\
+<number>     apply the action with that number
+pass         pass priority, if only one player can
+ls           list the valid actions again
+show         show the whole state of the game
+undo         take back the last action, along with the bot actions that followed it
+save <file>  save the game, including its database and history
+load <file>  load a saved game
+help         show this message
+quit         leave the game";
+
+/// stops the bots from playing forever when every player is a bot
+const MAX_BOT_ACTIONS: usize = 10_000;
+
+/// An interactive game, held in memory between the player's commands.
+struct Session {
+    game: Game,
+    db_path: Option<String>,
+
+    /// the format games are saved in
+    format: Format,
+
+    /// the games as they were before each of the player's actions, most recent last
+    undo_stack: Vec<Game>,
+
+    bots: Vec<PlayerId>,
+    rng: StdRng,
+}
+
+pub fn play(args: &PlayArgs, format: Format) -> Result<(), CLIError> {
+    let game = match (&args.state_file, &args.game_mode) {
+        (Some(_), _) => {
+            read_state_file(&args.state_file)?.into_game(|| load_cards_db(&args.db))?
+        }
+        (None, Some(game_mode)) => {
+            let options = game_options(args.seed, game_mode)?;
+            match Game::new_with_db(&options, load_cards_db(&args.db)?) {
+                Ok(game) => game,
+                Err(err) => return Err(CLIError::FailedToInitializeGame(err)),
+            }
+        }
+        (None, None) => {
+            return Err(CLIError::InvalidArgs("either a game mode or a state file to continue is required"));
+        }
+    };
+
+    let mut session = Session {
+        game,
+        db_path: args.db.clone(),
+        format,
+        undo_stack: Vec::new(),
+        bots: args.bots.iter().map(|&id| PlayerId(id)).collect(),
+        rng: StdRng::seed_from_u64(args.seed as u64),
+    };
+
+    session.run(io::stdin().lock())
+}
+
+impl Session {
+    /// reads the player's commands from `input` until they quit, or the input ends
+    fn run(&mut self, mut input: impl BufRead) -> Result<(), CLIError> {
+        println!("{}", HELP);
+
+        self.run_bots()?;
+        let mut actions = self.print_turn();
+
+        loop {
+            print!("> ");
+            io::stdout().flush().map_err(CLIError::IoError)?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line).map_err(CLIError::IoError)? == 0 {
+                // end of input
+                return Ok(());
+            }
+
+            let mut words = line.split_whitespace();
+            let Some(command) = words.next() else {
+                continue
+            };
+
+            match (command, words.next()) {
+                ("quit" | "exit" | "q", _) => {
+                    return Ok(());
+                }
+                ("help" | "?", _) => {
+                    println!("{}", HELP);
+                    continue
+                }
+                ("ls", _) => {}
+                ("show", _) => {
+                    print!("{}", self.game.state.render(&self.game.cards_db, None));
+                    continue
+                }
+                ("pass", _) => {
+                    let passes: Vec<&Action> = actions.iter().filter(|a| matches!(a.action_type(), ActionType::PassPriority(_))).collect();
+                    match passes.as_slice() {
+                        [pass] => {
+                            let pass = (*pass).clone();
+                            self.apply(pass)?;
+                        }
+                        [] => {
+                            println!("nobody can pass priority right now");
+                            continue
+                        }
+                        _ => {
+                            println!("more than one player can pass priority, choose by number instead");
+                            continue
+                        }
+                    }
+                }
+                ("undo", _) => {
+                    match self.undo_stack.pop() {
+                        Some(game) => self.game = game,
+                        None => {
+                            println!("there is nothing to undo");
+                            continue
+                        }
+                    }
+                }
+                ("save", Some(path)) => {
+                    match self.save(path) {
+                        Ok(()) => println!("saved to {}", path),
+                        Err(err) => println!("the game could not be saved: {}", err),
+                    }
+                    continue
+                }
+                ("load", Some(path)) => {
+                    // a file that can't be loaded leaves the current game as it was
+                    if let Err(err) = self.load(path) {
+                        println!("the game could not be loaded: {}", err);
+                        continue
+                    }
+                    self.run_bots()?;
+                }
+                (number, _) => {
+                    let chosen = number.parse::<usize>().ok()
+                        .and_then(|n| n.checked_sub(1))
+                        .and_then(|idx| actions.get(idx));
+
+                    match chosen {
+                        Some(action) => {
+                            let action = action.clone();
+                            self.apply(action)?;
+                        }
+                        None => {
+                            println!("unknown command '{}', type 'help' for a list of commands", line.trim());
+                            continue
+                        }
+                    }
+                }
+            }
+
+            actions = self.print_turn();
+        }
+    }
+
+    fn save(&self, path: &str) -> Result<(), CLIError> {
+        let game_serialized = serialize_game(&self.game, &Include::All, self.format)?;
+        std::fs::write(path, game_serialized).map_err(CLIError::IoError)
+    }
+
+    fn load(&mut self, path: &str) -> Result<(), CLIError> {
+        let db_path = &self.db_path;
+        self.game = read_state_file(&Some(path.to_string()))?.into_game(|| load_cards_db(db_path))?;
+        self.undo_stack.clear();
+        Ok(())
+    }
+
+    /// applies the player's action, followed by any actions the bots take in response
+    fn apply(&mut self, action: Action) -> Result<(), CLIError> {
+        let previous = self.game.clone();
+
+        println!("{}", action.describe(&self.game.state, &self.game.cards_db));
+        match self.game.apply_action(action.clone()) {
+            Ok(mutations) => {
+                // each mutation is described against the state it was applied to
+                let db = &self.game.cards_db;
+                let mut state = previous.state.clone();
+                for m in mutations {
+                    println!("  - {}", m.describe(&state, db));
+                    state = state.mutate(db, &m).map_err(|err| CLIError::InvalidAction(action.clone(), err))?;
+                }
+            }
+            Err(err) => {
+                println!("the action could not be applied: {}", err);
+                return Ok(());
+            }
+        }
+
+        self.undo_stack.push(previous);
+        self.run_bots()
+    }
+
+    /// lets the bots take actions until none of them have anything to do
+    fn run_bots(&mut self) -> Result<(), CLIError> {
+        for _ in 0..MAX_BOT_ACTIONS {
+            let mut bot_actions: Vec<Action> = self.game.valid_actions().into_iter()
+                .filter(|a| self.bots.contains(&a.issuer_player_id()))
+                .filter(|a| !matches!(a.action_type(), ActionType::Concede(_)))
+                .collect();
+
+            // sorted so that the bots play the same way for the same seed
+            bot_actions.sort_by_cached_key(|a| format!("{:?}", a));
+
+            let Some(action) = bot_actions.choose(&mut self.rng).cloned() else {
+                return Ok(());
+            };
+
+            println!("[bot] {}", action.describe(&self.game.state, &self.game.cards_db));
+            if let Err(err) = self.game.apply_action(action.clone()) {
+                return Err(CLIError::InvalidAction(action, err));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// prints where the game is at, and the numbered actions the players can take
+    fn print_turn(&self) -> Vec<Action> {
+        let state = &self.game.state;
+
+        println!();
+        println!("turn {} (depth {})", state.turn, state.depth);
+        for region in &state.regions {
+            let waiting_on = match region.stack.next() {
+                Next::PassPriority(player_id) => format!("waiting on player {}", player_id),
+                Next::ResolveEffect(_) => "resolving an effect".to_string(),
+                Next::TransitionStep => "moving to the next step".to_string(),
+            };
+
+            let players: Vec<String> = region.players.iter()
+                .map(|p| format!("player {} ({} health)", p.id, p.health))
+                .collect();
+
+            println!("region {}: {:?}, {} | {}", region.id, region.step, waiting_on, players.join(", "));
+        }
+
+        if let Some(result) = &state.result {
+            println!("game over: {}", result);
+            return Vec::new();
+        }
+
+        let mut actions: Vec<Action> = self.game.valid_actions().into_iter()
+            .filter(|a| !self.bots.contains(&a.issuer_player_id()))
+            .collect();
+        // conceding goes last, so it isn't picked by accident
+        actions.sort_by_cached_key(|a| {
+            let is_concede = matches!(a.action_type(), ActionType::Concede(_));
+            (a.issuer_player_id().0, is_concede, a.describe(&self.game.state, &self.game.cards_db))
+        });
+
+        for (idx, action) in actions.iter().enumerate() {
+            println!("{:>4}. {}", idx + 1, action.describe(&self.game.state, &self.game.cards_db));
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use algomancer_gre::game::{Game, GameOptions};
+    use algomancer_gre::game::state::GameMode;
+    use crate::parser::Format;
+    use crate::play::Session;
+
+    #[test]
+    fn test_failed_save_and_load_keep_the_session_running() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let mut session = Session {
+            game,
+            db_path: None,
+            format: Format::Json,
+            undo_stack: Vec::new(),
+            bots: Vec::new(),
+            rng: StdRng::seed_from_u64(0),
+        };
+
+        let missing = std::env::temp_dir().join("algomancer_missing_dir").join("game.json");
+        let saved = std::env::temp_dir().join(format!("algomancer_play_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&saved);
+
+        // the good save only happens if the session is still running after the failed ones
+        let input = format!("save {}\nload {}\nsave {}\nquit\n", missing.display(), missing.display(), saved.display());
+        session.run(Cursor::new(input)).unwrap();
+
+        assert!(saved.exists());
+        std::fs::remove_file(&saved).unwrap();
+    }
+}
@@ -3,9 +3,12 @@ mod parser;
 use std::collections::HashMap;
 use std::fs;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 use algocore::{*};
 
+const CORE_CARDS_JSON: &str = include_str!("../../resources/core_cards.json");
+
 #[derive(Debug)]
 pub enum DbError {
     IO(std::io::Error),
@@ -13,10 +16,16 @@ pub enum DbError {
 }
 
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, Ord, PartialOrd)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, Ord, PartialOrd, JsonSchema)]
 pub struct CardPrototypeId(pub usize);
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+impl CardPrototypeId {
+    /// The prototype of a card whose identity is hidden. Prototype ids are counted from 1 as the
+    /// cards are parsed, so no prototype in a database has it.
+    pub const HIDDEN: CardPrototypeId = CardPrototypeId(0);
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct CardPrototype {
     pub prototype_id: CardPrototypeId,
     pub name: String,
@@ -24,9 +33,17 @@ pub struct CardPrototype {
     pub costs: Cost,
     pub card_type: CardType,
     pub std_name: String,
+    pub mod_type: Option<ModType>,
+
+    /// the printed power of a unit, 0 for other cards and for a variable power like 'X'
+    #[serde(default)]
+    pub power: i32,
+
+    #[serde(default)]
+    pub triggers: Vec<TriggeredAbility>,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct CardPrototypeDatabase {
     pub prototypes: HashMap<CardPrototypeId, CardPrototype>,
 }
@@ -44,6 +61,13 @@ impl CardPrototypeDatabase {
         }
     }
 
+    /// Finds the token prototype with the given std_name, ignoring case.
+    pub fn token(&self, std_name: &str) -> Option<&CardPrototype> {
+        self.prototypes.values().find(|c| {
+            matches!(c.card_type, CardType::UnitToken | CardType::SpellToken) && c.std_name.eq_ignore_ascii_case(std_name)
+        })
+    }
+
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         let parsed_prototypes = parser::parse_json(json)?;
 
@@ -52,6 +76,11 @@ impl CardPrototypeDatabase {
         })
     }
 
+    /// The database of the core set, bundled with the crate.
+    pub fn core() -> Result<Self, DbError> {
+        Self::from_json(CORE_CARDS_JSON).map_err(DbError::Serde)
+    }
+
     pub fn from_path(file_path: &str) -> Result<Self, DbError> {
         match fs::read_to_string(file_path) {
             Ok(file_content) => {
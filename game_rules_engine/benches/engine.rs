@@ -0,0 +1,138 @@
+//! Benchmarks for the engine's hot paths, run with `cargo bench -p algomancer_gre`.
+//!
+//! Every game is seeded, and actions are chosen with a seeded rng from the sorted valid actions,
+//! so each run measures the same games.
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use algocore::Faction;
+use algomancer_gre::game::{Game, GameOptions};
+use algomancer_gre::game::action::{Action, ActionType};
+use algomancer_gre::game::state::GameMode;
+use algomancer_gre::game::state::team_configuration::TeamConfiguration;
+use database::CardPrototypeDatabase;
+
+/// the longest a playout can go on for, random games don't always end
+const MAX_PLAYOUT_ACTIONS: usize = 500;
+
+fn modes() -> Vec<(&'static str, GameOptions)> {
+    [
+        ("1v1", TeamConfiguration::one_v_one()),
+        ("2v2", TeamConfiguration::two_v_two()),
+        ("3v3", TeamConfiguration::three_v_three()),
+    ].into_iter().map(|(name, team_configuration)| {
+        let options = GameOptions {
+            seed: [0; 16],
+            game_mode: GameMode::LiveDraft {
+                selected_deck_types: vec![Faction::Wood, Faction::Fire],
+                team_configuration,
+            },
+        };
+        (name, options)
+    }).collect()
+}
+
+/// a random valid action, concessions aside so the game gets played, or None if there are none
+fn random_action(game: &Game, rng: &mut StdRng) -> Option<Action> {
+    let mut actions: Vec<Action> = game.valid_actions().into_iter()
+        .filter(|a| !matches!(a.action_type(), ActionType::Concede(_)))
+        .collect();
+    actions.sort();
+
+    actions.choose(rng).cloned()
+}
+
+/// plays up to `n` random actions, stopping early if the game ends
+fn play_random(game: &mut Game, rng: &mut StdRng, n: usize) {
+    for _ in 0..n {
+        if game.is_over() {
+            break;
+        }
+
+        match random_action(game, rng) {
+            Some(action) => game.apply_action(action).unwrap(),
+            None => break,
+        };
+    }
+}
+
+/// a game some way into its first turn, past the draft, where there's more to consider
+fn mid_game(options: &GameOptions, db: &CardPrototypeDatabase) -> Game {
+    let mut game = Game::new_with_db(options, db.clone()).unwrap();
+    play_random(&mut game, &mut StdRng::seed_from_u64(0), 40);
+    game
+}
+
+fn bench_new_game(c: &mut Criterion) {
+    let db = CardPrototypeDatabase::core().unwrap();
+    let mut group = c.benchmark_group("new_game");
+
+    for (name, options) in modes() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &options, |b, options| {
+            b.iter_batched(|| db.clone(), |db| Game::new_with_db(options, db).unwrap(), BatchSize::SmallInput)
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_valid_actions(c: &mut Criterion) {
+    let db = CardPrototypeDatabase::core().unwrap();
+    let mut group = c.benchmark_group("valid_actions");
+
+    for (name, options) in modes() {
+        let game = mid_game(&options, &db);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &game, |b, game| {
+            b.iter(|| game.valid_actions())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_apply_action(c: &mut Criterion) {
+    let db = CardPrototypeDatabase::core().unwrap();
+    let mut group = c.benchmark_group("apply_action");
+
+    for (name, options) in modes() {
+        let game = mid_game(&options, &db);
+        let action = random_action(&game, &mut StdRng::seed_from_u64(0)).expect("an action");
+
+        group.bench_with_input(BenchmarkId::from_parameter(name), &(game, action), |b, (game, action)| {
+            b.iter_batched(
+                || (game.clone(), action.clone()),
+                |(mut game, action)| game.apply_action(action).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_playout(c: &mut Criterion) {
+    let db = CardPrototypeDatabase::core().unwrap();
+    let mut group = c.benchmark_group("playout");
+    group.sample_size(10);
+
+    for (name, options) in modes() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &options, |b, options| {
+            b.iter_batched(
+                || Game::new_with_db(options, db.clone()).unwrap(),
+                |mut game| {
+                    play_random(&mut game, &mut StdRng::seed_from_u64(0), MAX_PLAYOUT_ACTIONS);
+                    game
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_new_game, bench_valid_actions, bench_apply_action, bench_playout);
+criterion_main!(benches);
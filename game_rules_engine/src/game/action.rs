@@ -2,13 +2,18 @@ use std::cmp::Ordering;
 use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::action::apply_mod::ApplyModAction;
 use crate::game::action::attack::AttackAction;
+use crate::game::action::cast_spell_token::CastSpellTokenAction;
+use crate::game::action::concede::ConcedeAction;
 use crate::game::action::draft::DraftAction;
 use crate::game::action::pass_priority::PassPriorityAction;
 use crate::game::action::play_card::PlayCardAction;
 use crate::game::action::recycle_for_resource::RecycleForResourceAction;
 use database::CardPrototypeDatabase;
 use crate::game::Game;
+use crate::game::event::Event;
 use crate::game::state::error::{StateError};
 use crate::game::state::mutation::{StateMutation, StaticStateMutation};
 use crate::game::state::player::{Player, PlayerId};
@@ -19,6 +24,9 @@ mod pass_priority;
 mod play_card;
 mod recycle_for_resource;
 mod attack;
+mod apply_mod;
+mod cast_spell_token;
+mod concede;
 
 
 pub trait ActionTrait: Sized {
@@ -26,7 +34,7 @@ pub trait ActionTrait: Sized {
     fn get_valid(state: &State, db: &CardPrototypeDatabase) -> Vec<Action>;
 }
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Action {
     issuer_player_id: PlayerId,
 
@@ -56,7 +64,7 @@ impl Ord for Action {
     }
 }
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum ActionType {
     PassPriority(PassPriorityAction),
@@ -64,9 +72,20 @@ pub enum ActionType {
     RecycleForResource(RecycleForResourceAction),
     PlayCard(PlayCardAction),
     Attack(AttackAction),
+    ApplyMod(ApplyModAction),
+    CastSpellToken(CastSpellTokenAction),
+    Concede(ConcedeAction),
 }
 
 impl Action {
+    pub fn issuer_player_id(&self) -> PlayerId {
+        self.issuer_player_id
+    }
+
+    pub fn action_type(&self) -> &ActionType {
+        &self.action
+    }
+
     fn generate_mutations(&self, game: &Game) -> Result<Vec<StateMutation>, StateError> {
         let issuer_player = game.state.find_player(self.issuer_player_id)?;
         match &self.action {
@@ -75,6 +94,9 @@ impl Action {
             ActionType::PlayCard(a) => a.generate_mutations(&game.state, &game.cards_db, issuer_player),
             ActionType::Attack(a) => a.generate_mutations(&game.state, &game.cards_db, issuer_player),
             ActionType::PassPriority(a) => a.generate_mutations(&game.state, &game.cards_db, issuer_player),
+            ActionType::ApplyMod(a) => a.generate_mutations(&game.state, &game.cards_db, issuer_player),
+            ActionType::CastSpellToken(a) => a.generate_mutations(&game.state, &game.cards_db, issuer_player),
+            ActionType::Concede(a) => a.generate_mutations(&game.state, &game.cards_db, issuer_player),
         }
     }
 }
@@ -83,11 +105,29 @@ impl Action {
 
 impl Game {
     pub fn apply_action(&mut self, action: Action) -> Result<Vec<StaticStateMutation>, StateError> {
-        eprintln!("[{}] Applying Action [{:?}]", self.state.depth, &action);
+        let (static_mutations, _) = self.apply_action_with_events(action)?;
+        Ok(static_mutations)
+    }
+
+    /// Applies the action, returning the static mutations that were applied along with the events
+    /// they raised.
+    pub fn apply_action_with_events(&mut self, action: Action) -> Result<(Vec<StaticStateMutation>, Vec<Event>), StateError> {
+        let _span = tracing::debug_span!(
+            "action",
+            depth = self.state.depth,
+            turn = self.state.turn,
+            player_id = %action.issuer_player_id(),
+        ).entered();
+        tracing::debug!(?action, "applying action");
+
+        if self.state.is_over() {
+            return Err(StateError::GameIsOver);
+        }
 
         let mutations = action.generate_mutations(self)?;
 
         let mut static_mutations = Vec::new();
+        let mut events = Vec::new();
 
         if mutations.is_empty() {
             panic!("no mutations generated from action [{:?}]", action)
@@ -95,27 +135,14 @@ impl Game {
 
         let mut next_state = self.state.clone();
 
-        for mutation in mutations {
-            let static_mutation = mutation.to_static(&next_state)?;
-            for sub_mutation in static_mutation {
-                next_state = next_state.mutate(&self.cards_db, &sub_mutation)?;
-                static_mutations.push(sub_mutation);
-            }
-        }
-
+        next_state = self.apply_mutations(next_state, mutations, &mut static_mutations, &mut events)?;
 
         // just keep applying state based actions until there is nothing left to do
         loop {
             let num_starting_mutations = static_mutations.len();
 
-            let state_based_mutations = next_state.generate_state_based_mutations();
-            for mutation in state_based_mutations {
-                let static_mutation = mutation.to_static(&next_state)?;
-                for sub_mutation in static_mutation {
-                    next_state = next_state.mutate(&self.cards_db, &sub_mutation)?;
-                    static_mutations.push(sub_mutation);
-                }
-            }
+            let state_based_mutations = next_state.generate_state_based_mutations(&self.cards_db)?;
+            next_state = self.apply_mutations(next_state, state_based_mutations, &mut static_mutations, &mut events)?;
 
             // stop when the number of static mutations didn't grow
             if num_starting_mutations == static_mutations.len() {
@@ -124,7 +151,25 @@ impl Game {
         }
 
         for m in &static_mutations {
-            eprintln!("- {:?}", &m);
+            tracing::trace!(mutation = ?m);
+        }
+
+        for e in &events {
+            tracing::trace!(event = ?e);
+        }
+
+        // states the engine produces should always be consistent, so this is only checked in debug
+        // builds where it can't slow down simulations. The state may have been inconsistent before
+        // the action (e.g. edited by hand), so only what the action broke is reported.
+        #[cfg(debug_assertions)]
+        {
+            let before = self.state.validate(&self.cards_db);
+            let violations: Vec<_> = next_state.validate(&self.cards_db).into_iter()
+                .filter(|v| !before.contains(v))
+                .collect();
+            if !violations.is_empty() {
+                return Err(StateError::InvariantViolation(violations));
+            }
         }
 
         self.action_history.push(action);
@@ -132,17 +177,50 @@ impl Game {
         next_state.depth += 1;
         self.state = next_state;
 
-        Ok(static_mutations)
+        Ok((static_mutations, events))
+    }
+
+    /// Applies the mutations one at a time, then pushes any abilities triggered by the events they
+    /// raised onto the stack.
+    fn apply_mutations(&self, mut state: State, mutations: Vec<StateMutation>, static_mutations: &mut Vec<StaticStateMutation>, events: &mut Vec<Event>) -> Result<State, StateError> {
+        let mut new_events = Vec::new();
+
+        for mutation in mutations {
+            let static_mutation = mutation.to_static(&state)?;
+            for sub_mutation in static_mutation {
+                new_events.extend(sub_mutation.events(&state));
+                state = state.mutate(&self.cards_db, &sub_mutation)?;
+                static_mutations.push(sub_mutation);
+            }
+        }
+
+        for mutation in state.generate_trigger_mutations(&self.cards_db, &new_events) {
+            for sub_mutation in mutation.to_static(&state)? {
+                state = state.mutate(&self.cards_db, &sub_mutation)?;
+                static_mutations.push(sub_mutation);
+            }
+        }
+
+        events.extend(new_events);
+
+        Ok(state)
     }
 
     pub fn valid_actions(&self) -> HashSet<Action> {
         let mut actions = HashSet::new();
 
+        if self.state.is_over() {
+            return actions;
+        }
+
         actions.extend(PassPriorityAction::get_valid(&self.state, &self.cards_db));
         actions.extend(AttackAction::get_valid(&self.state, &self.cards_db));
         actions.extend(DraftAction::get_valid(&self.state, &self.cards_db));
         actions.extend(PlayCardAction::get_valid(&self.state, &self.cards_db));
         actions.extend(RecycleForResourceAction::get_valid(&self.state, &self.cards_db));
+        actions.extend(ApplyModAction::get_valid(&self.state, &self.cards_db));
+        actions.extend(CastSpellTokenAction::get_valid(&self.state, &self.cards_db));
+        actions.extend(ConcedeAction::get_valid(&self.state, &self.cards_db));
 
         actions
     }
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::action::{Action, ActionTrait, ActionType};
+use database::{CardPrototype, CardPrototypeDatabase};
+use algocore::ModType;
+use crate::game::state::card::{Card, CardId, FindCardResult};
+use crate::game::state::error::{CardNotPlayableError, ModError, StateError};
+use crate::game::state::error::InvalidActionError::InvalidMod;
+use crate::game::state::mutation::attach_mod::AttachModMutation;
+use crate::game::state::mutation::remove_card::RemoveCardMutation;
+use crate::game::state::mutation::StateMutation;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::player::Player;
+use crate::game::state::progression::{DeploymentPhaseStep, Phase};
+use crate::game::state::region::Region;
+use crate::game::state::State;
+use crate::sm_static;
+
+/// Applies a card as a mod onto a unit, instead of playing it normally.
+/// Augments are applied from their owner's discard, grafts from their owner's hand.
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApplyModAction {
+    pub card_id: CardId,
+    pub host_permanent_id: PermanentId,
+}
+
+impl ActionTrait for ApplyModAction {
+    fn generate_mutations(&self, state: &State, db: &CardPrototypeDatabase, issuer: &Player) -> Result<Vec<StateMutation>, StateError> {
+        let (player, card, in_hand) = match state.find_card(self.card_id)? {
+            FindCardResult::InPlayerHand(player, _, card) => (player, card, true),
+            FindCardResult::InPlayerDiscard(player, _, card) => (player, card, false),
+            FindCardResult::InPlayerDeck(_, _, _) |
+            FindCardResult::InPlayerPack(_, _, _) |
+            FindCardResult::InCommonDeck(_, _) => {
+                return Err(CardNotPlayableError::NotInPlayableZone(self.card_id).into())
+            }
+        };
+
+        if player.id != issuer.id {
+            return Err(CardNotPlayableError::NotUnderPlayersControl(self.card_id).into())
+        }
+
+        let proto = db.prototypes.get(&card.prototype_id).expect("a card prototype");
+        match (&proto.mod_type, in_hand) {
+            (None, _) => {
+                return Err(InvalidMod(ModError::NotAMod(self.card_id)).into())
+            }
+            (Some(ModType::Augment { .. }), true) => {
+                return Err(InvalidMod(ModError::AugmentMustBeAppliedFromDiscard(self.card_id)).into())
+            }
+            (Some(ModType::Graft { .. }), false) => {
+                return Err(InvalidMod(ModError::GraftMustBeAppliedFromHand(self.card_id)).into())
+            }
+            _ => {}
+        }
+
+        let region = state.find_region_containing_player(issuer.id)?;
+        if !Self::region_allows_mods(state, region, issuer) {
+            return Err(CardNotPlayableError::NotInPlayableStep(self.card_id).into())
+        }
+
+        let host = region.unformed_permanents.iter().find(|p| {
+            matches!(p, Permanent::Unit { common, .. } if common.permanent_id == self.host_permanent_id)
+        });
+
+        match host {
+            Some(Permanent::Unit { common, mods, .. }) if common.controller_player_id == issuer.id => {
+                if graft_limit_reached(db, proto, mods) {
+                    return Err(InvalidMod(ModError::GraftLimitReached(self.host_permanent_id)).into())
+                }
+            }
+            _ => {
+                return Err(InvalidMod(ModError::InvalidHost(self.host_permanent_id)).into())
+            }
+        }
+
+        let mutations = vec![
+            sm_static!(RemoveCard, RemoveCardMutation {
+                card_id: self.card_id
+            }),
+            sm_static!(AttachMod, AttachModMutation {
+                host_permanent_id: self.host_permanent_id,
+                card: card.clone(),
+            }),
+        ];
+
+        Ok(mutations)
+    }
+
+    fn get_valid(state: &State, db: &CardPrototypeDatabase) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+
+        for region in &state.regions {
+            if let Phase::DeploymentPhase(DeploymentPhaseStep::Deployment(_)) = region.step {} else {
+                continue
+            }
+
+            // assume single player per region at in the deployment step
+            let player = region.sole_player();
+
+            // player must be on the team with active initiative
+            if !Self::region_allows_mods(state, region, player) {
+                continue
+            }
+
+            // augments come from the discard, grafts come from the hand
+            let candidates = player.discard.iter()
+                .filter(|c| matches!(db.prototypes[&c.prototype_id].mod_type, Some(ModType::Augment { .. })))
+                .chain(player.hand.iter()
+                    .filter(|c| matches!(db.prototypes[&c.prototype_id].mod_type, Some(ModType::Graft { .. }))));
+
+            for card in candidates {
+                let proto = db.prototypes.get(&card.prototype_id).expect("a card prototype");
+                if !state.player_can_afford(db, player.id, &proto.costs).unwrap() {
+                    continue
+                }
+
+                for permanent in &region.unformed_permanents {
+                    if let Permanent::Unit { common, mods, .. } = permanent {
+                        if common.controller_player_id != player.id || graft_limit_reached(db, proto, mods) {
+                            continue
+                        }
+
+                        actions.push(Action {
+                            issuer_player_id: player.id,
+                            action: ActionType::ApplyMod(ApplyModAction {
+                                card_id: card.card_id,
+                                host_permanent_id: common.permanent_id,
+                            }),
+                        })
+                    }
+                }
+            }
+        }
+
+        actions
+    }
+}
+
+impl ApplyModAction {
+    /// Mods are applied at the same time units are normally played, during the deployment step
+    /// of the team with the active initiative.
+    fn region_allows_mods(state: &State, region: &Region, player: &Player) -> bool {
+        if let Phase::DeploymentPhase(DeploymentPhaseStep::Deployment(_)) = region.step {} else {
+            return false
+        }
+
+        match region.active_team_id(state) {
+            Some(active_team_id) => player.team_id == active_team_id,
+            None => true,
+        }
+    }
+}
+
+/// True if `proto` is a graft with a limit, and the host already holds that many grafts.
+fn graft_limit_reached(db: &CardPrototypeDatabase, proto: &CardPrototype, mods: &[Card]) -> bool {
+    let Some(ModType::Graft { limit: Some(limit) }) = proto.mod_type else {
+        return false
+    };
+
+    let grafts = mods.iter().filter(|m| {
+        matches!(db.prototypes[&m.prototype_id].mod_type, Some(ModType::Graft { .. }))
+    }).count();
+
+    grafts >= limit as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use algocore::ModType;
+    use database::{CardPrototypeDatabase, CardPrototypeId};
+    use crate::game::Game;
+    use crate::game::action::{Action, ActionType};
+    use crate::game::action::apply_mod::ApplyModAction;
+    use crate::game::game_builder::scenario::Scenario;
+    use crate::game::state::card::CardId;
+    use crate::game::state::error::{InvalidActionError, ModError, StateError};
+    use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
+    use crate::game::state::mutation::StaticStateMutation;
+    use crate::game::state::permanent::{Permanent, PermanentId};
+    use crate::game::state::player::PlayerId;
+
+    /// player 1 is deploying, with the mods in `hand` and `discard` and a unit to apply them to
+    fn deployment(db: CardPrototypeDatabase, hand: &str, discard: &str) -> Game {
+        let scenario = Scenario::from_toml(&format!(r#"
+            step = {{ phase = "DeploymentPhase", step = "Deployment", team = "IT" }}
+
+            [[players]]
+            team = 1
+            hand = [{hand}]
+            discard = [{discard}]
+            battlefield = ["A Fast Pile of Rocks"]
+            resources = [{{ name = "Earth Resource" }}, {{ name = "Earth Resource" }}, {{ name = "Earth Resource" }}]
+
+            [[players]]
+            team = 2
+            battlefield = ["A Fast Pile of Rocks"]
+        "#)).unwrap();
+
+        Game::from_scenario(&scenario, db).unwrap()
+    }
+
+    fn unit_of(game: &Game, player_id: PlayerId) -> PermanentId {
+        let region = game.state.find_region_containing_player(player_id).unwrap();
+        region.unformed_permanents.iter().find(|p| p.is_unit()).unwrap().common().permanent_id
+    }
+
+    fn mod_actions(game: &Game) -> Vec<ApplyModAction> {
+        game.valid_actions().into_iter().filter_map(|a| match a.action_type() {
+            ActionType::ApplyMod(a) => Some(a.clone()),
+            _ => None,
+        }).collect()
+    }
+
+    fn apply_mod(card_id: CardId, host_permanent_id: PermanentId) -> Action {
+        Action {
+            issuer_player_id: PlayerId(1),
+            action: ActionType::ApplyMod(ApplyModAction { card_id, host_permanent_id }),
+        }
+    }
+
+    #[test]
+    fn test_apply_augment_once() {
+        let mut game = deployment(CardPrototypeDatabase::core().unwrap(), "", r#""Awoken Tomb""#);
+        let host = unit_of(&game, PlayerId(1));
+        let augment = game.state.find_player(PlayerId(1)).unwrap().discard.iter().next().unwrap().clone();
+        assert_eq!(game.cards_db.prototypes[&augment.prototype_id].mod_type, Some(ModType::Augment { once: true }));
+        let augment = augment.card_id;
+
+        // the augment can only go onto the player's own unit
+        assert_eq!(mod_actions(&game), vec![ApplyModAction { card_id: augment, host_permanent_id: host }]);
+        game.apply_action(apply_mod(augment, host)).unwrap();
+
+        let player = game.state.find_player(PlayerId(1)).unwrap();
+        assert!(player.discard.is_empty());
+        let Permanent::Unit { mods, .. } = game.state.find_permanent(host).unwrap() else { panic!("a unit") };
+        assert_eq!(mods.iter().map(|m| m.card_id).collect::<Vec<_>>(), vec![augment]);
+
+        // when the host dies, a [once] augment is removed from the game rather than going back to
+        // the discard, so it can't be applied again
+        let destroy = StaticStateMutation::DestroyPermanent(DestroyPermanentMutation { permanent_id: host });
+        let state = game.state.clone().mutate(&game.cards_db, &destroy).unwrap();
+        let player = state.find_player(PlayerId(1)).unwrap();
+        assert_eq!(player.discard.len(), 1);
+        assert!(player.discard.iter().all(|c| c.card_id != augment));
+    }
+
+    #[test]
+    fn test_apply_graft_up_to_limit() {
+        let mut db = CardPrototypeDatabase::core().unwrap();
+        let mut graft = db.prototypes.values().find(|p| p.name == "A Fast Pile of Rocks").unwrap().clone();
+        graft.prototype_id = CardPrototypeId(db.prototypes.keys().map(|id| id.0).max().unwrap() + 1);
+        graft.name = "A Grafted Pile of Rocks".to_string();
+        graft.mod_type = Some(ModType::Graft { limit: Some(1) });
+        db.prototypes.insert(graft.prototype_id, graft);
+
+        let mut game = deployment(db, r#""A Grafted Pile of Rocks", "A Grafted Pile of Rocks""#, "");
+        let host = unit_of(&game, PlayerId(1));
+        let grafts: Vec<_> = game.state.find_player(PlayerId(1)).unwrap().hand.iter().map(|c| c.card_id).collect();
+        assert_eq!(mod_actions(&game).len(), 2);
+
+        game.apply_action(apply_mod(grafts[0], host)).unwrap();
+
+        // the host holds as many grafts as the limit allows, so the other can't be applied
+        assert!(mod_actions(&game).is_empty());
+        assert!(matches!(
+            game.apply_action(apply_mod(grafts[1], host)),
+            Err(StateError::InvalidAction(InvalidActionError::InvalidMod(ModError::GraftLimitReached(id)))) if id == host
+        ));
+    }
+
+    #[test]
+    fn test_apply_mod_to_invalid_target() {
+        let mut game = deployment(CardPrototypeDatabase::core().unwrap(), "", r#""Awoken Tomb""#);
+        let augment = game.state.find_player(PlayerId(1)).unwrap().discard.iter().next().unwrap().card_id;
+        let opponents_unit = unit_of(&game, PlayerId(2));
+        let resource = game.state.find_region_containing_player(PlayerId(1)).unwrap()
+            .unformed_permanents.iter().find(|p| !p.is_unit()).unwrap().common().permanent_id;
+
+        for host in [opponents_unit, resource, PermanentId(999)] {
+            assert!(!mod_actions(&game).iter().any(|a| a.host_permanent_id == host));
+            assert!(matches!(
+                game.apply_action(apply_mod(augment, host)),
+                Err(StateError::InvalidAction(InvalidActionError::InvalidMod(ModError::InvalidHost(id)))) if id == host
+            ));
+        }
+    }
+}
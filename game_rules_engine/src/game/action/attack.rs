@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::action::{Action, ActionTrait, ActionType};
 use crate::game::action::pass_priority::PassPriorityAction;
 use database::CardPrototypeDatabase;
@@ -6,14 +7,16 @@ use database::CardPrototypeDatabase;
 use crate::game::state::error::{StateError};
 use crate::game::state::formation::{Formation, FormationId, FormationPos};
 use crate::game::state::mutation::StateMutation;
+use crate::game::state::mutation::turn_mutations::RecordUnitsAttackedMutation;
 
 use crate::game::state::permanent::{Permanent, PermanentId};
 use crate::game::state::player::Player;
 use crate::game::state::region::RegionId;
 
 use crate::game::state::State;
+use crate::sm_static;
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AttackAction {
     pub home_region_id: RegionId,
     pub under_attack_region_id: RegionId,
@@ -21,8 +24,12 @@ pub struct AttackAction {
 }
 
 impl ActionTrait for AttackAction {
-    fn generate_mutations(&self, state: &State, _db: &CardPrototypeDatabase, _issuer: &Player) -> Result<Vec<StateMutation>, StateError> {
+    fn generate_mutations(&self, state: &State, _db: &CardPrototypeDatabase, issuer: &Player) -> Result<Vec<StateMutation>, StateError> {
         let mutations: Vec<StateMutation> = vec![
+            sm_static!(RecordUnitsAttacked, RecordUnitsAttackedMutation {
+                player_id: issuer.id,
+                permanent_ids: self.formation.cells_iter().copied().collect(),
+            }),
             state.generate_mutation_for_phase_transition(self.home_region_id),
             state.generate_mutation_for_phase_transition(self.under_attack_region_id)
         ];
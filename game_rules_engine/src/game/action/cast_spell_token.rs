@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::action::{Action, ActionTrait, ActionType};
+use database::CardPrototypeDatabase;
+use crate::game::state::error::{CardNotPlayableError, StateError};
+use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
+use crate::game::state::mutation::StateMutation;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::player::Player;
+use crate::game::state::State;
+use crate::sm_static;
+
+/// Casts a spell token from play. Spell tokens can be cast by their controller whenever they have
+/// priority during a battle priority window, and cease to exist once cast.
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CastSpellTokenAction {
+    pub permanent_id: PermanentId,
+}
+
+impl ActionTrait for CastSpellTokenAction {
+    fn generate_mutations(&self, state: &State, _db: &CardPrototypeDatabase, issuer: &Player) -> Result<Vec<StateMutation>, StateError> {
+        let permanent = state.find_permanent(self.permanent_id)?;
+
+        let Permanent::SpellToken { common, .. } = permanent else {
+            return Err(CardNotPlayableError::CannotCastANonSpellTokenPermanentFromPlay(self.permanent_id).into())
+        };
+
+        if common.controller_player_id != issuer.id {
+            return Err(CardNotPlayableError::PermanentNotUnderPlayersControl(self.permanent_id).into())
+        }
+
+        let region = state.find_region_containing_player(issuer.id)?;
+        if !region.step.is_priority_window() || !state.player_can_act(issuer.id) {
+            return Err(CardNotPlayableError::SpellTokenNotCastableNow(self.permanent_id).into())
+        }
+
+        // the spell's own text isn't resolved yet, as spells aren't supported by the engine
+        let mutations = vec![
+            sm_static!(DestroyPermanent, DestroyPermanentMutation {
+                permanent_id: self.permanent_id
+            })
+        ];
+
+        Ok(mutations)
+    }
+
+    fn get_valid(state: &State, _db: &CardPrototypeDatabase) -> Vec<Action> {
+        let mut actions = Vec::new();
+
+        for region in &state.regions {
+            if !region.step.is_priority_window() {
+                continue
+            }
+
+            for permanent in &region.unformed_permanents {
+                if let Permanent::SpellToken { common, .. } = permanent {
+                    if !state.player_can_act(common.controller_player_id) {
+                        continue
+                    }
+
+                    actions.push(Action {
+                        issuer_player_id: common.controller_player_id,
+                        action: ActionType::CastSpellToken(CastSpellTokenAction {
+                            permanent_id: common.permanent_id,
+                        }),
+                    })
+                }
+            }
+        }
+
+        actions
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::action::{Action, ActionTrait, ActionType};
+use database::CardPrototypeDatabase;
+use crate::game::state::error::{InvalidActionError, StateError};
+use crate::game::state::game_result::GameOverReason;
+use crate::game::state::mutation::player_mutations::UpdatePlayerAliveMutation;
+use crate::game::state::mutation::set_game_result::SetGameResultMutation;
+use crate::game::state::mutation::StateMutation;
+use crate::game::state::player::Player;
+use crate::game::state::State;
+use crate::sm_static;
+
+/// A player leaves the game. Conceding is possible at any time, regardless of priority.
+/// If it leaves fewer than two teams with living players, the game ends by concession.
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConcedeAction {}
+
+impl ActionTrait for ConcedeAction {
+    fn generate_mutations(&self, state: &State, _db: &CardPrototypeDatabase, issuer: &Player) -> Result<Vec<StateMutation>, StateError> {
+        if !issuer.is_alive {
+            return Err(InvalidActionError::PlayerIsDead(issuer.id).into())
+        }
+
+        let result = state.result_if_players_die(&[issuer.id], GameOverReason::Concession);
+
+        let mut mutations = vec![
+            sm_static!(UpdatePlayerAlive, UpdatePlayerAliveMutation {
+                player_id: issuer.id,
+                new_value: false,
+            }),
+        ];
+
+        if let Some(result) = result {
+            mutations.push(sm_static!(SetGameResult, SetGameResultMutation {
+                result
+            }));
+        }
+
+        Ok(mutations)
+    }
+
+    fn get_valid(state: &State, _db: &CardPrototypeDatabase) -> Vec<Action> {
+        state.players().filter(|p| p.is_alive).map(|p| {
+            Action {
+                issuer_player_id: p.id,
+                action: ActionType::Concede(ConcedeAction {}),
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::{Action, ActionType};
+    use crate::game::action::concede::ConcedeAction;
+    use crate::game::state::error::StateError;
+    use crate::game::state::game_result::GameOverReason;
+    use crate::game::state::GameMode;
+
+    #[test]
+    fn test_concede_ends_game() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let conceding = game.state.regions[0].players[0].clone();
+        let opponent = game.state.regions[1].players[0].clone();
+
+        let concede = |issuer_player_id| Action {
+            issuer_player_id,
+            action: ActionType::Concede(ConcedeAction {}),
+        };
+
+        game.apply_action(concede(conceding.id)).unwrap();
+
+        let result = game.state.result.clone().expect("a game result");
+        assert_eq!(result.winning_team_ids, vec![opponent.team_id]);
+        assert_eq!(result.reason, GameOverReason::Concession);
+        assert!(!game.state.find_player(conceding.id).unwrap().is_alive);
+
+        assert!(game.is_over());
+        assert!(game.valid_actions().is_empty());
+        assert!(matches!(game.apply_action(concede(opponent.id)), Err(StateError::GameIsOver)));
+    }
+}
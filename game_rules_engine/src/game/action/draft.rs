@@ -4,6 +4,7 @@ use std::hash::Hash;
 use rand::prelude::SliceRandom;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 use crate::game::action::{Action, ActionTrait, ActionType};
 use database::{CardPrototype, CardPrototypeDatabase};
@@ -26,7 +27,7 @@ use crate::game::state::progression::{Phase, PlanningPhaseStep};
 use crate::game::state::State;
 
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DraftAction {
     pub cards_to_keep: Vec<CardId>,
 }
@@ -116,7 +117,7 @@ impl ActionTrait for DraftAction {
             }
         }
 
-        eprintln!("Player [{:?}] has selected their draft.", player_id);
+        tracing::debug!(%player_id, "player has selected their draft");
 
         Ok(mutations)
     }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::action::{Action, ActionTrait, ActionType};
 use database::CardPrototypeDatabase;
 
@@ -10,7 +11,7 @@ use crate::game::state::State;
 use crate::{sm_static};
 use crate::game::state::mutation::stack_pass_priority::StackPassPriorityMutation;
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PassPriorityAction {}
 
 impl ActionTrait for PassPriorityAction {
@@ -1,5 +1,6 @@
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::action::{Action, ActionTrait, ActionType};
 use database::{CardPrototypeDatabase};
 use crate::game::{Game};
@@ -12,6 +13,7 @@ use crate::game::state::error::CardNotPlayableError::{CannotPlayMoreResources, C
 use crate::game::state::mutation::{StateMutation};
 
 use crate::game::state::mutation::create_permanent::CreatePermanentMutation;
+use crate::game::state::mutation::move_card::{MoveCardMutation, To};
 use crate::game::state::mutation::player_mutations::UpdatePlayerResourcesPlayedMutation;
 use crate::game::state::mutation::remove_card::RemoveCardMutation;
 use crate::game::state::permanent::{Permanent, PermanentCommon, PermanentId};
@@ -22,7 +24,7 @@ use crate::game::state::State;
 use crate::{sm_eval, sm_static};
 
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlayCardAction {
     pub card_id: CardId
 }
@@ -73,7 +75,7 @@ impl ActionTrait for PlayCardAction {
 
                         let mutation = sm_static!(UpdatePlayerResourcesPlayed, UpdatePlayerResourcesPlayedMutation{
                             player_id: issuer.id,
-                            new_value: issuer.resources_played_this_turn + 1
+                            new_value: issuer.this_turn.resources_played + 1
                         });
                         mutations.push(mutation);
 
@@ -92,9 +94,10 @@ impl ActionTrait for PlayCardAction {
                             let region_id = next_state.find_region_id_containing_player(player_id);
                             let permanent = Permanent::Unit {
                                 card: Card {
-                                    card_id: card_id,
-                                    prototype_id: prototype_id
+                                    card_id,
+                                    prototype_id
                                 },
+                                mods: Vec::new(),
                                 common: PermanentCommon {
                                     permanent_id: PermanentId(next_state.permanent_id_factory.peek()),
                                     controller_player_id: player_id
@@ -112,20 +115,33 @@ impl ActionTrait for PlayCardAction {
                         mutations.push(mutation);
                         Ok(mutations)
                     }
-                    CardType::Spell(_) => { todo!("not yet supported"); }
-                    CardType::UnitToken => { panic!("can't cast a unit token"); }
-                    CardType::SpellToken => { todo!("not yet supported"); }
+                    // spell effects aren't supported yet, a spell is cast straight into the discard
+                    CardType::Spell(_) => {
+                        mutations.push(sm_static!(MoveCard, MoveCardMutation {
+                            from: player.hand.id,
+                            to: To::Unordered(player.discard.id),
+                            card_id: self.card_id,
+                        }));
+
+                        Ok(mutations)
+                    }
+
+                    // tokens are never cards, spell tokens are cast from play with CastSpellTokenAction
+                    CardType::UnitToken |
+                    CardType::SpellToken => {
+                        Err(CardNotPlayableError::TokensOnlyExistInPlay(self.card_id).into())
+                    }
                     CardType::Meta(_) => { todo!("this is a meta card"); }
                 }
             }
 
-            // must be grafting or modifying from discard
+            // cards in the discard can only be applied as an augment, see ApplyModAction
             FindCardResult::InPlayerDiscard(player, _, _) => {
                 if player.id != issuer.id {
                     return Err(CardNotPlayableError::NotUnderPlayersControl(self.card_id).into())
                 }
 
-                todo!("card must be either grafting or augmenting")
+                Err(CardNotPlayableError::MustBePlayedFromHand(self.card_id).into())
             }
 
             FindCardResult::InPlayerDeck(_, _, _) |
@@ -177,15 +193,13 @@ impl PlayCardAction {
                     // spells are not supported yet
                     //CardType::Spell(Timing::Virus) |
                     //CardType::Spell(Timing::Default) |
-                    CardType::Unit(Timing::Default)  => {
-                        if state.player_can_afford(db, player.id, &proto.costs).unwrap() {
-                            actions.push(Action {
-                                issuer_player_id: player.id,
-                                action: ActionType::PlayCard(PlayCardAction {
-                                    card_id: card.card_id,
-                                })
+                    CardType::Unit(Timing::Default) if state.player_can_afford(db, player.id, &proto.costs).unwrap() => {
+                        actions.push(Action {
+                            issuer_player_id: player.id,
+                            action: ActionType::PlayCard(PlayCardAction {
+                                card_id: card.card_id,
                             })
-                        }
+                        })
                     }
                     _ => {}
                 }
@@ -219,15 +233,13 @@ impl PlayCardAction {
                 let proto = db.prototypes.get(&card.prototype_id).expect("a card prototype");
                 match proto.card_type {
                     CardType::Unit(Timing::Haste) |
-                    CardType::Spell(Timing::Haste) => {
-                        if state.player_can_afford(db, player.id, &proto.costs).unwrap() {
-                            actions.push(Action {
-                                issuer_player_id: player.id,
-                                action: ActionType::PlayCard(PlayCardAction {
-                                    card_id: card.card_id,
-                                })
+                    CardType::Spell(Timing::Haste) if state.player_can_afford(db, player.id, &proto.costs).unwrap() => {
+                        actions.push(Action {
+                            issuer_player_id: player.id,
+                            action: ActionType::PlayCard(PlayCardAction {
+                                card_id: card.card_id,
                             })
-                        }
+                        })
                     }
                     _ => {}
                 }
@@ -252,7 +264,7 @@ impl PlayCardAction {
             let player = region.sole_player();
 
             // can't play more than two resources per round
-            if player.resources_played_this_turn >= 2 {
+            if player.this_turn.resources_played >= 2 {
                 continue
             }
 
@@ -328,7 +340,7 @@ impl Game {
                                 // up to two resource cards are allowed during the mana step
                                 CardType::Resource(_) => {
                                     let player = state.find_player(player_id).expect("a player");
-                                    if player.resources_played_this_turn >= 2 {
+                                    if player.this_turn.resources_played >= 2 {
                                         return Err(CannotPlayMoreResources(card_id));
                                     }
                                 }
@@ -436,7 +448,7 @@ impl Game {
                 // special case for resource, need to increment counter
                 if let CardType::Resource(_) = proto.card_type {
                     let player = state.find_player_mut(player_id).expect("a player");
-                    player.resources_played_this_turn += 1;
+                    player.this_turn.resources_played += 1;
                 }
             }
 
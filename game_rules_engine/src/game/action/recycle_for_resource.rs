@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::action::{Action, ActionTrait, ActionType};
 use database::CardPrototypeDatabase;
 use crate::game::state::card::{Card, CardId, FindCardResult};
@@ -13,7 +14,7 @@ use algocore::ResourceType;
 use crate::game::state::State;
 use crate::{sm_eval, sm_static};
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RecycleForResourceAction {
     pub card_id: CardId,
     pub resource_type: ResourceType,
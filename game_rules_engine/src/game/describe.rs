@@ -0,0 +1,267 @@
+use database::CardPrototypeDatabase;
+use crate::game::action::{Action, ActionType};
+use crate::game::state::card::{Card, CardId, FindCardResult};
+use crate::game::state::card_collection::CardCollectionId;
+use crate::game::state::mutation::deal_damage::DamageTarget;
+use crate::game::state::mutation::move_card::To;
+use crate::game::state::mutation::StaticStateMutation;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::player::PlayerId;
+use crate::game::state::stack::Next;
+use crate::game::state::State;
+
+impl Action {
+    /// Describes the action in a sentence, for people to read,
+    /// e.g. "Player 2 drafts Fast Pile of Rocks, Pile of Runes".
+    ///
+    /// `state` is the state the action would be applied to.
+    pub fn describe(&self, state: &State, db: &CardPrototypeDatabase) -> String {
+        let player = format!("Player {}", self.issuer_player_id());
+
+        match self.action_type() {
+            ActionType::PassPriority(_) => format!("{} passes priority", player),
+            ActionType::Draft(a) => {
+                let names: Vec<String> = a.cards_to_keep.iter().map(|&c| card_name(state, db, c)).collect();
+                format!("{} drafts {}", player, names.join(", "))
+            }
+            ActionType::RecycleForResource(a) => {
+                format!("{} recycles {} for a {:?} resource", player, card_name(state, db, a.card_id), a.resource_type)
+            }
+            ActionType::PlayCard(a) => format!("{} plays {}", player, card_name(state, db, a.card_id)),
+            ActionType::Attack(a) => {
+                let attackers = a.formation.cells_iter().count();
+                format!("{} attacks region {} with {} unit(s)", player, a.under_attack_region_id, attackers)
+            }
+            ActionType::ApplyMod(a) => {
+                format!("{} applies {} to {}", player, card_name(state, db, a.card_id), permanent_name(state, db, a.host_permanent_id))
+            }
+            ActionType::CastSpellToken(a) => format!("{} casts {}", player, permanent_name(state, db, a.permanent_id)),
+            ActionType::Concede(_) => format!("{} concedes", player),
+        }
+    }
+}
+
+impl StaticStateMutation {
+    /// Describes the mutation in a sentence, for people to read, e.g. "Player 1's health 30 → 26".
+    ///
+    /// Like [StaticStateMutation::events], this must be called before the mutation is applied, as
+    /// some descriptions need what the mutation replaces or removes (e.g. the previous health).
+    pub fn describe(&self, state: &State, db: &CardPrototypeDatabase) -> String {
+        match self {
+            StaticStateMutation::StackClearPriority(m) => {
+                format!("Region {}'s priority is cleared", m.region_id)
+            }
+            StaticStateMutation::StackAddPriority(m) => {
+                format!("Player {} gets priority in region {}", m.player_id, m.region_id)
+            }
+            StaticStateMutation::StackPassPriority(m) => {
+                let passing = state.find_region(m.region_id).ok().and_then(|r| match r.stack.next() {
+                    Next::PassPriority(player_id) => Some(player_id),
+                    _ => None,
+                });
+
+                match passing {
+                    Some(player_id) => format!("Player {} passes priority in region {}", player_id, m.region_id),
+                    None => format!("Priority is passed in region {}", m.region_id),
+                }
+            }
+            StaticStateMutation::PhaseTransition(m) => {
+                format!("Region {} moves to {}", m.region_id, m.to_phase)
+            }
+            StaticStateMutation::MoveCard(m) => {
+                let to = match m.to {
+                    To::Ordered(id, _) | To::Unordered(id) => id,
+                };
+
+                format!("{} moves from {} to {}", card_name(state, db, m.card_id), collection_name(m.from), collection_name(to))
+            }
+            StaticStateMutation::CreatePackForPlayer(m) => {
+                format!("Player {} gets an empty pack", m.player_id)
+            }
+            StaticStateMutation::CreateCard(m) => {
+                format!("{} is created in {}", prototype_name(db, &m.card), collection_name(m.card_collection_id))
+            }
+            StaticStateMutation::RemoveCard(m) => {
+                format!("{} is removed from {}", card_name(state, db, m.card_id), card_location(state, m.card_id))
+            }
+            StaticStateMutation::UpdatePlayerHealth(m) => {
+                match state.find_player(m.player_id) {
+                    Ok(player) => format!("Player {}'s health {} → {}", m.player_id, player.health, m.new_value),
+                    Err(_) => format!("Player {}'s health → {}", m.player_id, m.new_value),
+                }
+            }
+            StaticStateMutation::UpdatePlayerAlive(m) => {
+                if m.new_value {
+                    format!("Player {} is alive again", m.player_id)
+                } else {
+                    format!("Player {} dies", m.player_id)
+                }
+            }
+            StaticStateMutation::UpdatePlayerResourcesPlayed(m) => {
+                format!("Player {} has played {} resource(s) this turn", m.player_id, m.new_value)
+            }
+            StaticStateMutation::CreatePermanent(m) => {
+                format!("{} enters play in region {}", permanent_label(db, &m.permanent), m.region_id)
+            }
+            StaticStateMutation::SetResourceTapped(m) => {
+                let tapped = if m.tapped { "tapped" } else { "untapped" };
+                format!("{} is {}", permanent_name(state, db, m.resource_permanent_id), tapped)
+            }
+            StaticStateMutation::AttachMod(m) => {
+                format!("{} is attached to {}", prototype_name(db, &m.card), permanent_name(state, db, m.host_permanent_id))
+            }
+            StaticStateMutation::DestroyPermanent(m) => {
+                format!("{} is destroyed", permanent_name(state, db, m.permanent_id))
+            }
+            StaticStateMutation::DealDamage(m) => {
+                let target = match m.target {
+                    DamageTarget::Player(player_id) => format!("player {}", player_id),
+                    DamageTarget::Permanent(permanent_id) => permanent_name(state, db, permanent_id),
+                };
+                format!("{} deals {} damage to {}", permanent_name(state, db, m.source_permanent_id), m.amount, target)
+            }
+            StaticStateMutation::StackPushEffect(m) => {
+                format!(
+                    "{}'s ability goes on region {}'s stack: {}",
+                    permanent_name(state, db, m.effect.source_permanent_id),
+                    m.region_id,
+                    m.effect.ability.effect_text,
+                )
+            }
+            StaticStateMutation::StackResolveEffect(m) => {
+                let top = state.find_region(m.region_id).ok().and_then(|r| r.stack.effects().last());
+                match top {
+                    Some(effect) => format!("{}'s ability resolves: {}", permanent_name(state, db, effect.source_permanent_id), effect.ability.effect_text),
+                    None => format!("The top of region {}'s stack resolves", m.region_id),
+                }
+            }
+            StaticStateMutation::SetGameResult(m) => {
+                format!("Game over: {}", m.result)
+            }
+            StaticStateMutation::SetTurn(m) => {
+                format!("Turn {} begins", m.turn)
+            }
+            StaticStateMutation::ResetTurnRecord(m) => {
+                format!("Player {}'s turn record is reset", m.player_id)
+            }
+            StaticStateMutation::RecordUnitsAttacked(m) => {
+                let names: Vec<String> = m.permanent_ids.iter().map(|&p| permanent_name(state, db, p)).collect();
+                format!("Player {} attacked with {}", m.player_id, names.join(", "))
+            }
+        }
+    }
+}
+
+/// The name of a card, or its id if the card can't be found.
+fn card_name(state: &State, db: &CardPrototypeDatabase, card_id: CardId) -> String {
+    let card = match state.find_card(card_id) {
+        Ok(FindCardResult::InPlayerHand(_, _, card)) |
+        Ok(FindCardResult::InPlayerDiscard(_, _, card)) |
+        Ok(FindCardResult::InPlayerDeck(_, _, card)) |
+        Ok(FindCardResult::InPlayerPack(_, _, card)) |
+        Ok(FindCardResult::InCommonDeck(_, card)) => card,
+        Err(_) => return card_id.to_string(),
+    };
+
+    prototype_name(db, card)
+}
+
+pub(crate) fn prototype_name(db: &CardPrototypeDatabase, card: &Card) -> String {
+    match db.prototypes.get(&card.prototype_id) {
+        Some(proto) => proto.name.clone(),
+        None => card.card_id.to_string(),
+    }
+}
+
+/// Where a card is, e.g. "Player 1's hand".
+fn card_location(state: &State, card_id: CardId) -> String {
+    match state.find_card(card_id) {
+        Ok(FindCardResult::InPlayerHand(player, _, _)) => format!("Player {}'s hand", player.id),
+        Ok(FindCardResult::InPlayerDiscard(player, _, _)) => format!("Player {}'s discard", player.id),
+        Ok(FindCardResult::InPlayerDeck(player, _, _)) => format!("Player {}'s deck", player.id),
+        Ok(FindCardResult::InPlayerPack(player, _, _)) => format!("Player {}'s pack", player.id),
+        Ok(FindCardResult::InCommonDeck(_, _)) => "the common deck".to_string(),
+        Err(_) => "the game".to_string(),
+    }
+}
+
+/// The name of a card collection, decoded from its id (see [CardCollectionId]).
+pub(crate) fn collection_name(id: CardCollectionId) -> String {
+    if id == CardCollectionId::new_common_deck() {
+        return "the common deck".to_string();
+    }
+
+    let [_, tens, ones, kind] = id.0;
+    let player_id = format!("{}{}", tens, ones).parse::<u8>().map(PlayerId);
+    let kind = match kind {
+        'h' => "hand",
+        'd' => "discard",
+        'p' => "pack",
+        'D' => "deck",
+        _ => return id.to_string(),
+    };
+
+    match player_id {
+        Ok(player_id) => format!("Player {}'s {}", player_id, kind),
+        Err(_) => id.to_string(),
+    }
+}
+
+/// The name of a permanent in play, including those in formations, or its id if it can't be found.
+fn permanent_name(state: &State, db: &CardPrototypeDatabase, permanent_id: PermanentId) -> String {
+    let permanent = state.regions.iter()
+        .flat_map(|r| r.permanents())
+        .find(|p| p.common().permanent_id == permanent_id);
+
+    match permanent {
+        Some(permanent) => permanent_label(db, permanent),
+        None => permanent_id.to_string(),
+    }
+}
+
+pub(crate) fn permanent_label(db: &CardPrototypeDatabase, permanent: &Permanent) -> String {
+    let common = permanent.common();
+    match db.prototypes.get(&permanent.prototype_id()) {
+        Some(proto) => format!("Player {}'s {}", common.controller_player_id, proto.name),
+        None => format!("Player {}'s {}", common.controller_player_id, common.permanent_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::ActionType;
+    use crate::game::state::GameMode;
+    use crate::game::state::mutation::player_mutations::UpdatePlayerHealthMutation;
+    use crate::game::state::mutation::StaticStateMutation;
+
+    #[test]
+    fn test_describe() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let player = game.state.regions[0].players[0].clone();
+
+        let health = StaticStateMutation::UpdatePlayerHealth(UpdatePlayerHealthMutation {
+            player_id: player.id,
+            new_value: player.health - 4,
+        });
+        assert_eq!(health.describe(&game.state, &game.cards_db), format!("Player {}'s health 30 → 26", player.id));
+
+        // pass priority until the draft step
+        let draft = loop {
+            let actions = game.valid_actions();
+            if let Some(draft) = actions.iter().find(|a| matches!(a.action_type(), ActionType::Draft(_))) {
+                break draft.clone();
+            }
+
+            let pass = actions.into_iter().find(|a| matches!(a.action_type(), ActionType::PassPriority(_))).expect("a pass");
+            game.apply_action(pass).unwrap();
+        };
+        let db = &game.cards_db;
+
+        let ActionType::Draft(d) = draft.action_type() else { unreachable!() };
+        let names: Vec<String> = d.cards_to_keep.iter().map(|&c| super::card_name(&game.state, db, c)).collect();
+
+        assert!(!names.iter().any(|n| n.starts_with("Card #")));
+        assert_eq!(draft.describe(&game.state, db), format!("Player {} drafts {}", draft.issuer_player_id(), names.join(", ")));
+    }
+}
@@ -0,0 +1,607 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::CardPrototypeDatabase;
+use crate::game::describe::{collection_name, permanent_label, prototype_name};
+use crate::game::state::card::{Card, CardId};
+use crate::game::state::card_collection::CardCollectionId;
+use crate::game::state::game_result::GameResult;
+use crate::game::state::mutation::create_card::CreateCardMutation;
+use crate::game::state::mutation::create_pack::CreatePackMutation;
+use crate::game::state::mutation::create_permanent::CreatePermanentMutation;
+use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
+use crate::game::state::mutation::move_card::{MoveCardMutation, Placement, To};
+use crate::game::state::mutation::phase_transition::PhaseTransitionMutation;
+use crate::game::state::mutation::player_mutations::{UpdatePlayerAliveMutation, UpdatePlayerHealthMutation, UpdatePlayerResourcesPlayedMutation};
+use crate::game::state::mutation::remove_card::RemoveCardMutation;
+use crate::game::state::mutation::set_game_result::SetGameResultMutation;
+use crate::game::state::mutation::set_resource_tapped::SetResourceTappedMutation;
+use crate::game::state::mutation::stack_add_priority::StackAddPriorityMutation;
+use crate::game::state::mutation::stack_clear_priority::StackClearPriorityMutation;
+use crate::game::state::mutation::stack_push_effect::StackPushEffectMutation;
+use crate::game::state::mutation::stack_resolve_effect::StackResolveEffectMutation;
+use crate::game::state::mutation::attach_mod::AttachModMutation;
+use crate::game::state::mutation::turn_mutations::{RecordUnitsAttackedMutation, ResetTurnRecordMutation, SetTurnMutation};
+use crate::game::state::mutation::StaticStateMutation;
+use crate::game::state::permanent::Permanent;
+use crate::game::state::player::{Player, PlayerId};
+use crate::game::state::progression::Phase;
+use crate::game::state::region::{Region, RegionId};
+use crate::game::state::stack::Effect;
+use crate::game::state::turn_record::TurnRecord;
+use crate::game::state::State;
+
+/// One way two states differ, see [State::diff].
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(tag = "type")]
+pub enum Difference {
+    Depth { from: usize, to: usize },
+    Turn { from: u32, to: u32 },
+    InitiativePlayer { from: PlayerId, to: PlayerId },
+    Result { from: Option<GameResult>, to: Option<GameResult> },
+
+    /// the random number generators are in different states
+    Rng,
+
+    /// one of the id factories has handed out a different number of ids
+    IdFactory { name: String, from: usize, to: usize },
+
+    RegionAdded { region_id: RegionId },
+    RegionRemoved { region_id: RegionId },
+    Step { region_id: RegionId, from: Phase, to: Phase },
+    Priority { region_id: RegionId, from: Vec<PlayerId>, to: Vec<PlayerId> },
+
+    /// the effects above the part of the stack the two states have in common
+    Stack { region_id: RegionId, removed: Vec<Effect>, added: Vec<Effect> },
+
+    /// the attacking or defending formations differ, beyond which permanents are in play
+    Formation { region_id: RegionId },
+
+    PermanentAdded { region_id: RegionId, permanent: Permanent },
+    PermanentRemoved { region_id: RegionId, permanent: Permanent },
+    PermanentChanged { region_id: RegionId, from: Permanent, to: Permanent },
+
+    PlayerAdded { player_id: PlayerId },
+    PlayerRemoved { player_id: PlayerId },
+    Health { player_id: PlayerId, from: i32, to: i32 },
+    Alive { player_id: PlayerId, from: bool, to: bool },
+    TurnRecord { player_id: PlayerId, from: TurnRecord, to: TurnRecord },
+    PackCreated { player_id: PlayerId },
+    PackRemoved { player_id: PlayerId },
+
+    /// `to_index` is the card's position in the zone it moved to, if that zone is a deck
+    CardMoved { card: Card, from: CardCollectionId, to: CardCollectionId, to_index: Option<usize> },
+    CardAdded { card: Card, zone: CardCollectionId },
+    CardRemoved { card: Card, zone: CardCollectionId },
+
+    /// the cards that stayed in the deck are in a different order
+    DeckReordered { zone: CardCollectionId },
+}
+
+/// The structural differences between two states, from the first to the second.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, JsonSchema)]
+pub struct StateDiff {
+    pub differences: Vec<Difference>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// The differences expressed as mutations, in order. Differences that no mutation can make
+    /// (e.g. the rng state or a reordered deck) are left out, see [Difference::mutations].
+    pub fn mutations(&self) -> Vec<StaticStateMutation> {
+        // destroying a unit already puts its card and mods in its controller's discard, so those
+        // cards aren't created again, only moved if they ended up somewhere else
+        let mut destroyed_cards: HashMap<CardId, CardCollectionId> = HashMap::new();
+        for d in &self.differences {
+            if let Difference::PermanentRemoved { permanent: Permanent::Unit { common, card, mods }, .. } = d {
+                let discard = CardCollectionId::new_discard(common.controller_player_id);
+                for c in std::iter::once(card).chain(mods) {
+                    destroyed_cards.insert(c.card_id, discard);
+                }
+            }
+        }
+
+        self.differences.iter().flat_map(|d| match d {
+            Difference::CardAdded { card, zone } if destroyed_cards.contains_key(&card.card_id) => {
+                let discard = destroyed_cards[&card.card_id];
+                if *zone == discard {
+                    Vec::new()
+                } else {
+                    vec![StaticStateMutation::MoveCard(MoveCardMutation { from: discard, to: To::Unordered(*zone), card_id: card.card_id })]
+                }
+            }
+            d => d.mutations(),
+        }).collect()
+    }
+}
+
+impl State {
+    /// Compares this state to `other`, reporting what changed per region, player and zone.
+    pub fn diff(&self, other: &State) -> StateDiff {
+        let mut differences = Vec::new();
+
+        diff_game(&mut differences, self, other);
+        diff_regions(&mut differences, self, other);
+        diff_players(&mut differences, self, other);
+        diff_cards(&mut differences, self, other);
+
+        StateDiff {
+            differences
+        }
+    }
+}
+
+impl Difference {
+    /// The mutations that turn the first state into the second for this difference, or nothing
+    /// if the difference doesn't map onto any of the existing mutations.
+    pub fn mutations(&self) -> Vec<StaticStateMutation> {
+        match self {
+            Difference::Turn { to, .. } => {
+                vec![StaticStateMutation::SetTurn(SetTurnMutation { turn: *to })]
+            }
+            Difference::Result { to: Some(result), .. } => {
+                vec![StaticStateMutation::SetGameResult(SetGameResultMutation { result: result.clone() })]
+            }
+            Difference::Step { region_id, to, .. } => {
+                vec![StaticStateMutation::PhaseTransition(PhaseTransitionMutation { region_id: *region_id, to_phase: *to })]
+            }
+            Difference::Priority { region_id, to, .. } => {
+                // the player with priority is the last one pushed
+                let mut mutations = vec![StaticStateMutation::StackClearPriority(StackClearPriorityMutation { region_id: *region_id })];
+                mutations.extend(to.iter().map(|&player_id| {
+                    StaticStateMutation::StackAddPriority(StackAddPriorityMutation { region_id: *region_id, player_id })
+                }));
+                mutations
+            }
+            Difference::Stack { region_id, removed, added } => {
+                let resolved = removed.iter().map(|_| {
+                    StaticStateMutation::StackResolveEffect(StackResolveEffectMutation { region_id: *region_id })
+                });
+                let pushed = added.iter().map(|effect| {
+                    StaticStateMutation::StackPushEffect(StackPushEffectMutation { region_id: *region_id, effect: effect.clone() })
+                });
+                resolved.chain(pushed).collect()
+            }
+            Difference::PermanentAdded { region_id, permanent } => {
+                vec![StaticStateMutation::CreatePermanent(CreatePermanentMutation { region_id: *region_id, permanent: permanent.clone() })]
+            }
+            Difference::PermanentRemoved { permanent, .. } => {
+                vec![StaticStateMutation::DestroyPermanent(DestroyPermanentMutation { permanent_id: permanent.common().permanent_id })]
+            }
+            Difference::PermanentChanged { from, to, .. } => permanent_mutations(from, to),
+            Difference::Health { player_id, to, .. } => {
+                vec![StaticStateMutation::UpdatePlayerHealth(UpdatePlayerHealthMutation { player_id: *player_id, new_value: *to })]
+            }
+            Difference::Alive { player_id, to, .. } => {
+                vec![StaticStateMutation::UpdatePlayerAlive(UpdatePlayerAliveMutation { player_id: *player_id, new_value: *to })]
+            }
+            Difference::TurnRecord { player_id, from, to } => turn_record_mutations(*player_id, from, to),
+            Difference::PackCreated { player_id } => {
+                vec![StaticStateMutation::CreatePackForPlayer(CreatePackMutation { player_id: *player_id })]
+            }
+            Difference::CardMoved { card, from, to, to_index } => {
+                let to = match to_index {
+                    Some(idx) => To::Ordered(*to, Placement::ToIndex(*idx)),
+                    None => To::Unordered(*to),
+                };
+                vec![StaticStateMutation::MoveCard(MoveCardMutation { from: *from, to, card_id: card.card_id })]
+            }
+            Difference::CardAdded { card, zone } => {
+                vec![StaticStateMutation::CreateCard(CreateCardMutation { card_collection_id: *zone, card: card.clone() })]
+            }
+            Difference::CardRemoved { card, .. } => {
+                vec![StaticStateMutation::RemoveCard(RemoveCardMutation { card_id: card.card_id })]
+            }
+            Difference::Depth { .. } |
+            Difference::InitiativePlayer { .. } |
+            Difference::Result { to: None, .. } |
+            Difference::Rng |
+            Difference::IdFactory { .. } |
+            Difference::RegionAdded { .. } |
+            Difference::RegionRemoved { .. } |
+            Difference::Formation { .. } |
+            Difference::PlayerAdded { .. } |
+            Difference::PlayerRemoved { .. } |
+            Difference::PackRemoved { .. } |
+            Difference::DeckReordered { .. } => Vec::new(),
+        }
+    }
+
+    /// Describes the difference in a line of text, for people to read.
+    pub fn describe(&self, db: &CardPrototypeDatabase) -> String {
+        match self {
+            Difference::Depth { from, to } => format!("Depth {} → {}", from, to),
+            Difference::Turn { from, to } => format!("Turn {} → {}", from, to),
+            Difference::InitiativePlayer { from, to } => format!("Initiative Player {} → Player {}", from, to),
+            Difference::Result { from, to } => {
+                let result = |r: &Option<GameResult>| r.as_ref().map(|r| r.to_string()).unwrap_or("none".to_string());
+                format!("Result {} → {}", result(from), result(to))
+            }
+            Difference::Rng => "The random number generators differ".to_string(),
+            Difference::IdFactory { name, from, to } => format!("{} id factory {} → {}", name, from, to),
+            Difference::RegionAdded { region_id } => format!("Region {} added", region_id),
+            Difference::RegionRemoved { region_id } => format!("Region {} removed", region_id),
+            Difference::Step { region_id, from, to } => format!("Region {}: step {} → {}", region_id, from, to),
+            Difference::Priority { region_id, from, to } => {
+                let players = |p: &[PlayerId]| p.iter().map(|p| format!("Player {}", p)).collect::<Vec<String>>().join(", ");
+                format!("Region {}: priority [{}] → [{}]", region_id, players(from), players(to))
+            }
+            Difference::Stack { region_id, removed, added } => {
+                let texts = |e: &[Effect]| e.iter().map(|e| e.ability.effect_text.clone()).collect::<Vec<String>>().join("; ");
+                format!("Region {}: stack - [{}] + [{}]", region_id, texts(removed), texts(added))
+            }
+            Difference::Formation { region_id } => format!("Region {}: formations differ", region_id),
+            Difference::PermanentAdded { region_id, permanent } => {
+                format!("Region {}: + {} ({})", region_id, permanent_label(db, permanent), permanent.common().permanent_id)
+            }
+            Difference::PermanentRemoved { region_id, permanent } => {
+                format!("Region {}: - {} ({})", region_id, permanent_label(db, permanent), permanent.common().permanent_id)
+            }
+            Difference::PermanentChanged { region_id, to, .. } => {
+                format!("Region {}: {} ({}) changed", region_id, permanent_label(db, to), to.common().permanent_id)
+            }
+            Difference::PlayerAdded { player_id } => format!("Player {} added", player_id),
+            Difference::PlayerRemoved { player_id } => format!("Player {} removed", player_id),
+            Difference::Health { player_id, from, to } => format!("Player {}: health {} → {}", player_id, from, to),
+            Difference::Alive { player_id, from, to } => format!("Player {}: alive {} → {}", player_id, from, to),
+            Difference::TurnRecord { player_id, from, to } => format!("Player {}: turn record {:?} → {:?}", player_id, from, to),
+            Difference::PackCreated { player_id } => format!("Player {}: pack created", player_id),
+            Difference::PackRemoved { player_id } => format!("Player {}: pack removed", player_id),
+            Difference::CardMoved { card, from, to, .. } => {
+                format!("{} ({}) moved from {} to {}", prototype_name(db, card), card.card_id, collection_name(*from), collection_name(*to))
+            }
+            Difference::CardAdded { card, zone } => {
+                format!("{}: + {} ({})", collection_name(*zone), prototype_name(db, card), card.card_id)
+            }
+            Difference::CardRemoved { card, zone } => {
+                format!("{}: - {} ({})", collection_name(*zone), prototype_name(db, card), card.card_id)
+            }
+            Difference::DeckReordered { zone } => format!("{}: reordered", collection_name(*zone)),
+        }
+    }
+}
+
+/// True if the two values serialize the same, for types that can't be compared directly.
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_string(a).ok() == serde_json::to_string(b).ok()
+}
+
+fn diff_game(differences: &mut Vec<Difference>, a: &State, b: &State) {
+    if a.depth != b.depth {
+        differences.push(Difference::Depth { from: a.depth, to: b.depth });
+    }
+
+    if a.turn != b.turn {
+        differences.push(Difference::Turn { from: a.turn, to: b.turn });
+    }
+
+    if a.initiative_player != b.initiative_player {
+        differences.push(Difference::InitiativePlayer { from: a.initiative_player, to: b.initiative_player });
+    }
+
+    if a.result != b.result {
+        differences.push(Difference::Result { from: a.result.clone(), to: b.result.clone() });
+    }
+
+    if !same(&a.rand, &b.rand) {
+        differences.push(Difference::Rng);
+    }
+
+    let factories = [
+        ("Permanent", &a.permanent_id_factory, &b.permanent_id_factory),
+        ("Card", &a.card_id_factory, &b.card_id_factory),
+        ("Formation", &a.formation_id_factory, &b.formation_id_factory),
+    ];
+
+    for (name, from, to) in factories {
+        if from.0 != to.0 {
+            differences.push(Difference::IdFactory { name: name.to_string(), from: from.0, to: to.0 });
+        }
+    }
+}
+
+fn diff_regions(differences: &mut Vec<Difference>, a: &State, b: &State) {
+    for region in &a.regions {
+        if b.find_region(region.id).is_err() {
+            differences.push(Difference::RegionRemoved { region_id: region.id });
+        }
+    }
+
+    for to in &b.regions {
+        match a.find_region(to.id) {
+            Ok(from) => diff_region(differences, from, to),
+            Err(_) => differences.push(Difference::RegionAdded { region_id: to.id }),
+        }
+    }
+}
+
+fn diff_region(differences: &mut Vec<Difference>, a: &Region, b: &Region) {
+    let region_id = b.id;
+
+    if a.step != b.step {
+        differences.push(Difference::Step { region_id, from: a.step, to: b.step });
+    }
+
+    if a.stack.priority() != b.stack.priority() {
+        differences.push(Difference::Priority { region_id, from: a.stack.priority().to_vec(), to: b.stack.priority().to_vec() });
+    }
+
+    let (from_effects, to_effects) = (a.stack.effects(), b.stack.effects());
+    let common = from_effects.iter().zip(to_effects).take_while(|(x, y)| x == y).count();
+    if common != from_effects.len() || common != to_effects.len() {
+        differences.push(Difference::Stack {
+            region_id,
+            removed: from_effects[common..].iter().rev().cloned().collect(),
+            added: to_effects[common..].to_vec(),
+        });
+    }
+
+    if !same(&a.attacking_formation, &b.attacking_formation) || !same(&a.defending_formation, &b.defending_formation) {
+        differences.push(Difference::Formation { region_id });
+    }
+
+    let (from_permanents, to_permanents) = (a.permanents(), b.permanents());
+    for &from in &from_permanents {
+        let permanent_id = from.common().permanent_id;
+        match to_permanents.iter().find(|p| p.common().permanent_id == permanent_id) {
+            Some(&to) if !same(from, to) => {
+                differences.push(Difference::PermanentChanged { region_id, from: from.clone(), to: to.clone() });
+            }
+            Some(_) => {}
+            None => differences.push(Difference::PermanentRemoved { region_id, permanent: from.clone() }),
+        }
+    }
+
+    for &to in &to_permanents {
+        let permanent_id = to.common().permanent_id;
+        if !from_permanents.iter().any(|p| p.common().permanent_id == permanent_id) {
+            differences.push(Difference::PermanentAdded { region_id, permanent: to.clone() });
+        }
+    }
+}
+
+fn diff_players(differences: &mut Vec<Difference>, a: &State, b: &State) {
+    for player in a.players() {
+        if b.find_player(player.id).is_err() {
+            differences.push(Difference::PlayerRemoved { player_id: player.id });
+        }
+    }
+
+    for to in b.players() {
+        match a.find_player(to.id) {
+            Ok(from) => diff_player(differences, from, to),
+            Err(_) => differences.push(Difference::PlayerAdded { player_id: to.id }),
+        }
+    }
+}
+
+fn diff_player(differences: &mut Vec<Difference>, a: &Player, b: &Player) {
+    let player_id = b.id;
+
+    if a.health != b.health {
+        differences.push(Difference::Health { player_id, from: a.health, to: b.health });
+    }
+
+    if a.is_alive != b.is_alive {
+        differences.push(Difference::Alive { player_id, from: a.is_alive, to: b.is_alive });
+    }
+
+    if a.this_turn != b.this_turn {
+        differences.push(Difference::TurnRecord { player_id, from: a.this_turn.clone(), to: b.this_turn.clone() });
+    }
+
+    match (&a.pack, &b.pack) {
+        (None, Some(_)) => differences.push(Difference::PackCreated { player_id }),
+        (Some(_), None) => differences.push(Difference::PackRemoved { player_id }),
+        _ => {}
+    }
+}
+
+/// Every zone of cards in the state with its cards, in order.
+fn zones(state: &State) -> Vec<(CardCollectionId, Vec<&Card>)> {
+    let mut zones = Vec::new();
+
+    if let Some(deck) = &state.common_deck {
+        zones.push((deck.id, deck.iter().collect()));
+    }
+
+    for player in state.players() {
+        zones.push((player.hand.id, player.hand.iter().collect()));
+        zones.push((player.discard.id, player.discard.iter().collect()));
+        if let Some(pack) = &player.pack {
+            zones.push((pack.id, pack.iter().collect()));
+        }
+        if let Some(deck) = &player.own_deck {
+            zones.push((deck.id, deck.iter().collect()));
+        }
+    }
+
+    zones
+}
+
+fn is_deck(zone: CardCollectionId) -> bool {
+    zone.0[3] == 'D'
+}
+
+fn diff_cards(differences: &mut Vec<Difference>, a: &State, b: &State) {
+    let (from_zones, to_zones) = (zones(a), zones(b));
+
+    let locations = |zones: &[(CardCollectionId, Vec<&Card>)]| -> HashMap<CardId, (CardCollectionId, usize)> {
+        zones.iter()
+            .flat_map(|(zone, cards)| cards.iter().enumerate().map(move |(idx, c)| (c.card_id, (*zone, idx))))
+            .collect()
+    };
+    let (from_locations, to_locations) = (locations(&from_zones), locations(&to_zones));
+
+    for (zone, cards) in &from_zones {
+        for &card in cards {
+            match to_locations.get(&card.card_id) {
+                Some((to, to_idx)) if to != zone => {
+                    differences.push(Difference::CardMoved {
+                        card: card.clone(),
+                        from: *zone,
+                        to: *to,
+                        to_index: is_deck(*to).then_some(*to_idx),
+                    });
+                }
+                Some(_) => {}
+                None => differences.push(Difference::CardRemoved { card: card.clone(), zone: *zone }),
+            }
+        }
+    }
+
+    for (zone, cards) in &to_zones {
+        for &card in cards {
+            if !from_locations.contains_key(&card.card_id) {
+                differences.push(Difference::CardAdded { card: card.clone(), zone: *zone });
+            }
+        }
+
+        if !is_deck(*zone) {
+            continue
+        }
+
+        // only the order of the cards that were in the deck before and after matters here
+        let Some((_, from_cards)) = from_zones.iter().find(|(id, _)| id == zone) else {
+            continue
+        };
+        let stayed = |cards: &[&Card], other: &[&Card]| -> Vec<CardId> {
+            cards.iter().map(|c| c.card_id).filter(|id| other.iter().any(|o| o.card_id == *id)).collect()
+        };
+        if stayed(from_cards, cards) != stayed(cards, from_cards) {
+            differences.push(Difference::DeckReordered { zone: *zone });
+        }
+    }
+}
+
+fn permanent_mutations(from: &Permanent, to: &Permanent) -> Vec<StaticStateMutation> {
+    match (from, to) {
+        (Permanent::Resource { tapped: was_tapped, .. }, Permanent::Resource { common, tapped, .. }) if was_tapped != tapped => {
+            vec![StaticStateMutation::SetResourceTapped(SetResourceTappedMutation {
+                resource_permanent_id: common.permanent_id,
+                tapped: *tapped,
+            })]
+        }
+        (Permanent::Unit { mods: old_mods, .. }, Permanent::Unit { common, mods, .. }) if mods.starts_with(old_mods) => {
+            mods[old_mods.len()..].iter().map(|card| {
+                StaticStateMutation::AttachMod(AttachModMutation {
+                    host_permanent_id: common.permanent_id,
+                    card: card.clone(),
+                })
+            }).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn turn_record_mutations(player_id: PlayerId, from: &TurnRecord, to: &TurnRecord) -> Vec<StaticStateMutation> {
+    if to.is_empty() {
+        return vec![StaticStateMutation::ResetTurnRecord(ResetTurnRecordMutation { player_id })];
+    }
+
+    let only_resources_played = TurnRecord { resources_played: to.resources_played, ..from.clone() };
+    if only_resources_played == *to {
+        return vec![StaticStateMutation::UpdatePlayerResourcesPlayed(UpdatePlayerResourcesPlayedMutation {
+            player_id,
+            new_value: to.resources_played,
+        })];
+    }
+
+    let only_units_attacked = TurnRecord { units_attacked: to.units_attacked.clone(), ..from.clone() };
+    if only_units_attacked == *to && to.units_attacked.starts_with(&from.units_attacked) {
+        return vec![StaticStateMutation::RecordUnitsAttacked(RecordUnitsAttackedMutation {
+            player_id,
+            permanent_ids: to.units_attacked[from.units_attacked.len()..].to_vec(),
+        })];
+    }
+
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::ActionType;
+    use crate::game::diff::Difference;
+    use crate::game::state::GameMode;
+    use crate::game::state::mutation::create_permanent::CreatePermanentMutation;
+    use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
+    use crate::game::state::mutation::remove_card::RemoveCardMutation;
+    use crate::game::state::mutation::StaticStateMutation;
+    use crate::game::state::permanent::{Permanent, PermanentCommon, PermanentId};
+
+    #[test]
+    fn test_diff_replays_as_mutations() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        // pass priority until the draft step, where drafting moves cards between zones
+        let draft = loop {
+            let actions = game.valid_actions();
+            if let Some(draft) = actions.iter().find(|a| matches!(a.action_type(), ActionType::Draft(_))) {
+                break draft.clone();
+            }
+
+            let pass = actions.into_iter().find(|a| matches!(a.action_type(), ActionType::PassPriority(_))).unwrap();
+            game.apply_action(pass).unwrap();
+        };
+
+        let before = game.state.clone();
+        game.apply_action(draft).unwrap();
+
+        let diff = before.diff(&game.state);
+        assert!(before.diff(&before).is_empty());
+        assert!(diff.differences.iter().any(|d| matches!(d, Difference::Depth { .. })));
+        assert!(diff.differences.iter().any(|d| matches!(d, Difference::CardMoved { .. })), "{:?}", diff);
+
+        // replaying the mutations from the diff leaves only what no mutation can express
+        let mut replayed = before.clone();
+        for m in diff.mutations() {
+            replayed = replayed.mutate(&game.cards_db, &m).unwrap();
+        }
+
+        let remaining = replayed.diff(&game.state);
+        assert!(
+            remaining.differences.iter().all(|d| matches!(d, Difference::Depth { .. } | Difference::IdFactory { .. } | Difference::Rng)),
+            "{:?}", remaining,
+        );
+    }
+
+    #[test]
+    fn test_diff_replays_destroyed_unit() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let player = game.state.regions[0].players[0].clone();
+        let card = player.hand.iter().next().expect("a card in hand").clone();
+
+        // put a unit in play, with a card from the player's hand
+        let mut before = game.state.clone();
+        let permanent = Permanent::Unit {
+            common: PermanentCommon { permanent_id: PermanentId(before.permanent_id_factory.peek()), controller_player_id: player.id },
+            card: card.clone(),
+            mods: Vec::new(),
+        };
+        let permanent_id = permanent.common().permanent_id;
+        for m in [
+            StaticStateMutation::RemoveCard(RemoveCardMutation { card_id: card.card_id }),
+            StaticStateMutation::CreatePermanent(CreatePermanentMutation { region_id: game.state.regions[0].id, permanent }),
+        ] {
+            before = before.mutate(&game.cards_db, &m).unwrap();
+        }
+
+        let after = before.clone().mutate(&game.cards_db, &StaticStateMutation::DestroyPermanent(DestroyPermanentMutation { permanent_id })).unwrap();
+        let diff = before.diff(&after);
+        assert!(diff.differences.iter().any(|d| matches!(d, Difference::PermanentRemoved { .. })), "{:?}", diff);
+        assert!(diff.differences.iter().any(|d| matches!(d, Difference::CardAdded { .. })), "{:?}", diff);
+
+        // the unit's card goes to the discard once, rather than being created there a second time
+        let mut replayed = before.clone();
+        for m in diff.mutations() {
+            replayed = replayed.mutate(&game.cards_db, &m).unwrap();
+        }
+
+        assert!(replayed.validate(&game.cards_db).is_empty());
+        assert!(replayed.diff(&after).is_empty(), "{:?}", replayed.diff(&after));
+    }
+}
@@ -0,0 +1,122 @@
+//! A compact binary encoding for games, states, actions and mutations, as an alternative to json.
+//!
+//! The data is MessagePack, which (unlike most binary formats) supports the internally tagged
+//! enums and flattened fields the engine's types use. It's preceded by a header, the [MAGIC] bytes
+//! and the [FORMAT_VERSION], so that anything else, or data from an incompatible version, is
+//! rejected before it's decoded.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+/// the bytes every encoding starts with
+pub const MAGIC: [u8; 4] = *b"ALGM";
+
+/// bumped whenever the binary layout changes in a way older versions can't read
+pub const FORMAT_VERSION: u8 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+#[derive(Error, Debug)]
+pub enum EncodingError {
+    #[error("the data is not in the binary format, it doesn't start with the expected header")]
+    NotBinary,
+
+    #[error("the data is in version {found} of the binary format, only version {supported} is supported")]
+    UnsupportedVersion { found: u8, supported: u8 },
+
+    #[error("failed to encode: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+
+    #[error("failed to decode: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+}
+
+/// True if the data starts with the binary format's magic bytes, whatever its version.
+/// Useful to tell binary data apart from json, which can't start with them.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Encodes the value with the header in front.
+pub fn to_binary<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodingError> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(FORMAT_VERSION);
+
+    rmp_serde::encode::write(&mut bytes, value)?;
+
+    Ok(bytes)
+}
+
+/// Decodes a value written by [to_binary], checking the header first.
+pub fn from_binary<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, EncodingError> {
+    if !is_binary(bytes) || bytes.len() < HEADER_LEN {
+        return Err(EncodingError::NotBinary);
+    }
+
+    let version = bytes[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(EncodingError::UnsupportedVersion { found: version, supported: FORMAT_VERSION });
+    }
+
+    Ok(rmp_serde::from_slice(&bytes[HEADER_LEN..])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::Action;
+    use crate::game::encoding::{EncodingError, from_binary, FORMAT_VERSION, to_binary};
+    use crate::game::state::GameMode;
+    use crate::game::state::mutation::StaticStateMutation;
+    use crate::game::state::State;
+
+    #[test]
+    fn test_binary_round_trip() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        let mut actions: Vec<Action> = game.valid_actions().into_iter().collect();
+        actions.sort();
+        let mutations = game.apply_action(actions[0].clone()).unwrap();
+
+        // the database is a hash map, so it's written out in a different order each time
+        let decoded: Game = from_binary(&to_binary(&game).unwrap()).unwrap();
+        assert_eq!(json(&decoded.state), json(&game.state));
+        assert_eq!(decoded.action_history, game.action_history);
+        assert_eq!(decoded.cards_db.prototypes.len(), game.cards_db.prototypes.len());
+
+        let decoded: State = from_binary(&to_binary(&game.state).unwrap()).unwrap();
+        assert_eq!(json(&decoded), json(&game.state));
+
+        let decoded: Vec<Action> = from_binary(&to_binary(&actions).unwrap()).unwrap();
+        assert_eq!(decoded, actions);
+
+        let decoded: Vec<StaticStateMutation> = from_binary(&to_binary(&mutations).unwrap()).unwrap();
+        assert_eq!(json(&decoded), json(&mutations));
+
+        // the binary state is a fraction of the size of the json one
+        assert!(to_binary(&game.state).unwrap().len() * 2 < serde_json::to_vec(&game.state).unwrap().len());
+    }
+
+    #[test]
+    fn test_binary_header() {
+        let state = State::default();
+        let mut bytes = to_binary(&state).unwrap();
+
+        let json = serde_json::to_vec(&state).unwrap();
+        assert!(matches!(from_binary::<State>(&json), Err(EncodingError::NotBinary)));
+
+        bytes[4] = FORMAT_VERSION + 1;
+        assert!(matches!(
+            from_binary::<State>(&bytes),
+            Err(EncodingError::UnsupportedVersion { found, supported: FORMAT_VERSION }) if found == FORMAT_VERSION + 1
+        ));
+    }
+
+    /// the engine's types aren't comparable, so they're compared as json
+    fn json<T: Serialize>(value: &T) -> String {
+        serde_json::to_string(value).unwrap()
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::state::card::CardId;
+use crate::game::state::mutation::deal_damage::DamageTarget;
+use crate::game::state::mutation::StaticStateMutation;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::player::PlayerId;
+use crate::game::state::progression::Phase;
+use crate::game::state::region::RegionId;
+use crate::game::state::State;
+
+/// Events are the things that happened as a result of a mutation, which cards can react to.
+/// They mirror the triggers named by the card grammar, e.g. 'when I die' or 'after combat'.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(tag = "type")]
+pub enum Event {
+    /// A unit or unit token entered play.
+    UnitSpawned {
+        region_id: RegionId,
+        permanent: Permanent,
+    },
+
+    /// A unit or unit token was destroyed. The permanent is kept here as it was before it left
+    /// play, so that its own 'when I die' abilities can still be found.
+    UnitDied {
+        region_id: RegionId,
+        permanent: Permanent,
+    },
+
+    /// A permanent dealt damage to a player or a permanent, in combat or from an effect.
+    DamageDealt {
+        source: PermanentId,
+        target: DamageTarget,
+        amount: i32,
+    },
+
+    /// A player's health went down.
+    PlayerLostLife {
+        player_id: PlayerId,
+        amount: i32,
+    },
+
+    /// A region moved to a new phase/step.
+    PhaseEntered {
+        region_id: RegionId,
+        phase: Phase,
+    },
+
+    /// A card was attached to a unit as a mod. This covers both sides of
+    /// 'when I am modded or applied as a mod'.
+    Modded {
+        host_permanent_id: PermanentId,
+        mod_card_id: CardId,
+    },
+}
+
+impl StaticStateMutation {
+    /// The events raised by applying this mutation to `state`.
+    /// This must be called before the mutation is applied, as some events need to know what the
+    /// state looked like beforehand (e.g. the permanent that died).
+    pub fn events(&self, state: &State) -> Vec<Event> {
+        match self {
+            StaticStateMutation::CreatePermanent(m) if m.permanent.is_unit() => {
+                vec![Event::UnitSpawned {
+                    region_id: m.region_id,
+                    permanent: m.permanent.clone(),
+                }]
+            }
+            StaticStateMutation::DestroyPermanent(m) => {
+                let found = state.regions.iter().find_map(|r| {
+                    let permanent = r.permanents().into_iter().find(|p| p.common().permanent_id == m.permanent_id)?;
+                    Some((r.id, permanent))
+                });
+
+                match found {
+                    Some((region_id, permanent)) if permanent.is_unit() => {
+                        vec![Event::UnitDied {
+                            region_id,
+                            permanent: permanent.clone(),
+                        }]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            StaticStateMutation::UpdatePlayerHealth(m) => {
+                match state.find_player(m.player_id) {
+                    Ok(player) if m.new_value < player.health => {
+                        vec![Event::PlayerLostLife {
+                            player_id: m.player_id,
+                            amount: player.health - m.new_value,
+                        }]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            StaticStateMutation::DealDamage(m) => {
+                let mut events = vec![Event::DamageDealt {
+                    source: m.source_permanent_id,
+                    target: m.target,
+                    amount: m.amount,
+                }];
+
+                // damage causes life loss
+                if let DamageTarget::Player(player_id) = m.target {
+                    if m.amount > 0 {
+                        events.push(Event::PlayerLostLife {
+                            player_id,
+                            amount: m.amount,
+                        });
+                    }
+                }
+
+                events
+            }
+            StaticStateMutation::PhaseTransition(m) => {
+                vec![Event::PhaseEntered {
+                    region_id: m.region_id,
+                    phase: m.to_phase,
+                }]
+            }
+            StaticStateMutation::AttachMod(m) => {
+                vec![Event::Modded {
+                    host_permanent_id: m.host_permanent_id,
+                    mod_card_id: m.card.card_id,
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Commit-reveal seeds, so that the players of a game run by a server can check that its decks were
+//! shuffled fairly rather than arranged by the server.
+//!
+//! Before the game starts the server draws a secret [ServerSeed] and publishes its
+//! [SeedCommitment], the sha-256 hash of it. The players can each contribute some entropy of their
+//! own, and the game's seed is derived from the server seed and every contribution (see
+//! [SeedReveal::seed]), so that neither the server nor any one player picks it alone. When the game
+//! starts the server also publishes a commitment to all of these inputs
+//! ([SeedReveal::inputs_commitment]), so the contributions can't be changed afterwards. Once the game
+//! is over the server reveals the server seed along with the contributions, and the players check
+//! it against both commitments, find their own contribution in it, and [replay] the game's actions
+//! from the seed it gives.
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::game::{Game, GameOptions};
+use crate::game::action::Action;
+use crate::game::game_builder::NewGameError;
+use crate::game::state::error::StateError;
+use crate::game::state::GameMode;
+use crate::game::state::rng::AlgomancerRngSeed;
+
+/// the server's secret part of a game's seed
+pub type ServerSeed = [u8; 32];
+
+/// The sha-256 hash of what a game's seed is made from, as lowercase hex, published before it's
+/// revealed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SeedCommitment(pub String);
+
+/// What a game's seed was made from, revealed once the game is over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SeedReveal {
+    pub server_seed: ServerSeed,
+
+    /// the entropy the agents contributed, in the order it's hashed in
+    pub contributions: Vec<Contribution>,
+}
+
+/// Entropy an agent contributed to a game's seed, so that each player can find their own in the reveal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Contribution {
+    /// the id of the agent on the server that ran the game
+    pub agent_id: u64,
+    pub entropy: Vec<u8>,
+}
+
+#[derive(Error, Debug)]
+pub enum FairnessError {
+    #[error("the revealed server seed doesn't match the commitment")]
+    CommitmentMismatch,
+
+    #[error("the revealed contributions don't match the ones the game was started with")]
+    InputsMismatch,
+
+    #[error("the game couldn't be created from the revealed seed: {0:?}")]
+    NewGame(NewGameError),
+
+    #[error("action {index} of the game can't be taken when replaying it")]
+    InvalidAction { index: usize },
+
+    #[error("action {index} of the game failed when replaying it: {err}")]
+    ActionFailed { index: usize, err: StateError },
+}
+
+/// draws a server seed from the operating system's secure random number generator
+pub fn draw_server_seed() -> ServerSeed {
+    let mut server_seed = ServerSeed::default();
+    OsRng.fill_bytes(&mut server_seed);
+    server_seed
+}
+
+impl SeedCommitment {
+    pub fn of(server_seed: &ServerSeed) -> SeedCommitment {
+        SeedCommitment::from_hash(&Sha256::digest(server_seed))
+    }
+
+    fn from_hash(hash: &[u8]) -> SeedCommitment {
+        SeedCommitment(hash.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+impl SeedReveal {
+    /// The game's seed, the first 16 bytes of the sha-256 hash of the server seed followed by each
+    /// contribution, each prefixed with its length as a little endian u32.
+    pub fn seed(&self) -> AlgomancerRngSeed {
+        let mut hasher = Sha256::new();
+        hasher.update(self.server_seed);
+        for contribution in &self.contributions {
+            hasher.update((contribution.entropy.len() as u32).to_le_bytes());
+            hasher.update(&contribution.entropy);
+        }
+
+        let hash = hasher.finalize();
+        let mut seed = AlgomancerRngSeed::default();
+        let len = seed.len();
+        seed.copy_from_slice(&hash[..len]);
+        seed
+    }
+
+    /// The commitment to everything the seed is made from, published when the game starts: the
+    /// sha-256 hash of the server seed followed by each contribution's agent id, as a little endian
+    /// u64, and its entropy, prefixed with its length as a little endian u32.
+    pub fn inputs_commitment(&self) -> SeedCommitment {
+        let mut hasher = Sha256::new();
+        hasher.update(self.server_seed);
+        for contribution in &self.contributions {
+            hasher.update(contribution.agent_id.to_le_bytes());
+            hasher.update((contribution.entropy.len() as u32).to_le_bytes());
+            hasher.update(&contribution.entropy);
+        }
+
+        SeedCommitment::from_hash(&hasher.finalize())
+    }
+
+    /// checks the reveal against the commitment to the server seed, published before the game was
+    /// set up, and the commitment to all of the seed's inputs, published when it started
+    pub fn verify(&self, commitment: &SeedCommitment, inputs_commitment: &SeedCommitment) -> Result<(), FairnessError> {
+        if SeedCommitment::of(&self.server_seed) != *commitment {
+            return Err(FairnessError::CommitmentMismatch);
+        }
+
+        if self.inputs_commitment() != *inputs_commitment {
+            return Err(FairnessError::InputsMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Replays a game from a revealed seed, checking the reveal against the commitments first. The
+/// replayed game is what the server's game must have been, and can be compared with what was seen
+/// of it, e.g. the state as the player saw it at the end ([crate::game::state::State::visible_to]).
+pub fn replay(commitment: &SeedCommitment, inputs_commitment: &SeedCommitment, reveal: &SeedReveal, game_mode: GameMode, actions: &[Action]) -> Result<Game, FairnessError> {
+    reveal.verify(commitment, inputs_commitment)?;
+
+    let mut game = Game::new(&GameOptions { seed: reveal.seed(), game_mode }).map_err(FairnessError::NewGame)?;
+
+    for (index, action) in actions.iter().enumerate() {
+        if !game.valid_actions().contains(action) {
+            return Err(FairnessError::InvalidAction { index });
+        }
+
+        game.apply_action(action.clone()).map_err(|err| FairnessError::ActionFailed { index, err })?;
+    }
+
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::ActionType;
+    use crate::game::fairness::{Contribution, FairnessError, replay, SeedCommitment, SeedReveal};
+    use crate::game::state::GameMode;
+
+    #[test]
+    fn test_replay() {
+        let reveal = SeedReveal {
+            server_seed: [7; 32],
+            contributions: vec![
+                Contribution { agent_id: 1, entropy: vec![1, 2, 3] },
+                Contribution { agent_id: 2, entropy: vec![] },
+            ],
+        };
+        let commitment = SeedCommitment::of(&reveal.server_seed);
+        let inputs_commitment = reveal.inputs_commitment();
+
+        let mut game = Game::new(&GameOptions { seed: reveal.seed(), game_mode: GameMode::new_player_mode() }).unwrap();
+        for _ in 0..4 {
+            // conceding would end the game, and Action's ordering only puts passing priority first,
+            // so the json breaks the ties
+            let mut actions: Vec<_> = game.valid_actions().into_iter()
+                .filter(|a| !matches!(a.action_type(), ActionType::Concede(_)))
+                .collect();
+            actions.sort_by_cached_key(|a| serde_json::to_string(a).unwrap());
+            actions.sort();
+            game.apply_action(actions[0].clone()).unwrap();
+        }
+
+        let replayed = replay(&commitment, &inputs_commitment, &reveal, GameMode::new_player_mode(), &game.action_history).unwrap();
+        assert_eq!(serde_json::to_string(&replayed.state).unwrap(), serde_json::to_string(&game.state).unwrap());
+
+        // every contribution changes the seed
+        let mut other = reveal.clone();
+        other.contributions[1].entropy.push(0);
+        assert_ne!(other.seed(), reveal.seed());
+
+        let mut forged = reveal.clone();
+        forged.server_seed[0] = 0;
+        assert!(matches!(replay(&commitment, &inputs_commitment, &forged, GameMode::new_player_mode(), &game.action_history), Err(FairnessError::CommitmentMismatch)));
+
+        // the contributions can't be changed, or given to other agents, after the game started
+        let check = |reveal: &SeedReveal| replay(&commitment, &inputs_commitment, reveal, GameMode::new_player_mode(), &game.action_history);
+        assert!(matches!(check(&other), Err(FairnessError::InputsMismatch)));
+
+        let mut reattributed = reveal.clone();
+        reattributed.contributions[0].agent_id = 3;
+        assert_eq!(reattributed.seed(), reveal.seed());
+        assert!(matches!(check(&reattributed), Err(FairnessError::InputsMismatch)));
+    }
+}
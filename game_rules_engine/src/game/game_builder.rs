@@ -1,12 +1,14 @@
 
 use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
 use rand::prelude::SliceRandom;
 
 use crate::game::{Game, GameOptions};
 use database::{CardPrototypeDatabase, CardPrototypeId};
 use crate::game::game_builder::NewGameError::NotSupportedYet;
+use crate::game::schema::SCHEMA_VERSION;
 use crate::game::state::{GameMode, IdFactory, State};
 use crate::game::state::card::{Card, CardId};
 use algocore::CardType;
@@ -25,6 +27,8 @@ use crate::game::state::rng::AlgomancerRng;
 use crate::game::state::stack::Stack;
 use crate::game::state::team_configuration::TeamConfiguration;
 
+pub mod scenario;
+
 #[derive(Debug)]
 pub enum NewGameError {
     //InvalidConfiguration(&'static str),
@@ -40,13 +44,25 @@ impl fmt::Display for NewGameError {
     }
 }
 
-const CORE_DB_JSON: &str = include_str!("../../../resources/core_cards.json");
-
 impl Game {
+    /// creates a new game using the bundled core set database
     pub fn new(options: &GameOptions) -> Result<Game, NewGameError> {
+        let cards_db = match CardPrototypeDatabase::core() {
+            Ok(cards_db) => cards_db,
+            Err(_) => {
+                return Err(NewGameError::FailedToLoadResource)
+            }
+        };
+
+        Self::new_with_db(options, cards_db)
+    }
+
+    pub fn new_with_db(options: &GameOptions, cards_db: CardPrototypeDatabase) -> Result<Game, NewGameError> {
+        tracing::debug!(game_mode = %options.game_mode, "building a new game");
+
         match &options.game_mode {
             GameMode::LiveDraft { .. } => {
-                Self::build_live_draft(options)
+                Self::build_live_draft(options, cards_db)
             }
             game_mode => {
                 Err(NotSupportedYet(format!("the game mode [{:?}] is not yet supported", game_mode)))
@@ -54,28 +70,23 @@ impl Game {
         }
     }
 
-    fn build_live_draft(options: &GameOptions) -> Result<Game, NewGameError> {
+    fn build_live_draft(options: &GameOptions, cards_db: CardPrototypeDatabase) -> Result<Game, NewGameError> {
         if let GameMode::LiveDraft { team_configuration, .. } = &options.game_mode {
             let mut algomancer_rng = AlgomancerRng::new(options.seed);
 
-            let cards_db = {
-                match CardPrototypeDatabase::from_json(CORE_DB_JSON) {
-                    Ok(card_prototypes) => card_prototypes,
-                    Err(_) => {
-                        return Err(NewGameError::FailedToLoadResource)
-                    }
-                }
-            };
-
             let card_prototypes = &cards_db.prototypes;
 
             let mana_converter_prototype_id = {
                 card_prototypes.values().find(|c| c.name == "Dormant Resource").expect("a dormant resource in the db").prototype_id
             };
 
-            // takes all the non-token, non-resource card prototypes and maps them to card instances
+            // takes all the non-token, non-resource card prototypes and maps them to card instances,
+            // in order of prototype id, as the hash map's order changes from run to run
+            let mut sorted_prototypes: Vec<_> = card_prototypes.iter().collect();
+            sorted_prototypes.sort_by_key(|(&id, _)| id);
+
             let mut card_id_counter = 0;
-            let mut cards_for_deck: Vec<Card> = card_prototypes.iter()
+            let mut cards_for_deck: Vec<Card> = sorted_prototypes.into_iter()
                 .filter(|(_, c)| {
                     match c.card_type {
                         CardType::Resource(_) | CardType::UnitToken | CardType::SpellToken | CardType::Meta(_) => false,
@@ -100,7 +111,9 @@ impl Game {
             }
 
             let state = State {
+                schema_version: SCHEMA_VERSION,
                 depth: 0,
+                turn: 1,
                 game_mode: options.game_mode.clone(),
                 common_deck: Some(deck),
                 rand: algomancer_rng,
@@ -109,6 +122,7 @@ impl Game {
                 permanent_id_factory: IdFactory(1),
                 card_id_factory: IdFactory(card_id_counter + 1),
                 formation_id_factory: IdFactory(1),
+                result: None,
             };
 
             let mut game = Game {
@@ -150,7 +164,7 @@ impl Game {
                         permanents.push(permanent);
 
                         // initial resources count to the first turn
-                        player.resources_played_this_turn += 1;
+                        player.this_turn.resources_played += 1;
                     }
 
                     let mut region = Region{
@@ -167,7 +181,7 @@ impl Game {
                     region.step = Phase::PlanningPhase(PlanningPhaseStep::Draw);
                     region.stack.push_priority(player_id);
 
-                    game.state.regions.push(region);
+                    game.state.regions.push(Arc::new(region));
 
                     game.state.player_draw_n_cards(player_id, 16);
                 }
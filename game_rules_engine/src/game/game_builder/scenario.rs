@@ -0,0 +1,451 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use thiserror::Error;
+
+use algocore::CardType;
+use database::{CardPrototype, CardPrototypeDatabase};
+
+use crate::game::Game;
+use crate::game::schema::SCHEMA_VERSION;
+use crate::game::state::{GameMode, IdFactory, State};
+use crate::game::state::card::{Card, CardId};
+use crate::game::state::card_collection::CardCollectionId;
+use crate::game::state::deck::Deck;
+use crate::game::state::error::InvariantViolation;
+use crate::game::state::permanent::{Permanent, PermanentCommon, PermanentId};
+use crate::game::state::player::{Player, PlayerId, TeamId};
+use crate::game::state::progression::{Phase, PlanningPhaseStep};
+use crate::game::state::region::{Region, RegionId};
+use crate::game::state::rng::AlgomancerRng;
+use crate::game::state::stack::Stack;
+use crate::game::state::team_configuration::TeamConfiguration;
+use crate::game::state::unordered_cards::UnorderedCards;
+
+/// A description of a specific board state, written by hand (usually as JSON or TOML) to set up
+/// a game part way through, e.g. for tests or to reproduce a bug.
+///
+/// Cards are referred to by name. Every player gets their own region, with the same id as the
+/// player, and players are numbered from 1 in the order they're listed.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct Scenario {
+    /// the game mode, defaults to constructed with teams made up from the players' teams
+    #[serde(default)]
+    pub game_mode: Option<GameMode>,
+
+    #[serde(default)]
+    pub seed: [u8; 16],
+
+    #[serde(default = "default_turn")]
+    pub turn: u32,
+
+    /// the step every region is in
+    #[serde(default = "default_step")]
+    pub step: Phase,
+
+    #[serde(default = "default_initiative_player")]
+    pub initiative_player: PlayerId,
+
+    pub players: Vec<ScenarioPlayer>,
+
+    /// the cards players draw from in live draft, top first
+    #[serde(default)]
+    pub common_deck: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct ScenarioPlayer {
+    /// players without a team are each on a team of their own, numbered after the named teams
+    #[serde(default)]
+    pub team: Option<u8>,
+
+    #[serde(default = "default_health")]
+    pub health: i32,
+
+    #[serde(default)]
+    pub hand: Vec<String>,
+
+    /// the player's own deck, top first, only used outside of live draft
+    #[serde(default)]
+    pub deck: Vec<String>,
+
+    #[serde(default)]
+    pub discard: Vec<String>,
+
+    #[serde(default)]
+    pub pack: Option<Vec<String>>,
+
+    /// the units and tokens the player controls, outside of any formation
+    #[serde(default)]
+    pub battlefield: Vec<String>,
+
+    #[serde(default)]
+    pub resources: Vec<ScenarioResource>,
+
+    /// how many resources the player has played this turn
+    #[serde(default)]
+    pub resources_played: u8,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct ScenarioResource {
+    pub name: String,
+
+    #[serde(default)]
+    pub tapped: bool,
+}
+
+fn default_turn() -> u32 {
+    1
+}
+
+fn default_step() -> Phase {
+    Phase::PlanningPhase(PlanningPhaseStep::Draw)
+}
+
+fn default_initiative_player() -> PlayerId {
+    PlayerId(1)
+}
+
+fn default_health() -> i32 {
+    30
+}
+
+#[derive(Error, Debug)]
+pub enum ScenarioError {
+    #[error("failed to parse the scenario: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("failed to parse the scenario: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("the scenario has no players")]
+    NoPlayers,
+
+    #[error("the game mode [{0}] is not supported in scenarios")]
+    UnsupportedGameMode(GameMode),
+
+    #[error("there is no card named [{0}] in the database")]
+    UnknownCard(String),
+
+    #[error("[{0}] can't be on the battlefield, only units and tokens can")]
+    NotAPermanent(String),
+
+    #[error("[{0}] is not a resource")]
+    NotAResource(String),
+
+    #[error("player [{0}] has their own deck, but players draw from the common deck in live draft")]
+    OwnDeckInLiveDraft(PlayerId),
+
+    #[error("the scenario has a common deck, which is only used in live draft")]
+    CommonDeckOutsideLiveDraft,
+
+    #[error("the scenario results in an invalid state: {0:?}")]
+    Invalid(Vec<InvariantViolation>),
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Scenario, ScenarioError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Scenario, ScenarioError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// the game mode the scenario is played in
+    pub fn game_mode(&self) -> GameMode {
+        if let Some(game_mode) = &self.game_mode {
+            return game_mode.clone();
+        }
+
+        let teams = self.teams();
+        let num_teams = teams.iter().copied().max().unwrap_or(0);
+        let teams_of_players = (1..=num_teams)
+            .map(|team| teams.iter().filter(|&&t| t == team).count() as u8)
+            .collect();
+
+        GameMode::Constructed {
+            team_configuration: TeamConfiguration::Teams { teams_of_players },
+        }
+    }
+
+    /// the team of each player, in the order they're listed
+    pub fn teams(&self) -> Vec<u8> {
+        let mut next_team = self.players.iter().filter_map(|p| p.team).max().unwrap_or(0);
+        self.players.iter().map(|p| {
+            p.team.unwrap_or_else(|| {
+                next_team += 1;
+                next_team
+            })
+        }).collect()
+    }
+}
+
+impl Game {
+    /// Builds a game in the board state the scenario describes.
+    ///
+    /// Ids are handed out by the state's id factories in a fixed order (the common deck, then each
+    /// player's deck, hand, discard, pack, battlefield and resources), so the same scenario always
+    /// builds the same game.
+    pub fn from_scenario(scenario: &Scenario, cards_db: CardPrototypeDatabase) -> Result<Game, ScenarioError> {
+        if scenario.players.is_empty() {
+            return Err(ScenarioError::NoPlayers);
+        }
+
+        let game_mode = scenario.game_mode();
+        let is_live_draft = match &game_mode {
+            GameMode::LiveDraft { .. } => true,
+            GameMode::PreDraft { .. } | GameMode::Constructed { .. } => false,
+            GameMode::TeamDraft { .. } => return Err(ScenarioError::UnsupportedGameMode(game_mode)),
+        };
+
+        if !is_live_draft && !scenario.common_deck.is_empty() {
+            return Err(ScenarioError::CommonDeckOutsideLiveDraft);
+        }
+
+        let mut state = State {
+            schema_version: SCHEMA_VERSION,
+            depth: 0,
+            turn: scenario.turn,
+            game_mode: game_mode.clone(),
+            common_deck: None,
+            rand: AlgomancerRng::new(scenario.seed),
+            regions: Vec::new(),
+            initiative_player: scenario.initiative_player,
+            permanent_id_factory: IdFactory(0),
+            card_id_factory: IdFactory(0),
+            formation_id_factory: IdFactory(0),
+            result: None,
+        };
+
+        if is_live_draft {
+            let mut common_deck = Deck::new(CardCollectionId::new_common_deck());
+            for name in &scenario.common_deck {
+                common_deck.add_to_bottom(create_card(&cards_db, &mut state, name)?);
+            }
+            state.common_deck = Some(common_deck);
+        }
+
+        for (idx, (scenario_player, team)) in scenario.players.iter().zip(scenario.teams()).enumerate() {
+            let player_id = PlayerId((idx + 1) as u8);
+
+            if is_live_draft && !scenario_player.deck.is_empty() {
+                return Err(ScenarioError::OwnDeckInLiveDraft(player_id));
+            }
+
+            let region = build_region(&cards_db, &mut state, scenario_player, player_id, TeamId(team), scenario.step)?;
+            state.regions.push(Arc::new(region));
+        }
+
+        let violations = state.validate(&cards_db);
+        if !violations.is_empty() {
+            return Err(ScenarioError::Invalid(violations));
+        }
+
+        Ok(Game {
+            cards_db,
+            action_history: Vec::new(),
+            state,
+        })
+    }
+}
+
+/// builds the player's region, with the player in it and their permanents in play
+fn build_region(db: &CardPrototypeDatabase, state: &mut State, scenario_player: &ScenarioPlayer, player_id: PlayerId, team_id: TeamId, step: Phase) -> Result<Region, ScenarioError> {
+    let own_deck = if state.common_deck.is_some() {
+        None
+    } else {
+        let mut deck = Deck::new(CardCollectionId::new_deck(player_id));
+        for name in &scenario_player.deck {
+            deck.add_to_bottom(create_card(db, state, name)?);
+        }
+        Some(deck)
+    };
+
+    let mut player = Player::new(player_id, team_id, own_deck, None);
+    player.health = scenario_player.health;
+    player.is_alive = scenario_player.health > 0;
+    player.this_turn.resources_played = scenario_player.resources_played;
+
+    for name in &scenario_player.hand {
+        player.hand.add(create_card(db, state, name)?);
+    }
+
+    for name in &scenario_player.discard {
+        player.discard.add(create_card(db, state, name)?);
+    }
+
+    if let Some(names) = &scenario_player.pack {
+        let mut pack = UnorderedCards::new(CardCollectionId::new_pack(player_id));
+        for name in names {
+            pack.add(create_card(db, state, name)?);
+        }
+        player.pack = Some(pack);
+    }
+
+    let mut permanents = Vec::new();
+
+    for name in &scenario_player.battlefield {
+        let proto = find_prototype(db, name)?;
+        let permanent = match proto.card_type {
+            CardType::Unit(_) => {
+                let card = create_card(db, state, name)?;
+                Permanent::Unit {
+                    common: PermanentCommon {
+                        permanent_id: PermanentId(state.permanent_id_factory.proceed()),
+                        controller_player_id: player_id,
+                    },
+                    card,
+                    mods: Vec::new(),
+                }
+            }
+            CardType::UnitToken | CardType::SpellToken => Permanent::from_card_prototype(proto, player_id, state),
+            _ => return Err(ScenarioError::NotAPermanent(name.clone())),
+        };
+        permanents.push(permanent);
+    }
+
+    for resource in &scenario_player.resources {
+        let proto = find_prototype(db, &resource.name)?;
+        let CardType::Resource(_) = proto.card_type else {
+            return Err(ScenarioError::NotAResource(resource.name.clone()));
+        };
+
+        let mut permanent = Permanent::from_card_prototype(proto, player_id, state);
+        if let Permanent::Resource { tapped, .. } = &mut permanent {
+            *tapped = resource.tapped;
+        }
+        permanents.push(permanent);
+    }
+
+    let mut region = Region {
+        id: RegionId(player_id.0),
+        owner_player_id: player_id,
+        players: vec![player],
+        unformed_permanents: permanents,
+        attacking_formation: None,
+        defending_formation: None,
+        step,
+        stack: Stack::default(),
+    };
+
+    // as when the region enters the step, but the dead don't get priority
+    if scenario_player.health > 0 {
+        region.stack.push_priority(player_id);
+    }
+
+    Ok(region)
+}
+
+/// creates a card instance of the named prototype, with the next card id
+fn create_card(db: &CardPrototypeDatabase, state: &mut State, name: &str) -> Result<Card, ScenarioError> {
+    let proto = find_prototype(db, name)?;
+
+    Ok(Card {
+        card_id: CardId(state.card_id_factory.proceed()),
+        prototype_id: proto.prototype_id,
+    })
+}
+
+/// finds a prototype by its exact name, if several share the name, the one with the lowest id
+fn find_prototype<'a>(db: &'a CardPrototypeDatabase, name: &str) -> Result<&'a CardPrototype, ScenarioError> {
+    db.prototypes.values()
+        .filter(|p| p.name == name)
+        .min_by_key(|p| p.prototype_id.0)
+        .ok_or_else(|| ScenarioError::UnknownCard(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use database::CardPrototypeDatabase;
+    use crate::game::Game;
+    use crate::game::action::ActionType;
+    use crate::game::game_builder::scenario::{Scenario, ScenarioError};
+    use crate::game::state::permanent::Permanent;
+    use crate::game::state::player::PlayerId;
+    use crate::game::state::progression::{Phase, PlanningPhaseStep, Team};
+    use crate::game::state::GameMode;
+    use crate::game::state::team_configuration::TeamConfiguration;
+
+    const SCENARIO: &str = r#"
+        initiative_player = 2
+        step = { phase = "PlanningPhase", step = "Mana", team = "IT" }
+
+        [[players]]
+        team = 1
+        health = 12
+        hand = ["A Fast Pile of Rocks", "Fire Resource"]
+        deck = ["Water Resource", "Wood Resource"]
+        battlefield = ["A Fast Pile of Rocks", "Crystal"]
+        resources = [{ name = "Fire Resource", tapped = true }, { name = "Dormant Resource" }]
+        resources_played = 1
+
+        [[players]]
+        team = 2
+        pack = ["A Fast Pile of Rocks"]
+    "#;
+
+    #[test]
+    fn test_from_scenario() {
+        let scenario = Scenario::from_toml(SCENARIO).unwrap();
+        let game = Game::from_scenario(&scenario, CardPrototypeDatabase::core().unwrap()).unwrap();
+        let state = &game.state;
+
+        assert_eq!(state.initiative_player, PlayerId(2));
+        assert!(state.regions.iter().all(|r| r.step == Phase::PlanningPhase(PlanningPhaseStep::Mana(Team::IT))));
+
+        let player = state.find_player(PlayerId(1)).unwrap();
+        assert_eq!(player.health, 12);
+        assert_eq!(player.hand.len(), 2);
+        assert_eq!(player.own_deck.as_ref().unwrap().len(), 2);
+        assert_eq!(player.this_turn.resources_played, 1);
+
+        let region = &state.regions[0];
+        assert_eq!(region.permanents().len(), 4);
+        assert_eq!(region.resources().iter().filter(|r| matches!(r, Permanent::Resource { tapped: true, .. })).count(), 1);
+
+        // the same scenario always builds the same game
+        let json = serde_json::to_string(&scenario).unwrap();
+        let again = Game::from_scenario(&Scenario::from_json(&json).unwrap(), CardPrototypeDatabase::core().unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(state).unwrap(), serde_json::to_value(&again.state).unwrap());
+
+        // and the game can be played on from there
+        let mut game = game;
+        let pass = game.valid_actions().into_iter().find(|a| matches!(a.action_type(), ActionType::PassPriority(_))).expect("a pass");
+        assert_eq!(pass.issuer_player_id(), PlayerId(2));
+        game.apply_action(pass).unwrap();
+    }
+
+    #[test]
+    fn test_from_scenario_errors() {
+        let db = CardPrototypeDatabase::core().unwrap();
+
+        let scenario = Scenario::from_json(r#"{ "players": [{ "hand": ["Not A Card"] }] }"#).unwrap();
+        assert!(matches!(Game::from_scenario(&scenario, db.clone()), Err(ScenarioError::UnknownCard(name)) if name == "Not A Card"));
+
+        let scenario = Scenario::from_json(r#"{ "players": [{ "battlefield": ["Fire Resource"] }] }"#).unwrap();
+        assert!(matches!(Game::from_scenario(&scenario, db.clone()), Err(ScenarioError::NotAPermanent(_))));
+
+        let scenario = Scenario::from_json(r#"{ "initiative_player": 3, "players": [{}, {}] }"#).unwrap();
+        assert!(matches!(Game::from_scenario(&scenario, db), Err(ScenarioError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_players_without_a_team() {
+        let scenario = Scenario::from_json(r#"{ "players": [{}, {}] }"#).unwrap();
+        assert_eq!(scenario.teams(), vec![1, 2]);
+
+        // they come after the named teams
+        let scenario = Scenario::from_json(r#"{ "players": [{}, { "team": 1 }, {}, { "team": 1 }] }"#).unwrap();
+        assert_eq!(scenario.teams(), vec![2, 1, 3, 1]);
+        assert!(matches!(
+            scenario.game_mode(),
+            GameMode::Constructed { team_configuration: TeamConfiguration::Teams { teams_of_players } } if teams_of_players == vec![2, 1, 1]
+        ));
+
+        let game = Game::from_scenario(&Scenario::from_json(r#"{ "players": [{}, {}] }"#).unwrap(), CardPrototypeDatabase::core().unwrap()).unwrap();
+        assert_ne!(game.state.find_player(PlayerId(1)).unwrap().team_id, game.state.find_player(PlayerId(2)).unwrap().team_id);
+    }
+}
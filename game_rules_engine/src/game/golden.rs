@@ -0,0 +1,155 @@
+//! Golden-file regression tests for the rules.
+//!
+//! Each case in `golden/` is a `<name>.case.json` file with a starting point (a new game or a
+//! scenario) and a list of actions to apply. Next to it, `<name>.expected.json` holds the mutations
+//! each action produced and a hash of the resulting state. A rules change that alters any of these
+//! fails the test.
+//!
+//! After an intentional rules change, regenerate the expected files with
+//! `UPDATE_GOLDEN=1 cargo test -p algomancer_gre golden` and review the diff.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use database::CardPrototypeDatabase;
+use crate::game::{Game, GameOptions};
+use crate::game::action::Action;
+use crate::game::game_builder::scenario::Scenario;
+use crate::game::state::mutation::StaticStateMutation;
+use crate::game::state::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Start {
+    New(GameOptions),
+    Scenario(Scenario),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct GoldenCase {
+    description: String,
+    start: Start,
+    actions: Vec<Action>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct GoldenExpected {
+    state_hash: String,
+
+    /// the mutations applied by each action, in order
+    mutations: Vec<Vec<StaticStateMutation>>,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+/// A 64-bit FNV-1a hash of the serialized state, which is stable across runs and platforms,
+/// unlike the std hasher.
+fn state_hash(state: &State) -> String {
+    let bytes = serde_json::to_vec(state).expect("a serializable state");
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}", hash)
+}
+
+fn run_case(case: &GoldenCase, db: &CardPrototypeDatabase) -> Result<GoldenExpected, String> {
+    let mut game = match &case.start {
+        Start::New(options) => Game::new_with_db(options, db.clone()).map_err(|e| e.to_string())?,
+        Start::Scenario(scenario) => Game::from_scenario(scenario, db.clone()).map_err(|e| e.to_string())?,
+    };
+
+    let mut mutations = Vec::new();
+    for (idx, action) in case.actions.iter().enumerate() {
+        let description = action.describe(&game.state, db);
+        let applied = game.apply_action(action.clone())
+            .map_err(|e| format!("action {} ({}) failed: {}", idx, description, e))?;
+        mutations.push(applied);
+    }
+
+    Ok(GoldenExpected {
+        state_hash: state_hash(&game.state),
+        mutations,
+    })
+}
+
+/// describes where the actual results first stray from the expected ones, or None if they don't
+fn first_difference(expected: &GoldenExpected, actual: &GoldenExpected) -> Option<String> {
+    // mutations aren't comparable, but their serialized forms are
+    let same = |e: &Vec<StaticStateMutation>, a: &Vec<StaticStateMutation>| {
+        serde_json::to_value(e).unwrap() == serde_json::to_value(a).unwrap()
+    };
+
+    if let Some(idx) = expected.mutations.iter().zip(&actual.mutations).position(|(e, a)| !same(e, a)) {
+        return Some(format!("the mutations of action {} differ", idx));
+    }
+
+    if expected.mutations.len() != actual.mutations.len() {
+        return Some(format!("expected {} actions, but {} were applied", expected.mutations.len(), actual.mutations.len()));
+    }
+
+    if expected.state_hash != actual.state_hash {
+        return Some(format!("the state hash {} differs from the expected {}", actual.state_hash, expected.state_hash));
+    }
+
+    None
+}
+
+#[test]
+fn test_golden() {
+    let db = CardPrototypeDatabase::core().unwrap();
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut case_paths: Vec<PathBuf> = fs::read_dir(golden_dir()).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with(".case.json"))
+        .collect();
+    case_paths.sort();
+    assert!(!case_paths.is_empty(), "no golden cases found");
+
+    let mut failures = Vec::new();
+    for case_path in case_paths {
+        let name = case_path.file_name().unwrap().to_string_lossy().trim_end_matches(".case.json").to_string();
+        let expected_path = case_path.with_file_name(format!("{}.expected.json", name));
+
+        let case: GoldenCase = serde_json::from_str(&fs::read_to_string(&case_path).unwrap()).unwrap();
+        let actual = match run_case(&case, &db) {
+            Ok(actual) => actual,
+            Err(err) => {
+                failures.push(format!("{}: {}", name, err));
+                continue;
+            }
+        };
+
+        if update {
+            fs::write(&expected_path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+            continue;
+        }
+
+        let expected: GoldenExpected = match fs::read_to_string(&expected_path) {
+            Ok(json) => serde_json::from_str(&json).unwrap(),
+            Err(_) => {
+                failures.push(format!("{}: missing {}", name, expected_path.display()));
+                continue;
+            }
+        };
+
+        if let Some(difference) = first_difference(&expected, &actual) {
+            failures.push(format!("{} ({}): {}", name, case.description, difference));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "golden cases failed, run with UPDATE_GOLDEN=1 to regenerate them if the change is intentional:\n{}",
+        failures.join("\n")
+    );
+}
+
@@ -1,22 +1,39 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use state::rng::AlgomancerRngSeed;
 
 use crate::game::action::{Action};
 use database::CardPrototypeDatabase;
 use crate::game::state::GameMode;
+use crate::game::state::error::StateError;
+use crate::game::state::game_result::{GameOverReason, GameResult};
+use crate::game::state::mutation::set_game_result::SetGameResultMutation;
+use crate::game::state::mutation::StaticStateMutation;
+use crate::game::state::player::TeamId;
 
 pub mod state;
 pub mod action;
 pub mod game_builder;
 pub mod state_based_actions;
+pub mod event;
+pub mod trigger;
+pub mod render;
+pub mod describe;
+pub mod diff;
+pub mod encoding;
+pub mod schema;
+pub mod fairness;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg(test)]
+mod golden;
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct GameOptions {
     pub seed: AlgomancerRngSeed,
     pub game_mode: GameMode,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 pub struct Game {
     pub state: state::State,
     pub action_history: Vec<Action>,
@@ -24,11 +41,46 @@ pub struct Game {
 }
 
 impl Game {
-    // is_over returns true if there are any living players on at least two teams
+    /// True once the game has a result, after which no more actions can be taken.
     pub fn is_over(&self) -> bool {
-        let filtered = self.state.team_ids().into_iter().filter(|&t| !self.state.living_players_in_team(t).is_empty());
-        let count = filtered.take(2).count();
-        
-        count < 2
+        self.state.is_over()
+    }
+
+    /// Ends a game that has run for too long, e.g. past a turn limit set by the host.
+    /// The living team(s) with the most combined health win, if every living team is tied the
+    /// game is a draw.
+    pub fn end_by_timeout(&mut self) -> Result<Vec<StaticStateMutation>, StateError> {
+        if self.is_over() {
+            return Err(StateError::GameIsOver);
+        }
+
+        let team_health = |team_id: TeamId| -> i32 {
+            self.state.living_players_in_team(team_id).iter().map(|p| p.health).sum()
+        };
+
+        let living_team_ids = self.state.living_team_ids();
+        let best_health = living_team_ids.iter().map(|&t| team_health(t)).max();
+
+        let mut winning_team_ids: Vec<TeamId> = living_team_ids.iter()
+            .filter(|&&t| Some(team_health(t)) == best_health)
+            .copied()
+            .collect();
+
+        if living_team_ids.len() > 1 && winning_team_ids.len() == living_team_ids.len() {
+            winning_team_ids.clear();
+        }
+
+        let mutation = StaticStateMutation::SetGameResult(SetGameResultMutation {
+            result: GameResult {
+                winning_team_ids,
+                reason: GameOverReason::Timeout,
+                turn: self.state.turn,
+                depth: self.state.depth,
+            }
+        });
+
+        self.state = self.state.clone().mutate(&self.cards_db, &mutation)?;
+
+        Ok(vec![mutation])
     }
 }
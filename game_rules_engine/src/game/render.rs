@@ -0,0 +1,170 @@
+use std::fmt::Write;
+use database::CardPrototypeDatabase;
+use crate::game::state::card::Card;
+use crate::game::state::formation::Formation;
+use crate::game::state::permanent::Permanent;
+use crate::game::state::player::{Player, PlayerId};
+use crate::game::state::region::Region;
+use crate::game::state::State;
+
+impl State {
+    /// Renders the state as text, for people to read.
+    ///
+    /// When rendered from a player's perspective, only that player's hand is shown, everyone
+    /// else's is reduced to a count. Without a perspective, every hand is shown.
+    pub fn render(&self, db: &CardPrototypeDatabase, perspective: Option<PlayerId>) -> String {
+        let mut out = String::new();
+
+        // writing to a string can't fail
+        self.render_to(&mut out, db, perspective).expect("rendered state");
+
+        out
+    }
+
+    fn render_to(&self, out: &mut String, db: &CardPrototypeDatabase, perspective: Option<PlayerId>) -> std::fmt::Result {
+        writeln!(out, "Turn {} (depth {}) | {} | initiative: Player {}", self.turn, self.depth, self.game_mode, self.initiative_player)?;
+
+        if let Some(result) = &self.result {
+            writeln!(out, "Game over: {}", result)?;
+        }
+
+        for region in &self.regions {
+            writeln!(out)?;
+            render_region(out, region, db, perspective)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn render_region(out: &mut String, region: &Region, db: &CardPrototypeDatabase, perspective: Option<PlayerId>) -> std::fmt::Result {
+    writeln!(out, "Region {} | {}", region.id, region.step)?;
+
+    let priority: Vec<String> = region.stack.priority().iter().map(|p| format!("Player {}", p)).collect();
+    writeln!(out, "  Priority: [{}]", priority.join(", "))?;
+
+    for effect in region.stack.effects().iter().rev() {
+        writeln!(out, "  Stack: {} (Player {}): {}", effect.source_permanent_id, effect.controller_player_id, effect.ability.effect_text)?;
+    }
+
+    for player in &region.players {
+        render_player(out, player, db, perspective)?;
+    }
+
+    let resources = region.resources();
+    if !resources.is_empty() {
+        let untapped = resources.iter().filter(|r| matches!(r, Permanent::Resource { tapped: false, .. })).count();
+        writeln!(out, "  Resources: {} untapped, {} tapped", untapped, resources.len() - untapped)?;
+        for resource in resources {
+            let tapped = matches!(resource, Permanent::Resource { tapped: true, .. });
+            writeln!(out, "    {}{}", permanent_name(resource, db), if tapped { " (tapped)" } else { "" })?;
+        }
+    }
+
+    let permanents: Vec<&Permanent> = region.unformed_permanents.iter().filter(|p| !matches!(p, Permanent::Resource { .. })).collect();
+    if !permanents.is_empty() {
+        writeln!(out, "  Permanents:")?;
+        for permanent in permanents {
+            writeln!(out, "    {}", permanent_name(permanent, db))?;
+        }
+    }
+
+    if let Some(formation) = &region.attacking_formation {
+        writeln!(out, "  Attacking formation:")?;
+        render_formation(out, formation, db)?;
+    }
+
+    if let Some(formation) = &region.defending_formation {
+        writeln!(out, "  Defending formation:")?;
+        render_formation(out, &formation.formation, db)?;
+    }
+
+    Ok(())
+}
+
+fn render_player(out: &mut String, player: &Player, db: &CardPrototypeDatabase, perspective: Option<PlayerId>) -> std::fmt::Result {
+    let pack = player.pack.as_ref().map(|p| p.len()).unwrap_or(0);
+
+    writeln!(
+        out,
+        "  Player {} (team {}){}: {} health | hand {} | discard {} | pack {}",
+        player.id,
+        player.team_id.0,
+        if player.is_alive { "" } else { " [dead]" },
+        player.health,
+        player.hand.len(),
+        player.discard.len(),
+        pack,
+    )?;
+
+    let hand_visible = perspective.is_none_or(|p| p == player.id);
+    if hand_visible && !player.hand.is_empty() {
+        writeln!(out, "    Hand: {}", card_names(player.hand.iter(), db))?;
+    }
+
+    if !player.discard.is_empty() {
+        writeln!(out, "    Discard: {}", card_names(player.discard.iter(), db))?;
+    }
+
+    Ok(())
+}
+
+/// Formations are drawn as a grid, front row on top.
+fn render_formation(out: &mut String, formation: &Formation<Permanent>, db: &CardPrototypeDatabase) -> std::fmt::Result {
+    for row in [formation.front_row(), formation.back_row()] {
+        let cells: Vec<String> = row.iter().map(|cell| {
+            match cell {
+                None => "[ ]".to_string(),
+                Some(permanent) => format!("[{}]", permanent_name(permanent, db)),
+            }
+        }).collect();
+
+        writeln!(out, "    {}", cells.join(" "))?;
+    }
+
+    Ok(())
+}
+
+fn permanent_name(permanent: &Permanent, db: &CardPrototypeDatabase) -> String {
+    let name = &db.prototypes[&permanent.prototype_id()].name;
+    let common = permanent.common();
+    format!("{} #{} (Player {})", name, common.permanent_id.0, common.controller_player_id)
+}
+
+fn card_names<'a>(cards: impl Iterator<Item=&'a Card>, db: &CardPrototypeDatabase) -> String {
+    let names: Vec<&str> = cards.map(|c| db.prototypes[&c.prototype_id].name.as_str()).collect();
+    names.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::state::GameMode;
+
+    #[test]
+    fn test_render_hides_opponents_hands() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let db = &game.cards_db;
+
+        let me = &game.state.regions[0].players[0];
+        let opponent = &game.state.regions[1].players[0];
+        let card_name = |card_id| {
+            let card = me.hand.iter().chain(opponent.hand.iter()).find(|c| c.card_id == card_id).unwrap();
+            db.prototypes[&card.prototype_id].name.clone()
+        };
+
+        let my_card = card_name(me.hand.iter().next().unwrap().card_id);
+        let their_hand_line = format!(
+            "Hand: {}",
+            opponent.hand.iter().map(|c| card_name(c.card_id)).collect::<Vec<String>>().join(", ")
+        );
+
+        let everything = game.state.render(db, None);
+        assert!(everything.contains(&their_hand_line));
+
+        let mine = game.state.render(db, Some(me.id));
+        assert!(mine.contains(&my_card));
+        assert!(!mine.contains(&their_hand_line));
+        assert!(mine.contains(&format!("Player {} (team {}): 30 health | hand {}", opponent.id, opponent.team_id.0, opponent.hand.len())));
+    }
+}
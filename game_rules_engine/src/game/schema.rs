@@ -0,0 +1,247 @@
+//! Versioning of serialized states, so that games saved by older versions can still be loaded.
+//!
+//! Every serialized [State] carries its `schema_version`, and a [Game] carries it in its state.
+//! Documents written before states were versioned have none, and count as version 0. As a json
+//! document is loaded, the [MIGRATIONS] are applied to it one at a time, each upgrading it from one
+//! version to the next, until it's in the current [SCHEMA_VERSION]. A document from a newer version
+//! than this build supports is rejected with [SchemaError::TooNew] rather than failing to parse.
+//!
+//! Changing the serialized layout of the state, e.g. adding a field to a player, means adding a
+//! migration to the end of [MIGRATIONS] which upgrades documents in the previous layout, which in
+//! turn bumps the version.
+//!
+//! Binary documents aren't migrated, they're checked against their own format version instead
+//! (see [crate::game::encoding]).
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+use serde_json::value::RawValue;
+use thiserror::Error;
+
+use crate::game::Game;
+use crate::game::state::State;
+
+/// a state's fields as json, except for the [RAW_FIELDS]
+type Fields = Map<String, Value>;
+
+type Migration = fn(&mut Fields) -> Result<(), SchemaError>;
+
+/// The migrations in order, the one at index n upgrades a state from version n to version n + 1.
+const MIGRATIONS: &[Migration] = &[
+    v1_add_turns_results_and_mods,
+];
+
+/// the version of the layout states are serialized in by this build
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// fields which are left as they are while migrating, as a [Value] can't hold the rng's u128 state
+const RAW_FIELDS: &[&str] = &["rand"];
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("the document is in schema version {found}, which is newer than the latest version this build supports ({supported}), it has to be loaded by a newer build")]
+    TooNew { found: u32, supported: u32 },
+
+    #[error("the document can't be migrated, {0}")]
+    Malformed(&'static str),
+
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Reads a state from json, upgrading it to the current schema version first if it's older.
+pub fn state_from_json(json: &str) -> Result<State, SchemaError> {
+    let state: Box<RawValue> = serde_json::from_str(json)?;
+    Ok(serde_json::from_str(migrate_state(&state)?.get())?)
+}
+
+/// Reads a game from json, upgrading its state to the current schema version first if it's older.
+pub fn game_from_json(json: &str) -> Result<Game, SchemaError> {
+    let mut fields: BTreeMap<String, Box<RawValue>> = serde_json::from_str(json)?;
+
+    let state = fields.get("state").ok_or(SchemaError::Malformed("a game needs a state"))?;
+    let state = migrate_state(state)?;
+    fields.insert("state".to_string(), state);
+
+    Ok(serde_json::from_str(&serde_json::to_string(&fields)?)?)
+}
+
+/// The schema version of a serialized state, 0 if it predates versioning.
+pub fn schema_version(state: &RawValue) -> Result<u32, SchemaError> {
+    #[derive(serde::Deserialize)]
+    struct Versioned {
+        #[serde(default)]
+        schema_version: u32,
+    }
+
+    let versioned: Versioned = serde_json::from_str(state.get())?;
+    Ok(versioned.schema_version)
+}
+
+/// Upgrades a serialized state to the current schema version, one migration at a time.
+pub fn migrate_state(state: &RawValue) -> Result<Box<RawValue>, SchemaError> {
+    let version = schema_version(state)?;
+
+    if version > SCHEMA_VERSION {
+        return Err(SchemaError::TooNew { found: version, supported: SCHEMA_VERSION });
+    }
+
+    if version == SCHEMA_VERSION {
+        return Ok(state.to_owned());
+    }
+
+    let mut raw: BTreeMap<String, Box<RawValue>> = serde_json::from_str(state.get())?;
+    let mut fields = Fields::new();
+    for (name, value) in &raw {
+        if !RAW_FIELDS.contains(&name.as_str()) {
+            fields.insert(name.clone(), serde_json::from_str(value.get())?);
+        }
+    }
+
+    for (from_version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut fields)?;
+        fields.insert("schema_version".to_string(), json!(from_version + 1));
+    }
+
+    tracing::debug!(from = version, to = SCHEMA_VERSION, "migrated a state");
+
+    for (name, value) in fields {
+        raw.insert(name, serde_json::value::to_raw_value(&value)?);
+    }
+
+    Ok(serde_json::value::to_raw_value(&raw)?)
+}
+
+/// Version 1 added the schema version itself, along with the turn counter, the game result, each
+/// player's record of the current turn and the mods attached to units.
+fn v1_add_turns_results_and_mods(state: &mut Fields) -> Result<(), SchemaError> {
+    state.entry("turn").or_insert(json!(1));
+    state.entry("result").or_insert(Value::Null);
+
+    let regions = state.get_mut("regions")
+        .and_then(Value::as_array_mut)
+        .ok_or(SchemaError::Malformed("a state needs a list of regions"))?;
+
+    for region in regions {
+        let players = region.get_mut("players")
+            .and_then(Value::as_array_mut)
+            .ok_or(SchemaError::Malformed("a region needs a list of players"))?;
+
+        for player in players {
+            let player = player.as_object_mut().ok_or(SchemaError::Malformed("a player must be an object"))?;
+
+            // spelled out rather than serialized, so that later changes to the record don't change
+            // what this migration produces
+            player.entry("this_turn").or_insert(json!({
+                "resources_played": 0,
+                "cards_drawn": 0,
+                "units_attacked": [],
+                "units_died": [],
+            }));
+        }
+
+        add_missing_mods(region);
+    }
+
+    Ok(())
+}
+
+/// units may be anywhere in a region, formed or not, so every object in it is checked
+fn add_missing_mods(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let is_unit = object.get("type") == Some(&json!("Unit")) && object.contains_key("card");
+            if is_unit {
+                object.entry("mods").or_insert(json!([]));
+            }
+
+            object.values_mut().for_each(add_missing_mods);
+        }
+        Value::Array(values) => values.iter_mut().for_each(add_missing_mods),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value;
+    use serde_json::value::RawValue;
+
+    use database::CardPrototypeDatabase;
+    use crate::game::Game;
+    use crate::game::game_builder::scenario::Scenario;
+    use crate::game::schema::{game_from_json, SCHEMA_VERSION, SchemaError, state_from_json};
+
+    fn game_with_a_unit() -> Game {
+        let scenario = Scenario::from_json(r#"{ "players": [{ "battlefield": ["A Fast Pile of Rocks"] }, {}] }"#).unwrap();
+        Game::from_scenario(&scenario, CardPrototypeDatabase::core().unwrap()).unwrap()
+    }
+
+    /// rewrites a state's json as a version 0 state, from before the fields version 1 added
+    fn as_version_0(state_json: &str) -> String {
+        let mut fields: BTreeMap<String, Box<RawValue>> = serde_json::from_str(state_json).unwrap();
+        for name in ["schema_version", "turn", "result"] {
+            fields.remove(name);
+        }
+
+        let mut regions: Value = serde_json::from_str(fields["regions"].get()).unwrap();
+        for region in regions.as_array_mut().unwrap() {
+            for player in region["players"].as_array_mut().unwrap() {
+                player.as_object_mut().unwrap().remove("this_turn").unwrap();
+            }
+
+            for permanent in region["unformed_permanents"].as_array_mut().unwrap() {
+                permanent.as_object_mut().unwrap().remove("mods");
+            }
+        }
+
+        fields.insert("regions".to_string(), serde_json::value::to_raw_value(&regions).unwrap());
+        let json = serde_json::to_string(&fields).unwrap();
+        assert!(!json.contains("this_turn") && !json.contains("mods"));
+        json
+    }
+
+    #[test]
+    fn test_migrate_version_0() {
+        let game = game_with_a_unit();
+        let state_json = serde_json::to_string(&game.state).unwrap();
+
+        // the migrated state is exactly the original, including the rng's state
+        let migrated = state_from_json(&as_version_0(&state_json)).unwrap();
+        assert_eq!(migrated.schema_version, SCHEMA_VERSION);
+        assert_eq!(serde_json::to_string(&migrated).unwrap(), state_json);
+
+        let mut game_json: BTreeMap<String, Box<RawValue>> = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        game_json.insert("state".to_string(), RawValue::from_string(as_version_0(&state_json)).unwrap());
+        let migrated = game_from_json(&serde_json::to_string(&game_json).unwrap()).unwrap();
+        assert_eq!(serde_json::to_string(&migrated.state).unwrap(), state_json);
+        assert_eq!(migrated.valid_actions(), game.valid_actions());
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let game = game_with_a_unit();
+        let state_json = serde_json::to_string(&game.state).unwrap().replacen(
+            &format!(r#""schema_version":{}"#, SCHEMA_VERSION),
+            &format!(r#""schema_version":{}"#, SCHEMA_VERSION + 1),
+            1,
+        );
+
+        let supported = SCHEMA_VERSION;
+        let too_new = |result: Result<_, SchemaError>| {
+            matches!(result, Err(SchemaError::TooNew { found, supported: s }) if found == supported + 1 && s == supported)
+        };
+
+        assert!(too_new(state_from_json(&state_json).map(|_| ())));
+
+        let game_json = serde_json::to_string(&game).unwrap().replacen(
+            &format!(r#""schema_version":{}"#, SCHEMA_VERSION),
+            &format!(r#""schema_version":{}"#, SCHEMA_VERSION + 1),
+            1,
+        );
+        assert!(too_new(game_from_json(&game_json).map(|_| ())));
+    }
+}
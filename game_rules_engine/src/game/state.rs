@@ -1,6 +1,8 @@
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use rng::{AlgomancerRng, AlgomancerRngSeed};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::state::card_collection::CardCollectionId;
 
 use crate::game::state::player::PlayerId;
@@ -8,6 +10,8 @@ use crate::game::state::region::Region;
 use algocore::Faction;
 use crate::game::state::deck::Deck;
 use crate::game::state::team_configuration::TeamConfiguration;
+use crate::game::state::game_result::GameResult;
+use crate::game::schema::SCHEMA_VERSION;
 
 pub mod card;
 pub mod player;
@@ -23,9 +27,13 @@ pub mod error;
 pub mod deck;
 pub mod unordered_cards;
 pub mod stack;
+pub mod game_result;
+pub mod turn_record;
+pub mod validate;
+pub mod visibility;
 
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum GameMode {
     LiveDraft {
         selected_deck_types: Vec<Faction>,
@@ -74,9 +82,18 @@ impl GameMode {
             selected_deck_types: vec![Faction::Earth, Faction::Wood]
         }
     }
+
+    pub fn team_configuration(&self) -> &TeamConfiguration {
+        match self {
+            GameMode::LiveDraft { team_configuration, .. } |
+            GameMode::PreDraft { team_configuration } |
+            GameMode::TeamDraft { team_configuration } |
+            GameMode::Constructed { team_configuration } => team_configuration,
+        }
+    }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct IdFactory(pub usize);
 
 impl IdFactory {
@@ -90,23 +107,39 @@ impl IdFactory {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct State {
+    /// the version of the layout the state is serialized in, older documents are upgraded to the
+    /// current one as they're loaded (see [crate::game::schema])
+    pub schema_version: u32,
+
     pub depth: usize,
+
+    /// the current turn, starting at 1. A new turn starts when the regions enter the refresh step
+    pub turn: u32,
+
     pub game_mode: GameMode,
     pub rand: AlgomancerRng,
     pub common_deck: Option<Deck>,
-    pub regions: Vec<Region>,
+
+    /// regions are shared between clones of the state until one of them changes the region, which
+    /// then gets its own copy (see [Arc::make_mut]), so cloning a state per action stays cheap
+    pub regions: Vec<Arc<Region>>,
     pub initiative_player: PlayerId,
     pub permanent_id_factory: IdFactory,
     pub card_id_factory: IdFactory,
     pub formation_id_factory: IdFactory,
+
+    /// set once the game has ended, after which no more actions can be taken
+    pub result: Option<GameResult>,
 }
 
 impl Default for State {
     fn default() -> Self {
         State {
+            schema_version: SCHEMA_VERSION,
             depth: 0,
+            turn: 1,
             game_mode: GameMode::new_player_mode(),
             rand: AlgomancerRng::new(AlgomancerRngSeed::default()),
             common_deck: Some(Deck::new(CardCollectionId::new_common_deck())),
@@ -115,14 +148,16 @@ impl Default for State {
             permanent_id_factory: IdFactory(0),
             card_id_factory: IdFactory(0),
             formation_id_factory: IdFactory(0),
+            result: None,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    
-
+    use std::sync::Arc;
+    use crate::game::{Game, GameOptions};
+    use crate::game::state::GameMode;
     use crate::game::state::rng::{AlgomancerRng, AlgomancerRngSeed};
 
     // utility function to avoid code duplication
@@ -172,4 +207,23 @@ mod tests {
         // so this should not be equal
         assert_ne!(r1_val, r2_val);
     }
+
+    #[test]
+    fn test_clone_shares_regions() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let mut state = game.state.clone();
+        assert!(state.regions.iter().zip(&game.state.regions).all(|(a, b)| Arc::ptr_eq(a, b)));
+
+        // changing a player only copies their region, the other regions are still shared
+        let player_id = state.regions[0].players[0].id;
+        state.find_player_mut(player_id).unwrap().health -= 1;
+
+        assert!(!Arc::ptr_eq(&state.regions[0], &game.state.regions[0]));
+        assert!(Arc::ptr_eq(&state.regions[1], &game.state.regions[1]));
+        assert_eq!(game.state.find_player(player_id).unwrap().health, state.find_player(player_id).unwrap().health + 1);
+
+        // the serialized form is the same as if the regions and zones weren't shared
+        let region = serde_json::to_value(&game.state.regions[0]).unwrap();
+        assert!(region["players"][0]["hand"]["cards"].is_array());
+    }
 }
@@ -2,6 +2,7 @@ use std::fmt::{Display, Formatter};
 use std::hash::{Hash};
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase, CardPrototypeId};
 
 use crate::game::state::{GameMode, State};
@@ -16,7 +17,7 @@ use crate::game::state::player::Player;
 use algocore::ResourceType;
 use crate::game::state::unordered_cards::UnorderedCards;
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, Ord, PartialOrd)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, Ord, PartialOrd, JsonSchema)]
 pub struct CardId(pub usize);
 
 impl Display for CardId {
@@ -26,12 +27,28 @@ impl Display for CardId {
 }
 
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Card {
     pub card_id: CardId,
     pub prototype_id: CardPrototypeId,
 }
 
+impl Card {
+    /// A card whose identity is hidden from the player a state is filtered for, see
+    /// [State::visible_to]. Card ids start at 1, so a hidden card is told apart by its id of 0, and
+    /// its prototype is [CardPrototypeId::HIDDEN], which no prototype in the database has.
+    pub fn hidden() -> Card {
+        Card {
+            card_id: CardId(0),
+            prototype_id: CardPrototypeId::HIDDEN,
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.card_id == CardId(0) && self.prototype_id == CardPrototypeId::HIDDEN
+    }
+}
+
 
 pub enum FindCardResult<'a> {
     InPlayerHand(&'a Player, &'a UnorderedCards, &'a Card),
@@ -62,7 +79,7 @@ impl Card {
 
 impl State {
 
-    pub fn find_card(&self, card_id: CardId) -> Result<FindCardResult, EntityNotFoundError> {
+    pub fn find_card(&self, card_id: CardId) -> Result<FindCardResult<'_>, EntityNotFoundError> {
 
         // see if the card is in a player's hand or discard
         for player in self.players() {
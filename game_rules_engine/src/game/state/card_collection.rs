@@ -1,6 +1,9 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash};
 
+use schemars::gen::SchemaGenerator;
+use schemars::JsonSchema;
+use schemars::schema::{InstanceType, Schema, SchemaObject, StringValidation};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::game::state::error::{EntityNotFoundError, StateError};
@@ -54,6 +57,24 @@ impl<'de> Deserialize<'de> for CardCollectionId {
     }
 }
 
+impl JsonSchema for CardCollectionId {
+    fn schema_name() -> String {
+        "CardCollectionId".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                min_length: Some(4),
+                max_length: Some(4),
+                pattern: None,
+            })),
+            ..Default::default()
+        }.into()
+    }
+}
+
 impl CardCollectionId {
     pub fn from_string(str: &str) -> CardCollectionId {
         let mut char_array: [char; 4] = Default::default();
@@ -119,7 +140,7 @@ pub enum FindCardCollectionResult<'a> {
 }
 
 impl State {
-    pub fn find_card_collection_mut(&mut self, id: CardCollectionId) -> Result<FindCardCollectionMutResult, StateError> {
+    pub fn find_card_collection_mut(&mut self, id: CardCollectionId) -> Result<FindCardCollectionMutResult<'_>, StateError> {
         match self.find_card_collection(id)? {
             FindCardCollectionResult::CommonDeck(_) => {
                 let collection = self.common_deck.as_mut().unwrap();
@@ -148,7 +169,7 @@ impl State {
         }
     }
 
-    pub fn find_card_collection(&self, id: CardCollectionId) -> Result<FindCardCollectionResult, StateError> {
+    pub fn find_card_collection(&self, id: CardCollectionId) -> Result<FindCardCollectionResult<'_>, StateError> {
         // check if it's the common deck
         if let Some(deck) = &self.common_deck {
             if deck.id == id {
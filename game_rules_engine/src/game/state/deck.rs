@@ -1,22 +1,26 @@
 use std::collections::vec_deque::Iter;
 use std::collections::VecDeque;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::state::card::{Card, CardId};
 use crate::game::state::card_collection::CardCollectionId;
 use crate::game::state::error::{EntityNotFoundError, StateError};
 
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+/// An ordered pile of cards. The cards are shared between clones of the deck until one of them
+/// changes, so cloning a state doesn't copy every deck in it.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Deck {
     pub id: CardCollectionId,
-    cards: VecDeque<Card>
+    cards: Arc<VecDeque<Card>>
 }
 
 impl Deck {
     pub fn new(id: CardCollectionId) -> Deck {
         Deck {
             id,
-            cards: VecDeque::new()
+            cards: Arc::new(VecDeque::new())
         }
     }
 
@@ -24,23 +28,36 @@ impl Deck {
         self.cards.iter()
     }
 
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// replaces every card with a [Card::hidden] one, so only the number of cards is known
+    pub fn hide(&mut self) {
+        self.cards = Arc::new(self.cards.iter().map(|_| Card::hidden()).collect());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
     pub fn draw(&mut self) -> Result<Card, StateError> {
         if self.cards.is_empty() {
             return Err(StateError::Other);
         }
-        Ok(self.cards.remove(0).unwrap())
+        Ok(Arc::make_mut(&mut self.cards).remove(0).unwrap())
     }
 
     pub fn insert(&mut self, idx: usize, card: Card) {
-        self.cards.insert(idx, card)
+        Arc::make_mut(&mut self.cards).insert(idx, card)
     }
 
     pub fn add_to_bottom(&mut self, card: Card) {
-        self.cards.push_back(card)
+        Arc::make_mut(&mut self.cards).push_back(card)
     }
 
     pub fn add_to_top(&mut self, card: Card) {
-        self.cards.push_back(card)
+        Arc::make_mut(&mut self.cards).push_back(card)
     }
 
     pub fn remove(&mut self, card_id: CardId) -> Result<Card, StateError>  {
@@ -48,6 +65,6 @@ impl Deck {
             None => return Err(EntityNotFoundError::Card(card_id).into()),
             Some(idx) => idx
         };
-        Ok(self.cards.remove(idx).unwrap())
+        Ok(Arc::make_mut(&mut self.cards).remove(idx).unwrap())
     }
 }
@@ -16,6 +16,9 @@ pub enum CardNotPlayableError {
     CannotPlayMoreResources(CardId),
     MustBePlayedFromHand(CardId),
     CannotCastANonSpellTokenPermanentFromPlay(PermanentId),
+    TokensOnlyExistInPlay(CardId),
+    PermanentNotUnderPlayersControl(PermanentId),
+    SpellTokenNotCastableNow(PermanentId),
 }
 
 #[derive(Error, Debug)]
@@ -35,6 +38,9 @@ pub enum EntityNotFoundError {
 
     #[error("permanent [{0}] not found in state")]
     Permanent(PermanentId),
+
+    #[error("token prototype [{0}] not found in the database")]
+    TokenPrototype(String),
 }
 
 #[derive(Error, Debug)]
@@ -57,11 +63,33 @@ pub enum DraftError {
 }
 
 
+#[derive(Error, Debug)]
+#[error("mod error")]
+pub enum ModError {
+    #[error("card [{0}] cannot be applied as a mod")]
+    NotAMod(CardId),
+
+    #[error("card [{0}] is an augment, and must be applied from its owner's discard")]
+    AugmentMustBeAppliedFromDiscard(CardId),
+
+    #[error("card [{0}] is a graft, and must be applied from its owner's hand")]
+    GraftMustBeAppliedFromHand(CardId),
+
+    #[error("permanent [{0}] is not a unit controlled by the player applying the mod")]
+    InvalidHost(PermanentId),
+
+    #[error("permanent [{0}] cannot hold any more grafts")]
+    GraftLimitReached(PermanentId),
+}
+
+
 #[derive(Error, Debug)]
 #[error("invalid action")]
 pub enum InvalidActionError {
     InvalidDraft(DraftError),
     InvalidRecycle,
+    InvalidMod(ModError),
+    PlayerIsDead(PlayerId),
 }
 
 
@@ -83,6 +111,53 @@ pub enum StateError {
     #[error("player [{0:?}] cannot pass priority")]
     CannotPassPriority(PlayerId),
 
+    #[error("the game is over, no more actions can be taken")]
+    GameIsOver,
+
+    #[error("applying the action left the state inconsistent: {0:?}")]
+    InvariantViolation(Vec<InvariantViolation>),
+
     #[error("some other error")]
     Other
 }
+
+/// A way a state is inconsistent, see [crate::game::state::State::validate]. States the engine
+/// produces never have these, they come from states that were written or edited by hand.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum InvariantViolation {
+    #[error("card [{0}] appears more than once")]
+    DuplicateCardId(CardId),
+
+    #[error("permanent [{0}] appears more than once")]
+    DuplicatePermanentId(PermanentId),
+
+    #[error("player [{0}] appears more than once")]
+    DuplicatePlayerId(PlayerId),
+
+    #[error("region [{0}] appears more than once")]
+    DuplicateRegionId(RegionId),
+
+    #[error("the {name} id factory is at {value}, below the existing id {max_id}")]
+    IdFactoryBehindIds { name: &'static str, value: usize, max_id: usize },
+
+    #[error("card [{card_id}] has a prototype [{prototype_id}] that isn't in the database")]
+    UnknownCardPrototype { card_id: CardId, prototype_id: usize },
+
+    #[error("permanent [{permanent_id}] has a prototype [{prototype_id}] that isn't in the database")]
+    UnknownPermanentPrototype { permanent_id: PermanentId, prototype_id: usize },
+
+    #[error("permanent [{permanent_id}] is controlled by player [{player_id}], who isn't in the game")]
+    UnknownController { permanent_id: PermanentId, player_id: PlayerId },
+
+    #[error("card collection [{found}] of player [{player_id}] should have the id [{expected}]")]
+    WrongCardCollectionId { player_id: PlayerId, expected: CardCollectionId, found: CardCollectionId },
+
+    #[error("region [{region_id}] gives priority to player [{player_id}], who isn't in the region")]
+    PriorityPlayerNotInRegion { region_id: RegionId, player_id: PlayerId },
+
+    #[error("region [{region_id}] gives priority to player [{player_id}], who is dead")]
+    PriorityPlayerIsDead { region_id: RegionId, player_id: PlayerId },
+
+    #[error("the initiative player [{0}] isn't in the game")]
+    UnknownInitiativePlayer(PlayerId),
+}
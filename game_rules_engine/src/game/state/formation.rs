@@ -1,14 +1,15 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 use crate::game::state::formation::FormationError::{AttackingFormationMustBeCommitted, FormationAlreadyCommitted, InvalidPosition};
 use crate::game::state::formation::FormationPos::{BackRow, FrontRow};
 use crate::game::state::formation::RemoveError::NothingToRemove;
 use crate::game::state::player::PlayerId;
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 pub struct FormationId(pub usize);
 
-#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Formation<T> {
     formation_id: FormationId,
     owner_player_id: PlayerId,
@@ -19,7 +20,7 @@ pub struct Formation<T> {
 }
 
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum FormationPos {
     FrontRow(usize),
     BackRow(usize),
@@ -115,12 +116,50 @@ impl<'a, T> Formation<T> {
         Ok(())
     }
 
+    pub fn id(&self) -> FormationId {
+        self.formation_id
+    }
+
     pub fn cells_iter<'b>(&'b self) -> Box<dyn Iterator<Item=&'b T> + 'b> {
         let top_iter = self.top_row.iter().filter_map(|cell| cell.as_ref());
         let bot_iter = self.bot_row.iter().filter_map(|cell| cell.as_ref());
         Box::new(top_iter.chain(bot_iter))
     }
 
+    pub fn cells_iter_mut<'b>(&'b mut self) -> Box<dyn Iterator<Item=&'b mut T> + 'b> {
+        let top_iter = self.top_row.iter_mut().filter_map(|cell| cell.as_mut());
+        let bot_iter = self.bot_row.iter_mut().filter_map(|cell| cell.as_mut());
+        Box::new(top_iter.chain(bot_iter))
+    }
+
+    /// Takes the first cell matching `f` out of the formation, such as a unit leaving play.
+    /// Unlike remove_at, this works on committed formations, which keep their shape and are left
+    /// with an empty cell.
+    pub fn take_where(&mut self, f: impl Fn(&T) -> bool) -> Option<T> {
+        let pos = if let Some(col) = self.top_row.iter().position(|cell| cell.as_ref().is_some_and(&f)) {
+            FrontRow(col)
+        } else {
+            BackRow(self.bot_row.iter().position(|cell| cell.as_ref().is_some_and(&f))?)
+        };
+
+        if self.committed {
+            match pos {
+                FrontRow(col) => self.top_row[col].take(),
+                BackRow(col) => self.bot_row[col].take(),
+            }
+        } else {
+            self.remove_at(pos).ok()
+        }
+    }
+
+    pub fn front_row(&self) -> &[Option<T>] {
+        &self.top_row
+    }
+
+    pub fn back_row(&self) -> &[Option<T>] {
+        &self.bot_row
+    }
+
     pub fn get_at(&'a self, pos: FormationPos) -> Result<Option<&'a T>, FormationError> {
         match pos {
             FrontRow(col) => {
@@ -268,7 +307,7 @@ impl<'a, T> Formation<T> {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DefensiveFormation<T> {
     pub attacking_formation_id: FormationId,
     pub formation: Formation<T>,
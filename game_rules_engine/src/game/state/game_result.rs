@@ -0,0 +1,102 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::state::player::{PlayerId, TeamId};
+use crate::game::state::State;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Eq, PartialEq, JsonSchema)]
+pub enum GameOverReason {
+    /// every player on the losing team(s) ran out of health
+    Health,
+
+    /// the last player standing between the losing team(s) and defeat conceded
+    Concession,
+
+    /// the losing player(s) could not draw the cards required by the draw step
+    DeckOut,
+
+    /// the game was stopped from outside before anyone won, e.g. by a turn limit
+    Timeout,
+}
+
+impl Display for GameOverReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameOverReason::Health => write!(f, "health"),
+            GameOverReason::Concession => write!(f, "concession"),
+            GameOverReason::DeckOut => write!(f, "deck-out"),
+            GameOverReason::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+/// The outcome of a finished game, recorded in the state once the game ends.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq, JsonSchema)]
+pub struct GameResult {
+    /// the teams that won the game, empty if the game was a draw
+    pub winning_team_ids: Vec<TeamId>,
+    pub reason: GameOverReason,
+
+    /// the turn and depth of the state when the game ended
+    pub turn: u32,
+    pub depth: usize,
+}
+
+impl GameResult {
+    pub fn is_draw(&self) -> bool {
+        self.winning_team_ids.is_empty()
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_draw() {
+            write!(f, "Draw")?;
+        } else {
+            write!(f, "Team ")?;
+            for (idx, team_id) in self.winning_team_ids.iter().enumerate() {
+                write!(f, "{}", team_id.0)?;
+                if idx < self.winning_team_ids.len() - 1 {
+                    write!(f, " & ")?;
+                }
+            }
+            write!(f, " won")?;
+        }
+
+        write!(f, " by {} on turn {}", self.reason, self.turn)
+    }
+}
+
+impl State {
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// the teams that still have at least one living player
+    pub fn living_team_ids(&self) -> Vec<TeamId> {
+        self.team_ids().into_iter().filter(|&t| !self.living_players_in_team(t).is_empty()).collect()
+    }
+
+    /// The result of the game if every player in `player_ids` were to die now,
+    /// or None if at least two teams would still have living players.
+    pub fn result_if_players_die(&self, player_ids: &[PlayerId], reason: GameOverReason) -> Option<GameResult> {
+        if self.is_over() {
+            return None;
+        }
+
+        let remaining: Vec<TeamId> = self.team_ids().into_iter()
+            .filter(|&t| self.living_players_in_team(t).iter().any(|p| !player_ids.contains(&p.id)))
+            .collect();
+
+        if remaining.len() >= 2 {
+            return None;
+        }
+
+        Some(GameResult {
+            winning_team_ids: remaining,
+            reason,
+            turn: self.turn,
+            depth: self.depth,
+        })
+    }
+}
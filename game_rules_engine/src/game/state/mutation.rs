@@ -10,9 +10,17 @@ pub mod player_mutations;
 pub mod remove_card;
 pub mod create_permanent;
 pub mod set_resource_tapped;
+pub mod attach_mod;
+pub mod destroy_permanent;
+pub mod deal_damage;
+pub mod stack_push_effect;
+pub mod stack_resolve_effect;
+pub mod set_game_result;
+pub mod turn_mutations;
 
 use std::fmt::{Debug};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::{phase_transition, stack_add_priority, stack_clear_priority};
 use database::{CardPrototypeDatabase};
 use crate::game::state::error::StateError;
@@ -27,6 +35,13 @@ use crate::game::state::mutation::stack_add_priority::StackAddPriorityMutation;
 use crate::game::state::mutation::stack_clear_priority::StackClearPriorityMutation;
 use crate::game::state::mutation::stack_pass_priority::StackPassPriorityMutation;
 use crate::game::state::mutation::set_resource_tapped::SetResourceTappedMutation;
+use crate::game::state::mutation::attach_mod::AttachModMutation;
+use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
+use crate::game::state::mutation::deal_damage::DealDamageMutation;
+use crate::game::state::mutation::stack_push_effect::StackPushEffectMutation;
+use crate::game::state::mutation::stack_resolve_effect::StackResolveEffectMutation;
+use crate::game::state::mutation::set_game_result::SetGameResultMutation;
+use crate::game::state::mutation::turn_mutations::{RecordUnitsAttackedMutation, ResetTurnRecordMutation, SetTurnMutation};
 use crate::game::state::region::RegionId;
 use crate::game::state::State;
 
@@ -88,7 +103,7 @@ impl StateMutation {
 /// This list of individual small changes in state can be serialized and sent to clients so
 /// that they can coherently display what happened between the application of the last action
 /// and the next state.
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(tag = "type")]
 pub enum StaticStateMutation {
     StackClearPriority(StackClearPriorityMutation),
@@ -104,6 +119,15 @@ pub enum StaticStateMutation {
     UpdatePlayerResourcesPlayed(UpdatePlayerResourcesPlayedMutation),
     CreatePermanent(CreatePermanentMutation),
     SetResourceTapped(SetResourceTappedMutation),
+    AttachMod(AttachModMutation),
+    DestroyPermanent(DestroyPermanentMutation),
+    DealDamage(DealDamageMutation),
+    StackPushEffect(StackPushEffectMutation),
+    StackResolveEffect(StackResolveEffectMutation),
+    SetGameResult(SetGameResultMutation),
+    SetTurn(SetTurnMutation),
+    ResetTurnRecord(ResetTurnRecordMutation),
+    RecordUnitsAttacked(RecordUnitsAttackedMutation),
 }
 
 
@@ -124,6 +148,15 @@ impl State {
             StaticStateMutation::UpdatePlayerResourcesPlayed(m) => m.mutate_state(self, db),
             StaticStateMutation::CreatePermanent(m) => m.mutate_state(self, db),
             StaticStateMutation::SetResourceTapped(m) => m.mutate_state(self, db),
+            StaticStateMutation::AttachMod(m) => m.mutate_state(self, db),
+            StaticStateMutation::DestroyPermanent(m) => m.mutate_state(self, db),
+            StaticStateMutation::DealDamage(m) => m.mutate_state(self, db),
+            StaticStateMutation::StackPushEffect(m) => m.mutate_state(self, db),
+            StaticStateMutation::StackResolveEffect(m) => m.mutate_state(self, db),
+            StaticStateMutation::SetGameResult(m) => m.mutate_state(self, db),
+            StaticStateMutation::SetTurn(m) => m.mutate_state(self, db),
+            StaticStateMutation::ResetTurnRecord(m) => m.mutate_state(self, db),
+            StaticStateMutation::RecordUnitsAttacked(m) => m.mutate_state(self, db),
         }
     }
 }
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use crate::game::state::card::Card;
+use crate::game::state::error::{InvalidActionError, ModError, StateError};
+use crate::game::state::mutation::StateMutator;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::State;
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct AttachModMutation {
+    pub host_permanent_id: PermanentId,
+    pub card: Card,
+}
+
+impl StateMutator for AttachModMutation {
+    fn mutate_state(&self, mut state: State, _db: &CardPrototypeDatabase) -> Result<State, StateError> {
+        let id = self.host_permanent_id;
+        let permanent = state.find_permanent_mut(id)?;
+        if let Permanent::Unit { mods, .. } = permanent {
+            mods.push(self.card.clone());
+        } else {
+            return Err(InvalidActionError::InvalidMod(ModError::InvalidHost(id)).into());
+        }
+        Ok(state)
+    }
+}
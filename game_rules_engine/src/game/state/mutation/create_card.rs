@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 use crate::game::state::card::Card;
 use crate::game::state::card_collection::{CardCollectionId, FindCardCollectionMutResult};
@@ -6,7 +7,7 @@ use crate::game::state::error::StateError;
 use crate::game::state::mutation::StateMutator;
 use crate::game::state::State;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct CreateCardMutation {
     pub card_collection_id: CardCollectionId,
     pub card: Card
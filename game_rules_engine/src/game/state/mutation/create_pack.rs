@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 
 use crate::game::state::card_collection::{CardCollectionId};
@@ -8,7 +9,7 @@ use crate::game::state::player::PlayerId;
 use crate::game::state::State;
 use crate::game::state::unordered_cards::UnorderedCards;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct CreatePackMutation {
     pub player_id: PlayerId,
 }
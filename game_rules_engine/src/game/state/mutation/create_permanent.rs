@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 
 
@@ -8,7 +9,7 @@ use crate::game::state::permanent::Permanent;
 use crate::game::state::region::RegionId;
 use crate::game::state::State;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct CreatePermanentMutation {
     pub region_id: RegionId,
     pub permanent: Permanent
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use crate::game::state::error::StateError;
+use crate::game::state::mutation::StateMutator;
+use crate::game::state::permanent::PermanentId;
+use crate::game::state::player::PlayerId;
+use crate::game::state::State;
+
+/// What damage is dealt to.
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, Eq, PartialEq, JsonSchema)]
+pub enum DamageTarget {
+    Player(PlayerId),
+    Permanent(PermanentId),
+}
+
+/// A permanent deals damage, in combat or from an effect.
+/// Damage to a player causes them to lose that much life. Units don't keep track of the damage
+/// they are dealt yet, so damage to a unit changes nothing beyond the events it raises.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct DealDamageMutation {
+    pub source_permanent_id: PermanentId,
+    pub target: DamageTarget,
+    pub amount: i32,
+}
+
+impl StateMutator for DealDamageMutation {
+    fn mutate_state(&self, mut state: State, _db: &CardPrototypeDatabase) -> Result<State, StateError> {
+        match self.target {
+            DamageTarget::Player(player_id) => {
+                state.find_player_mut(player_id)?.health -= self.amount;
+            }
+            DamageTarget::Permanent(permanent_id) => {
+                state.find_permanent(permanent_id)?;
+            }
+        }
+
+        Ok(state)
+    }
+}
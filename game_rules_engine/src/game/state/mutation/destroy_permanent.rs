@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use algocore::ModType;
+use crate::game::state::error::{EntityNotFoundError, StateError};
+use crate::game::state::mutation::StateMutator;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::State;
+
+/// Removes a permanent from play.
+/// The card of a unit goes to its controller's discard, along with the cards modding it, except for
+/// `[once]` augments, which are removed from the game. Tokens and resources simply cease to exist.
+/// Units and unit tokens dying are recorded in their controller's turn record.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct DestroyPermanentMutation {
+    pub permanent_id: PermanentId,
+}
+
+impl StateMutator for DestroyPermanentMutation {
+    fn mutate_state(&self, mut state: State, db: &CardPrototypeDatabase) -> Result<State, StateError> {
+        // the permanent may be unformed or in the region's attacking or defending formation
+        let permanent = state.regions.iter_mut()
+            .find(|region| region.permanents().iter().any(|p| p.common().permanent_id == self.permanent_id))
+            .and_then(|region| Arc::make_mut(region).remove_permanent(self.permanent_id));
+
+        let Some(permanent) = permanent else {
+            return Err(EntityNotFoundError::Permanent(self.permanent_id).into())
+        };
+
+        if permanent.is_unit() {
+            let common = permanent.common();
+            let controller = state.find_player_mut(common.controller_player_id)?;
+            controller.this_turn.units_died.push(common.permanent_id);
+        }
+
+        if let Permanent::Unit { common, card, mods } = permanent {
+            let controller = state.find_player_mut(common.controller_player_id)?;
+            controller.discard.add(card);
+
+            for m in mods {
+                let proto = db.prototypes.get(&m.prototype_id).expect("a card prototype");
+                if let Some(ModType::Augment { once: true }) = proto.mod_type {
+                    continue
+                }
+                controller.discard.add(m);
+            }
+        }
+
+        Ok(state)
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 use crate::game::state::card::CardId;
 use crate::game::state::card_collection::{CardCollectionId, FindCardCollectionMutResult};
@@ -7,20 +8,20 @@ use crate::game::state::mutation::StateMutator;
 use crate::game::state::State;
 
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum Placement {
     OnTop,
     OnBottom,
     ToIndex(usize),
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum To {
     Ordered(CardCollectionId, Placement),
     Unordered(CardCollectionId)
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct MoveCardMutation {
     pub from: CardCollectionId,
     pub to: To,
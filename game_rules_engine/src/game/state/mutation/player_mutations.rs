@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 
 
@@ -7,7 +8,7 @@ use crate::game::state::mutation::StateMutator;
 use crate::game::state::player::PlayerId;
 use crate::game::state::State;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct UpdatePlayerHealthMutation {
     pub player_id: PlayerId,
     pub new_value: i32,
@@ -21,7 +22,7 @@ impl StateMutator for UpdatePlayerHealthMutation {
 }
 
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct UpdatePlayerAliveMutation {
     pub player_id: PlayerId,
     pub new_value: bool,
@@ -34,7 +35,7 @@ impl StateMutator for UpdatePlayerAliveMutation {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct UpdatePlayerResourcesPlayedMutation {
     pub player_id: PlayerId,
     pub new_value: u8,
@@ -42,7 +43,7 @@ pub struct UpdatePlayerResourcesPlayedMutation {
 
 impl StateMutator for UpdatePlayerResourcesPlayedMutation {
     fn mutate_state(&self, mut state: State, _db: &CardPrototypeDatabase) -> Result<State, StateError> {
-        state.find_player_mut(self.player_id)?.resources_played_this_turn = self.new_value;
+        state.find_player_mut(self.player_id)?.this_turn.resources_played = self.new_value;
         Ok(state)
     }
 }
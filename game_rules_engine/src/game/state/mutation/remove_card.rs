@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 use crate::game::state::card::{CardId, FindCardResult};
 
@@ -6,7 +7,7 @@ use crate::game::state::error::StateError;
 use crate::game::state::mutation::StateMutator;
 use crate::game::state::State;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct RemoveCardMutation {
     pub card_id: CardId,
 }
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use crate::game::state::error::StateError;
+use crate::game::state::game_result::GameResult;
+use crate::game::state::mutation::{StateMutator};
+use crate::game::state::State;
+
+/// Ends the game, recording who won and why.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct SetGameResultMutation {
+    pub result: GameResult,
+}
+
+impl StateMutator for SetGameResultMutation {
+    fn mutate_state(&self, mut state: State, _: &CardPrototypeDatabase) -> Result<State, StateError> {
+        state.result = Some(self.result.clone());
+        Ok(state)
+    }
+}
@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 use crate::game::state::error::StateError;
 use crate::game::state::mutation::StateMutator;
 use crate::game::state::permanent::{Permanent, PermanentId};
 use crate::game::state::State;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct SetResourceTappedMutation {
     pub resource_permanent_id: PermanentId,
     pub tapped: bool,
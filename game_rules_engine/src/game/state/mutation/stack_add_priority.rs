@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototypeDatabase};
 
 
@@ -11,7 +12,7 @@ use crate::game::state::State;
 
 
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct StackAddPriorityMutation {
     pub region_id: RegionId,
     pub player_id: PlayerId
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use crate::game::state::error::StateError;
+use crate::game::state::mutation::{StateMutator};
+use crate::game::state::region::RegionId;
+use crate::game::state::stack::Effect;
+use crate::game::state::State;
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct StackPushEffectMutation {
+    pub region_id: RegionId,
+    pub effect: Effect,
+}
+
+impl StateMutator for StackPushEffectMutation {
+    fn mutate_state(&self, mut state: State, _: &CardPrototypeDatabase) -> Result<State, StateError> {
+        let region = state.find_region_mut(self.region_id)?;
+        region.stack.push_effect(self.effect.clone());
+        Ok(state)
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use crate::game::state::error::StateError;
+use crate::game::state::mutation::{StateMutator};
+use crate::game::state::region::RegionId;
+use crate::game::state::State;
+
+/// Removes the top effect from a region's stack. The mutations that make up the effect itself
+/// follow this mutation.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct StackResolveEffectMutation {
+    pub region_id: RegionId,
+}
+
+impl StateMutator for StackResolveEffectMutation {
+    fn mutate_state(&self, mut state: State, _: &CardPrototypeDatabase) -> Result<State, StateError> {
+        let region = state.find_region_mut(self.region_id)?;
+        region.stack.pop_effect().expect("an effect to resolve");
+        Ok(state)
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use database::{CardPrototypeDatabase};
+use crate::game::state::error::StateError;
+use crate::game::state::mutation::StateMutator;
+use crate::game::state::permanent::PermanentId;
+use crate::game::state::player::PlayerId;
+use crate::game::state::State;
+use crate::game::state::turn_record::TurnRecord;
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct SetTurnMutation {
+    pub turn: u32,
+}
+
+impl StateMutator for SetTurnMutation {
+    fn mutate_state(&self, mut state: State, _db: &CardPrototypeDatabase) -> Result<State, StateError> {
+        state.turn = self.turn;
+        Ok(state)
+    }
+}
+
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct ResetTurnRecordMutation {
+    pub player_id: PlayerId,
+}
+
+impl StateMutator for ResetTurnRecordMutation {
+    fn mutate_state(&self, mut state: State, _db: &CardPrototypeDatabase) -> Result<State, StateError> {
+        state.find_player_mut(self.player_id)?.this_turn = TurnRecord::default();
+        Ok(state)
+    }
+}
+
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RecordUnitsAttackedMutation {
+    pub player_id: PlayerId,
+    pub permanent_ids: Vec<PermanentId>,
+}
+
+impl StateMutator for RecordUnitsAttackedMutation {
+    fn mutate_state(&self, mut state: State, _db: &CardPrototypeDatabase) -> Result<State, StateError> {
+        let record = &mut state.find_player_mut(self.player_id)?.this_turn;
+        record.units_attacked.extend(self.permanent_ids.iter().copied());
+        Ok(state)
+    }
+}
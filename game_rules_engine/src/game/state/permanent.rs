@@ -1,5 +1,7 @@
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use database::{CardPrototype, CardPrototypeDatabase, CardPrototypeId};
 
 use crate::game::state::card::{Card};
@@ -9,7 +11,7 @@ use crate::game::state::player::{PlayerId};
 use crate::game::state::State;
 
 
-#[derive(Hash, Clone, Eq, PartialEq, Serialize, Deserialize, Debug, Copy)]
+#[derive(Hash, Clone, Eq, PartialEq, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 pub struct PermanentId(pub usize);
 
 impl Display for PermanentId {
@@ -18,19 +20,24 @@ impl Display for PermanentId {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct PermanentCommon {
     pub permanent_id: PermanentId,
     pub controller_player_id: PlayerId,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+/// Something in play.
+/// Tokens only exist as permanents, they cease to exist when they leave play rather than going to
+/// a discard pile.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(tag="type")]
 pub enum Permanent {
     Unit {
         common: PermanentCommon,
         card: Card,
-        // modifications: SomeModificationType
+
+        /// cards attached to this unit as augments or grafts
+        mods: Vec<Card>,
     },
     Resource {
         common: PermanentCommon,
@@ -48,6 +55,55 @@ pub enum Permanent {
 }
 
 impl Permanent {
+    pub fn common(&self) -> &PermanentCommon {
+        match self {
+            Permanent::Unit { common, .. } |
+            Permanent::Resource { common, .. } |
+            Permanent::SpellToken { common, .. } |
+            Permanent::UnitToken { common, .. } => common
+        }
+    }
+
+    pub fn is_unit(&self) -> bool {
+        matches!(self, Permanent::Unit { .. } | Permanent::UnitToken { .. })
+    }
+
+    pub fn is_token(&self) -> bool {
+        matches!(self, Permanent::SpellToken { .. } | Permanent::UnitToken { .. })
+    }
+
+    /// The prototype of the card (or token) this permanent is.
+    pub fn prototype_id(&self) -> CardPrototypeId {
+        match self {
+            Permanent::Unit { card, .. } => card.prototype_id,
+            Permanent::Resource { card_prototype_id, .. } |
+            Permanent::SpellToken { card_prototype_id, .. } |
+            Permanent::UnitToken { card_prototype_id, .. } => *card_prototype_id,
+        }
+    }
+
+    /// The power printed on this permanent's card, 0 for anything that isn't a unit.
+    pub fn power(&self, db: &CardPrototypeDatabase) -> i32 {
+        if !self.is_unit() {
+            return 0;
+        }
+
+        db.prototypes.get(&self.prototype_id()).map_or(0, |p| p.power)
+    }
+
+    /// The prototypes that give this permanent its abilities.
+    /// For units, this includes the cards modding it.
+    pub fn ability_prototype_ids(&self) -> Vec<CardPrototypeId> {
+        match self {
+            Permanent::Unit { card, mods, .. } => {
+                std::iter::once(card).chain(mods).map(|c| c.prototype_id).collect()
+            }
+            Permanent::SpellToken { card_prototype_id, .. } |
+            Permanent::UnitToken { card_prototype_id, .. } => vec![*card_prototype_id],
+            Permanent::Resource { .. } => Vec::new(),
+        }
+    }
+
     pub fn from_unit_card(card: Card, controller_player_id: PlayerId, state: &mut State, db: &CardPrototypeDatabase) -> Permanent {
         let proto = db.prototypes.get(&card.prototype_id).expect("a prototype");
 
@@ -57,13 +113,28 @@ impl Permanent {
                     permanent_id: PermanentId(state.permanent_id_factory.proceed()),
                     controller_player_id,
                 },
-                card
+                card,
+                mods: Vec::new(),
             }
         } else {
             panic!("you need to call this only when the card type is some real card, not a token or resource")
         }
     }
 
+    /// Creates a token permanent, the permanent id must be reserved by whatever adds it to play.
+    pub fn from_token_prototype(card_prototype_id: CardPrototypeId, card_type: &CardType, permanent_id: PermanentId, controller_player_id: PlayerId) -> Permanent {
+        let common = PermanentCommon {
+            permanent_id,
+            controller_player_id,
+        };
+
+        match card_type {
+            CardType::UnitToken => Permanent::UnitToken { common, card_prototype_id },
+            CardType::SpellToken => Permanent::SpellToken { common, card_prototype_id },
+            _ => panic!("you need to call this only when the card type is a token")
+        }
+    }
+
     pub fn from_card_prototype(card_prototype: &CardPrototype, controller_player_id: PlayerId, state: &mut State) -> Permanent {
         match card_prototype.card_type {
             CardType::Resource(_) => {
@@ -103,7 +174,7 @@ impl Permanent {
 
 impl State {
     pub fn find_permanent(&self, id: PermanentId) -> Result<&Permanent, EntityNotFoundError> {
-        let find_result = self.regions.iter().flat_map(|region| &region.unformed_permanents).find(|p| match p {
+        let find_result = self.regions.iter().flat_map(|region| region.permanents()).find(|p| match p {
             Permanent::Unit { common, .. } |
             Permanent::Resource { common, .. } |
             Permanent::SpellToken { common, .. } |
@@ -113,20 +184,19 @@ impl State {
         });
 
         if let Some(permanent) = find_result {
-            Ok(&permanent)
+            Ok(permanent)
         } else {
             Err(EntityNotFoundError::Permanent(id))
         }
     }
 
     pub fn find_permanent_mut(&mut self, id: PermanentId) -> Result<&mut Permanent, EntityNotFoundError> {
-        let find_result = self.regions.iter_mut().flat_map(|region| &mut region.unformed_permanents).find(|p| match p {
-            Permanent::Unit { common, .. } |
-            Permanent::Resource { common, .. } |
-            Permanent::SpellToken { common, .. } |
-            Permanent::UnitToken { common, .. } => {
-                common.permanent_id == id
-            }
+        // only the region with the permanent is copied out of the shared regions
+        let region = self.regions.iter_mut().find(|region| {
+            region.permanents().iter().any(|p| p.common().permanent_id == id)
+        });
+        let find_result = region.and_then(|region| {
+            Arc::make_mut(region).permanents_mut().into_iter().find(|p| p.common().permanent_id == id)
         });
 
         if let Some(permanent) = find_result {
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use algocore::{Affinity, CardType, Cost, Faction, ResourceType};
 use database::CardPrototypeDatabase;
 
@@ -12,11 +14,12 @@ use crate::game::state::deck::Deck;
 use crate::game::state::permanent::Permanent;
 use crate::game::state::stack::Next;
 use crate::game::state::unordered_cards::UnorderedCards;
+use crate::game::state::turn_record::TurnRecord;
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 pub struct TeamId(pub u8);
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 pub struct PlayerId(pub u8);
 
 impl Display for PlayerId {
@@ -25,7 +28,7 @@ impl Display for PlayerId {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Player {
     pub id: PlayerId,
     pub team_id: TeamId,
@@ -35,7 +38,8 @@ pub struct Player {
     pub health: i32,
     pub hand: UnorderedCards,
     pub discard: UnorderedCards,
-    pub resources_played_this_turn: u8,
+
+    pub this_turn: TurnRecord,
 }
 
 impl Player {
@@ -49,7 +53,7 @@ impl Player {
             hand: UnorderedCards::new(CardCollectionId::new_hand(player_id)),
             discard: UnorderedCards::new(CardCollectionId::new_discard(player_id)),
             pack,
-            resources_played_this_turn: 0,
+            this_turn: TurnRecord::default(),
         }
     }
 
@@ -104,7 +108,7 @@ impl State {
     pub fn team_ids(&self) -> Vec<TeamId> {
         self.players().fold(Vec::new(), |mut acc, player| {
             // add the team to the list if it's no already there
-            if !acc.iter().any(|t_id| *t_id == player.team_id) {
+            if !acc.contains(&player.team_id) {
                 acc.push(player.team_id)
             }
             acc
@@ -118,6 +122,10 @@ impl State {
         let region = self.find_region_containing_player(player_id).expect("the player must be in a region");
         let player = self.find_player(player_id).expect("the player was supposed to be in this region");
 
+        if !player.is_alive {
+            return false;
+        }
+
         let player_is_on_initiative_team = player.team_id == self.initiative_team();
 
         let active_on_stack = match region.stack.next() {
@@ -305,8 +313,9 @@ pub enum Affordability {
 // mutable methods relating to state
 impl State {
     pub fn find_player_mut(&mut self, player_id: PlayerId) -> Result<&mut Player, EntityNotFoundError> {
-        let mut players_mut = self.regions.iter_mut().flat_map(|r| &mut r.players);
-        let find_result = players_mut.find(|p| p.id == player_id);
+        // only the region with the player is copied out of the shared regions
+        let region = self.regions.iter_mut().find(|r| r.players.iter().any(|p| p.id == player_id));
+        let find_result = region.and_then(|r| Arc::make_mut(r).players.iter_mut().find(|p| p.id == player_id));
         match find_result {
             None => {
                 Err(EntityNotFoundError::Player(player_id))
@@ -318,15 +327,18 @@ impl State {
     }
 
 
+    /// Draws up to n cards from the player's deck into their hand. Running out of cards doesn't
+    /// fail the draw, the player loses to the deck-out state based action instead.
     pub fn player_draw_n_cards(&mut self, player_id: PlayerId, n: usize) {
         let deck = self.player_deck(player_id).expect("a deck");
         let mut cards = Vec::new();
-        for _ in 0..n {
+        for _ in 0..n.min(deck.len()) {
             let card = deck.draw().expect("a card");
             cards.push(card);
         }
 
         let player = self.find_player_mut(player_id).expect("a player");
+        player.this_turn.cards_drawn += cards.len();
         for card in cards {
             player.hand.add(card);
         }
@@ -1,11 +1,13 @@
 
+use std::fmt::{Display, Formatter};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 use crate::game::state::{GameMode};
 
 
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 #[serde(tag = "phase")]
 pub enum Phase {
     PlanningPhase(PlanningPhaseStep),
@@ -14,7 +16,7 @@ pub enum Phase {
     DeploymentPhase(DeploymentPhaseStep),
 }
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 #[serde(tag = "step")]
 pub enum PlanningPhaseStep {
     Refresh,
@@ -25,7 +27,7 @@ pub enum PlanningPhaseStep {
     Haste(Team),
 }
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 #[serde(tag = "step")]
 pub enum BattlePhaseStep {
     Attack(Team),
@@ -37,20 +39,31 @@ pub enum BattlePhaseStep {
 }
 
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 #[serde(tag = "step")]
 pub enum DeploymentPhaseStep {
     Regroup,
     Deployment(Team),
 }
 
-#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 #[serde(tag = "team")]
 pub enum Team {
     IT,
     NIT,
 }
 
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::PlanningPhase(step) => write!(f, "Planning Phase: {:?}", step),
+            Phase::BattlePhaseA(step) => write!(f, "Battle Phase A: {:?}", step),
+            Phase::BattlePhaseB(step) => write!(f, "Battle Phase B: {:?}", step),
+            Phase::DeploymentPhase(step) => write!(f, "Deployment Phase: {:?}", step),
+        }
+    }
+}
+
 impl Phase {
 
     /// This method returns the next phase for a game running with a given game mode.
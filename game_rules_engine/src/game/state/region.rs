@@ -1,18 +1,23 @@
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 use crate::game::state::card::CardId;
 use crate::game::state::error::StateError;
 use crate::game::state::error::EntityNotFoundError;
 use crate::game::state::formation::{DefensiveFormation, Formation};
-use crate::game::state::permanent::Permanent;
+use crate::game::state::permanent::{Permanent, PermanentId};
 use crate::game::state::player::{Player, PlayerId, TeamId};
 use crate::game::state::progression::{Phase, PlanningPhaseStep, Team};
 use crate::game::state::stack::Stack;
 use crate::game::state::State;
 use crate::game::state::unordered_cards::UnorderedCards;
 
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy)]
+/// the number of cards each player draws during the draw step
+pub const DRAW_STEP_CARD_COUNT: usize = 2;
+
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
 pub struct RegionId(pub u8);
 
 impl Display for RegionId {
@@ -22,7 +27,7 @@ impl Display for RegionId {
 }
 
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Region {
     pub id: RegionId,
     pub owner_player_id: PlayerId,
@@ -49,6 +54,45 @@ impl Region {
         resources
     }
 
+    /// All the permanents in play in this region, formed or not.
+    pub fn permanents(&self) -> Vec<&Permanent> {
+        let mut permanents: Vec<&Permanent> = self.unformed_permanents.iter().collect();
+
+        for formation in self.formations() {
+            permanents.extend(formation.cells_iter());
+        }
+
+        permanents
+    }
+
+    pub fn permanents_mut(&mut self) -> Vec<&mut Permanent> {
+        let mut permanents: Vec<&mut Permanent> = self.unformed_permanents.iter_mut().collect();
+
+        if let Some(f) = self.attacking_formation.as_mut() {
+            permanents.extend(f.cells_iter_mut());
+        }
+
+        if let Some(f) = self.defending_formation.as_mut() {
+            permanents.extend(f.formation.cells_iter_mut());
+        }
+
+        permanents
+    }
+
+    /// Takes a permanent out of play in this region, from wherever it is, formed or not.
+    pub fn remove_permanent(&mut self, id: PermanentId) -> Option<Permanent> {
+        if let Some(idx) = self.unformed_permanents.iter().position(|p| p.common().permanent_id == id) {
+            return Some(self.unformed_permanents.remove(idx));
+        }
+
+        let is_permanent = |p: &Permanent| p.common().permanent_id == id;
+        if let Some(permanent) = self.attacking_formation.as_mut().and_then(|f| f.take_where(is_permanent)) {
+            return Some(permanent);
+        }
+
+        self.defending_formation.as_mut().and_then(|f| f.formation.take_where(is_permanent))
+    }
+
     pub fn formations(&self) -> Vec<&Formation<Permanent>> {
         let mut formations = Vec::new();
 
@@ -125,7 +169,7 @@ impl State {
                 Err(EntityNotFoundError::Region(region_id))
             }
             Some(region) => {
-                Ok(region)
+                Ok(Arc::make_mut(region))
             }
         }
     }
@@ -156,7 +200,7 @@ impl State {
         let players = self.players_in_region_mut(region_id).expect("a set of players in a region");
         let player_ids: Vec<PlayerId> = players.iter().map(|p| p.id).collect();
         for p_id in player_ids {
-            self.player_draw_n_cards(p_id, 2);
+            self.player_draw_n_cards(p_id, DRAW_STEP_CARD_COUNT);
         }
     }
 
@@ -200,7 +244,7 @@ impl State {
             r.players.iter().any(|p| p.id == player_id)
         }).expect("a region containing this player");
 
-        region
+        Arc::make_mut(region)
     }
 
     pub fn find_region_containing_player(&self, player_id: PlayerId) -> Result<&Region, EntityNotFoundError> {
@@ -233,7 +277,7 @@ impl State {
         }
 
         for (idx, pack) in packs.into_iter().enumerate() {
-            self.regions[idx].sole_player_mut().pack = Some(pack);
+            Arc::make_mut(&mut self.regions[idx]).sole_player_mut().pack = Some(pack);
         }
 
 
@@ -242,6 +286,8 @@ impl State {
 
 
     pub fn region_transition_to_next_step(mut self, region_id: RegionId) -> State {
+        let _span = tracing::debug_span!("region", %region_id).entered();
+
         let next_step = {
             let region = self.find_region(region_id).expect("a region");
             region.step.get_next_phase(&self.game_mode)
@@ -263,7 +309,7 @@ impl State {
 
         {
             let region = self.find_region_mut(region_id).expect("a region");
-            // eprintln!("Region {:?} is transitioning from {:?} to {:?}", region.id, region.step, next_step);
+            tracing::debug!(from = %region.step, to = %next_step, "region is transitioning");
             region.step = next_step;
         }
 
@@ -1,6 +1,9 @@
 use rand::{Error, Rng, RngCore, SeedableRng};
 use rand::distributions::uniform::{SampleRange, SampleUniform};
 use serde::{Deserialize, Serialize};
+use schemars::gen::SchemaGenerator;
+use schemars::JsonSchema;
+use schemars::schema::Schema;
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AlgomancerRng(rand_pcg::Mcg128Xsl64);
@@ -25,6 +28,23 @@ impl RngCore for AlgomancerRng {
     }
 }
 
+impl JsonSchema for AlgomancerRng {
+    fn schema_name() -> String {
+        "AlgomancerRng".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        // the generator's serialized form, its type isn't visible outside of rand_pcg
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        struct Mcg128Xsl64 {
+            state: u128,
+        }
+
+        Mcg128Xsl64::json_schema(gen)
+    }
+}
+
 impl AlgomancerRng {
     pub fn new(seed: AlgomancerRngSeed) -> AlgomancerRng {
         let rng =  rand_pcg::Mcg128Xsl64::from_seed(seed);
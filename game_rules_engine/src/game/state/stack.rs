@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
-use crate::game::state::card::CardId;
-use crate::game::state::error::StateError;
+use schemars::JsonSchema;
+use algocore::{AbilityEffect, TriggeredAbility};
+use database::CardPrototypeDatabase;
+use crate::game::state::error::{EntityNotFoundError, StateError};
+use crate::game::state::mutation::create_permanent::CreatePermanentMutation;
+use crate::game::state::mutation::deal_damage::{DamageTarget, DealDamageMutation};
+use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
 use crate::game::state::mutation::StateMutation;
+use crate::game::state::permanent::{Permanent, PermanentId};
 use crate::game::state::player::PlayerId;
-use crate::game::state::State;
+use crate::game::state::region::RegionId;
+use crate::{sm_eval, sm_eval_vec, sm_static};
 
 
 pub enum Next<'a> {
@@ -12,13 +19,76 @@ pub enum Next<'a> {
     ResolveEffect(&'a Effect)
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+/// An effect waiting on the stack to be resolved, created when a permanent's triggered ability
+/// matched an event.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq, JsonSchema)]
 pub struct Effect {
-    source: CardId
+    pub source_permanent_id: PermanentId,
+    pub controller_player_id: PlayerId,
+    pub ability: TriggeredAbility,
 }
 
+impl Effect {
+    /// Generates the mutations that carry out this effect, for an effect on the stack of `region_id`.
+    /// Abilities that the engine doesn't understand yet resolve without doing anything.
+    pub fn resolve(&self, region_id: RegionId, db: &CardPrototypeDatabase) -> Result<Vec<StateMutation>, StateError> {
+        let mut mutations = Vec::new();
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+        match &self.ability.effect {
+            Some(AbilityEffect::CreateToken { token_std_name, amount }) => {
+                let proto = db.token(token_std_name).ok_or_else(|| EntityNotFoundError::TokenPrototype(token_std_name.clone()))?;
+                for _ in 0..*amount {
+                    let prototype_id = proto.prototype_id;
+                    let card_type = proto.card_type.clone();
+                    let controller_player_id = self.controller_player_id;
+                    mutations.push(sm_eval!(move |next_state| {
+                        let permanent_id = PermanentId(next_state.permanent_id_factory.peek());
+                        let permanent = Permanent::from_token_prototype(prototype_id, &card_type, permanent_id, controller_player_id);
+
+                        Ok(Some(sm_static!(CreatePermanent, CreatePermanentMutation {
+                            region_id,
+                            permanent
+                        })))
+                    }));
+                }
+            }
+            Some(AbilityEffect::SacrificeMe) => {
+                // the source may have already left play by the time this resolves
+                let permanent_id = self.source_permanent_id;
+                mutations.push(sm_eval!(move |next_state| {
+                    if next_state.find_permanent(permanent_id).is_err() {
+                        return Ok(None);
+                    }
+
+                    Ok(Some(sm_static!(DestroyPermanent, DestroyPermanentMutation {
+                        permanent_id
+                    })))
+                }));
+            }
+            Some(AbilityEffect::DamageEachOpponent { amount }) => {
+                let source_permanent_id = self.source_permanent_id;
+                let controller_player_id = self.controller_player_id;
+                let amount = *amount as i32;
+                mutations.push(sm_eval_vec!(move |next_state| {
+                    let team_id = next_state.find_player(controller_player_id)?.team_id;
+                    let opponents = next_state.players().filter(|p| p.team_id != team_id && p.is_alive);
+
+                    Ok(opponents.map(|p| sm_static!(DealDamage, DealDamageMutation {
+                        source_permanent_id,
+                        target: DamageTarget::Player(p.id),
+                        amount,
+                    })).collect())
+                }));
+            }
+            None => {}
+        }
+
+        Ok(mutations)
+    }
+}
+
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 #[derive(Default)]
 pub struct Stack {
     priority: Vec<PlayerId>,
@@ -28,9 +98,21 @@ pub struct Stack {
 
 
 impl Stack {
-    pub fn push_effect(&mut self, state: &State, effect: Effect){
+    pub fn push_effect(&mut self, effect: Effect) {
         self.stack.push(effect);
-        self.priority.extend(state.players().map(|p| p.id));
+    }
+
+    pub fn pop_effect(&mut self) -> Option<Effect> {
+        self.stack.pop()
+    }
+
+    /// the players who have yet to pass priority, in the order they receive it
+    pub fn priority(&self) -> &[PlayerId] {
+        &self.priority
+    }
+
+    pub fn effects(&self) -> &[Effect] {
+        &self.stack
     }
 
     pub fn acting_player(&self) -> Option<PlayerId>{
@@ -54,7 +136,7 @@ impl Stack {
         }
     }
 
-    pub fn next(&self) -> Next {
+    pub fn next(&self) -> Next<'_> {
         if self.priority.is_empty() {
             if self.stack.is_empty() {
                 Next::TransitionStep
@@ -65,13 +147,133 @@ impl Stack {
             Next::PassPriority(self.acting_player().expect("a player with priority"))
         }
     }
+}
 
-    pub fn resolve_next_effect(&mut self) -> Option<Result<Vec<StateMutation>, StateError>> {
-        let effect = self.stack.pop();
-        effect.map(|effect| self.resolve_effect(effect))
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use algocore::{AbilityEffect, Trigger, TriggeredAbility};
+    use crate::game::{Game, GameOptions};
+    use crate::game::state::GameMode;
+    use crate::game::state::error::{EntityNotFoundError, StateError};
+    use crate::game::state::formation::{Formation, FormationId, FormationPos};
+    use crate::game::state::mutation::destroy_permanent::DestroyPermanentMutation;
+    use crate::game::state::mutation::{StateMutation, StaticStateMutation};
+    use crate::game::state::permanent::{Permanent, PermanentId};
+    use crate::game::state::stack::Effect;
+    use crate::game::state::State;
+
+    fn apply(game: &Game, mut state: State, mutations: Vec<StateMutation>) -> State {
+        for mutation in mutations {
+            for m in mutation.to_static(&state).unwrap() {
+                state = state.mutate(&game.cards_db, &m).unwrap();
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn test_create_token_effect() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let region = &game.state.regions[0];
+        let player_id = region.players[0].id;
+        let discard_len = region.players[0].discard.len();
+
+        let effect = Effect {
+            source_permanent_id: PermanentId(0),
+            controller_player_id: player_id,
+            ability: TriggeredAbility {
+                trigger: Trigger::SelfSpawns,
+                effect_text: "create two wisps".to_string(),
+                effect: Some(AbilityEffect::CreateToken { token_std_name: "wisp".to_string(), amount: 2 }),
+            },
+        };
+
+        let state = apply(&game, game.state.clone(), effect.resolve(region.id, &game.cards_db).unwrap());
+
+        let tokens: Vec<PermanentId> = state.regions[0].unformed_permanents.iter().filter_map(|p| match p {
+            Permanent::UnitToken { common, .. } => Some(common.permanent_id),
+            _ => None,
+        }).collect();
+        assert_eq!(tokens.len(), 2);
+        assert_ne!(tokens[0], tokens[1]);
+
+        // tokens cease to exist when they leave play
+        let state = state.mutate(&game.cards_db, &StaticStateMutation::DestroyPermanent(DestroyPermanentMutation {
+            permanent_id: tokens[0],
+        })).unwrap();
+        assert!(state.find_permanent(tokens[0]).is_err());
+        assert_eq!(state.regions[0].players[0].discard.len(), discard_len);
+
+        // a token that isn't in the database is an error rather than a panic
+        let unknown = Effect {
+            ability: TriggeredAbility {
+                effect: Some(AbilityEffect::CreateToken { token_std_name: "not a token".to_string(), amount: 1 }),
+                ..effect.ability.clone()
+            },
+            ..effect
+        };
+        assert!(matches!(
+            unknown.resolve(region.id, &game.cards_db),
+            Err(StateError::EntityNotFound(EntityNotFoundError::TokenPrototype(_)))
+        ));
     }
 
-    fn resolve_effect(&self, _effect: Effect) -> Result<Vec<StateMutation>, StateError>{
-        Ok(Vec::new())
+    #[test]
+    fn test_destroy_formed_permanent() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let region = &game.state.regions[0];
+        let player_id = region.players[0].id;
+
+        let effect = Effect {
+            source_permanent_id: PermanentId(0),
+            controller_player_id: player_id,
+            ability: TriggeredAbility {
+                trigger: Trigger::SelfSpawns,
+                effect_text: "create a wisp".to_string(),
+                effect: Some(AbilityEffect::CreateToken { token_std_name: "wisp".to_string(), amount: 1 }),
+            },
+        };
+        let mut state = apply(&game, game.state.clone(), effect.resolve(region.id, &game.cards_db).unwrap());
+
+        // move the token into a committed attacking formation
+        let region = Arc::make_mut(&mut state.regions[0]);
+        let token = region.unformed_permanents.pop().expect("a token");
+        let token_id = token.common().permanent_id;
+        let mut formation = Formation::new(FormationId(0), player_id);
+        formation.insert_at(FormationPos::FrontRow(0), token).unwrap();
+        formation.commit().unwrap();
+        region.attacking_formation = Some(formation);
+
+        assert!(state.find_permanent(token_id).is_ok());
+
+        let state = state.mutate(&game.cards_db, &StaticStateMutation::DestroyPermanent(DestroyPermanentMutation {
+            permanent_id: token_id,
+        })).unwrap();
+        assert!(state.find_permanent(token_id).is_err());
+        assert_eq!(state.regions[0].attacking_formation.as_ref().unwrap().cells_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_resolve_damage_each_opponent() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let region = &game.state.regions[0];
+        let player_id = region.players[0].id;
+        let opponent_id = game.state.regions[1].players[0].id;
+
+        let effect = Effect {
+            source_permanent_id: PermanentId(0),
+            controller_player_id: player_id,
+            ability: TriggeredAbility {
+                trigger: Trigger::UnitDies { another: false, nontoken: false },
+                effect_text: "i deal 1 damage to each opponent".to_string(),
+                effect: Some(AbilityEffect::DamageEachOpponent { amount: 1 }),
+            },
+        };
+
+        let before = &game.state;
+        let state = apply(&game, before.clone(), effect.resolve(region.id, &game.cards_db).unwrap());
+        assert_eq!(state.find_player(player_id).unwrap().health, before.find_player(player_id).unwrap().health);
+        assert_eq!(state.find_player(opponent_id).unwrap().health, before.find_player(opponent_id).unwrap().health - 1);
     }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub enum TeamConfiguration {
     // this can't be generalized to teams of 1 since it also affects how the turn progresses.
     // and includes the introduction of 'intent cards'
@@ -29,4 +30,12 @@ impl TeamConfiguration {
     pub fn ffa(num_players: u8) -> TeamConfiguration {
         TeamConfiguration::Ffa { num_players  }
     }
+
+    /// the number of players in the game, across every team
+    pub fn num_players(&self) -> u8 {
+        match self {
+            TeamConfiguration::Ffa { num_players } => *num_players,
+            TeamConfiguration::Teams { teams_of_players } => teams_of_players.iter().sum(),
+        }
+    }
 }
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::game::state::permanent::PermanentId;
+
+/// What a player has done during the current turn, for card conditions and displays that care
+/// about 'this turn'. Every player's record is cleared during the refresh step.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct TurnRecord {
+    pub resources_played: u8,
+    pub cards_drawn: usize,
+
+    /// the units the player attacked with
+    pub units_attacked: Vec<PermanentId>,
+
+    /// the units the player controlled which died, including tokens
+    pub units_died: Vec<PermanentId>,
+}
+
+impl TurnRecord {
+    pub fn is_empty(&self) -> bool {
+        *self == TurnRecord::default()
+    }
+}
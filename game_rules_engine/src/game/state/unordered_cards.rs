@@ -1,20 +1,24 @@
 use std::slice::Iter;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::game::state::card::{Card, CardId};
 use crate::game::state::card_collection::CardCollectionId;
 use crate::game::state::error::{EntityNotFoundError, StateError};
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+/// A hand, discard or pack. Like [crate::game::state::deck::Deck], the cards are shared between
+/// clones until one of them changes.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct UnorderedCards {
     pub id: CardCollectionId,
-    cards: Vec<Card>
+    cards: Arc<Vec<Card>>
 }
 
 impl UnorderedCards {
     pub fn new(id: CardCollectionId) -> UnorderedCards {
         UnorderedCards {
             id,
-            cards: Vec::new()
+            cards: Arc::new(Vec::new())
         }
     }
 
@@ -23,7 +27,7 @@ impl UnorderedCards {
     }
 
     pub fn add(&mut self, card: Card)  {
-        self.cards.push(card)
+        Arc::make_mut(&mut self.cards).push(card)
     }
 
     pub fn remove(&mut self, card_id: CardId) -> Result<Card, StateError>  {
@@ -31,7 +35,7 @@ impl UnorderedCards {
             None => return Err(EntityNotFoundError::Card(card_id).into()),
             Some(idx) => idx
         };
-        Ok(self.cards.remove(idx))
+        Ok(Arc::make_mut(&mut self.cards).remove(idx))
     }
 
     pub fn transfer_to(&mut self, receiver: &mut UnorderedCards, card_id: CardId) -> Result<(), StateError>{
@@ -44,6 +48,11 @@ impl UnorderedCards {
         self.cards.len()
     }
 
+    /// replaces every card with a [Card::hidden] one, so only the number of cards is known
+    pub fn hide(&mut self) {
+        self.cards = Arc::new(self.cards.iter().map(|_| Card::hidden()).collect());
+    }
+
     pub fn is_empty(&self) -> bool {
         self.cards.is_empty()
     }
@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+use database::CardPrototypeDatabase;
+use crate::game::state::card::{Card, CardId};
+use crate::game::state::card_collection::CardCollectionId;
+use crate::game::state::error::InvariantViolation;
+use crate::game::state::permanent::{Permanent, PermanentId};
+use crate::game::state::player::PlayerId;
+use crate::game::state::region::RegionId;
+use crate::game::state::State;
+
+impl State {
+    /// Checks the state for inconsistencies the engine can't recover from, such as the same card
+    /// in two places, or id factories that would hand out ids already in use.
+    /// An empty list means the state is consistent.
+    pub fn validate(&self, db: &CardPrototypeDatabase) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+
+        self.validate_ids(&mut violations);
+        self.validate_cards(&mut violations, db);
+        self.validate_permanents(&mut violations, db);
+        self.validate_players(&mut violations);
+
+        violations
+    }
+
+    /// every card in the game, wherever it is, including cards in play and attached as mods
+    fn all_cards(&self) -> Vec<&Card> {
+        let mut cards: Vec<&Card> = Vec::new();
+
+        if let Some(deck) = &self.common_deck {
+            cards.extend(deck.iter());
+        }
+
+        for player in self.players() {
+            cards.extend(player.hand.iter());
+            cards.extend(player.discard.iter());
+            cards.extend(player.pack.iter().flat_map(|p| p.iter()));
+            cards.extend(player.own_deck.iter().flat_map(|d| d.iter()));
+        }
+
+        for permanent in self.regions.iter().flat_map(|r| r.permanents()) {
+            if let Permanent::Unit { card, mods, .. } = permanent {
+                cards.push(card);
+                cards.extend(mods.iter());
+            }
+        }
+
+        cards
+    }
+
+    fn validate_ids(&self, violations: &mut Vec<InvariantViolation>) {
+        let card_ids: Vec<CardId> = self.all_cards().iter().map(|c| c.card_id).collect();
+        violations.extend(duplicates(&card_ids).into_iter().map(InvariantViolation::DuplicateCardId));
+
+        let permanent_ids: Vec<PermanentId> = self.regions.iter()
+            .flat_map(|r| r.permanents())
+            .map(|p| p.common().permanent_id)
+            .collect();
+        violations.extend(duplicates(&permanent_ids).into_iter().map(InvariantViolation::DuplicatePermanentId));
+
+        let player_ids: Vec<PlayerId> = self.players().map(|p| p.id).collect();
+        violations.extend(duplicates(&player_ids).into_iter().map(InvariantViolation::DuplicatePlayerId));
+
+        let region_ids: Vec<RegionId> = self.regions.iter().map(|r| r.id).collect();
+        violations.extend(duplicates(&region_ids).into_iter().map(InvariantViolation::DuplicateRegionId));
+
+        let formation_ids: Vec<usize> = self.regions.iter()
+            .flat_map(|r| {
+                let attacking = r.attacking_formation.as_ref().map(|f| f.id());
+                let defending = r.defending_formation.as_ref().map(|f| f.formation.id());
+                attacking.into_iter().chain(defending)
+            })
+            .map(|id| id.0)
+            .collect();
+
+        // the factories hand out the id after their value, so every existing id must be at or below it
+        let factories = [
+            ("card", self.card_id_factory.0, card_ids.iter().map(|id| id.0).max()),
+            ("permanent", self.permanent_id_factory.0, permanent_ids.iter().map(|id| id.0).max()),
+            ("formation", self.formation_id_factory.0, formation_ids.iter().copied().max()),
+        ];
+
+        for (name, value, max_id) in factories {
+            if let Some(max_id) = max_id {
+                if max_id > value {
+                    violations.push(InvariantViolation::IdFactoryBehindIds { name, value, max_id });
+                }
+            }
+        }
+    }
+
+    fn validate_cards(&self, violations: &mut Vec<InvariantViolation>, db: &CardPrototypeDatabase) {
+        for card in self.all_cards() {
+            if !db.prototypes.contains_key(&card.prototype_id) {
+                violations.push(InvariantViolation::UnknownCardPrototype {
+                    card_id: card.card_id,
+                    prototype_id: card.prototype_id.0,
+                });
+            }
+        }
+
+        for player in self.players() {
+            let mut collections = vec![
+                (CardCollectionId::new_hand(player.id), player.hand.id),
+                (CardCollectionId::new_discard(player.id), player.discard.id),
+            ];
+
+            if let Some(pack) = &player.pack {
+                collections.push((CardCollectionId::new_pack(player.id), pack.id));
+            }
+
+            if let Some(deck) = &player.own_deck {
+                collections.push((CardCollectionId::new_deck(player.id), deck.id));
+            }
+
+            for (expected, found) in collections {
+                if expected != found {
+                    violations.push(InvariantViolation::WrongCardCollectionId { player_id: player.id, expected, found });
+                }
+            }
+        }
+    }
+
+    fn validate_permanents(&self, violations: &mut Vec<InvariantViolation>, db: &CardPrototypeDatabase) {
+        for permanent in self.regions.iter().flat_map(|r| r.permanents()) {
+            let common = permanent.common();
+
+            // a unit's own card is already checked along with the other cards
+            if !matches!(permanent, Permanent::Unit { .. }) && !db.prototypes.contains_key(&permanent.prototype_id()) {
+                violations.push(InvariantViolation::UnknownPermanentPrototype {
+                    permanent_id: common.permanent_id,
+                    prototype_id: permanent.prototype_id().0,
+                });
+            }
+
+            if self.find_player(common.controller_player_id).is_err() {
+                violations.push(InvariantViolation::UnknownController {
+                    permanent_id: common.permanent_id,
+                    player_id: common.controller_player_id,
+                });
+            }
+        }
+    }
+
+    fn validate_players(&self, violations: &mut Vec<InvariantViolation>) {
+        if self.find_player(self.initiative_player).is_err() {
+            violations.push(InvariantViolation::UnknownInitiativePlayer(self.initiative_player));
+        }
+
+        for region in &self.regions {
+            for &player_id in region.stack.priority() {
+                match region.players.iter().find(|p| p.id == player_id) {
+                    None => {
+                        violations.push(InvariantViolation::PriorityPlayerNotInRegion { region_id: region.id, player_id });
+                    }
+                    // the dead stop passing priority once the game is over, as nobody acts anymore
+                    Some(player) if !player.is_alive && !self.is_over() => {
+                        violations.push(InvariantViolation::PriorityPlayerIsDead { region_id: region.id, player_id });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+}
+
+/// the values that appear more than once, each reported once, in the order they're first repeated
+fn duplicates<T: Copy + Eq + std::hash::Hash>(values: &[T]) -> Vec<T> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+
+    values.iter()
+        .filter(|&&v| !seen.insert(v) && reported.insert(v))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::ActionType;
+    use crate::game::state::error::InvariantViolation;
+    use crate::game::state::GameMode;
+    use crate::game::state::formation::{Formation, FormationId};
+
+    #[test]
+    fn test_validate() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let db = &game.cards_db;
+        assert_eq!(game.state.validate(db), Vec::new());
+
+        let mut state = game.state.clone();
+        let card = state.all_cards()[0].clone();
+        state.find_region_mut(state.regions[1].id).unwrap().players[0].hand.add(card.clone());
+        state.card_id_factory.0 = 0;
+
+        let dead_player_id = state.regions[0].players[0].id;
+        let region = state.find_region_mut(state.regions[0].id).unwrap();
+        region.players[0].is_alive = false;
+        region.stack.clear_priority();
+        region.stack.push_priority(dead_player_id);
+
+        let violations = state.validate(db);
+        assert!(violations.contains(&InvariantViolation::DuplicateCardId(card.card_id)));
+        assert!(violations.iter().any(|v| matches!(v, InvariantViolation::IdFactoryBehindIds { name: "card", .. })));
+        assert!(violations.contains(&InvariantViolation::PriorityPlayerIsDead { region_id: state.regions[0].id, player_id: dead_player_id }));
+
+        let mut state = game.state.clone();
+        let owner_player_id = state.regions[0].players[0].id;
+        let formation_id = FormationId(state.formation_id_factory.0 + 1);
+        let region = state.find_region_mut(state.regions[0].id).unwrap();
+        region.attacking_formation = Some(Formation::new(formation_id, owner_player_id));
+        assert!(state.validate(db).iter().any(|v| matches!(v, InvariantViolation::IdFactoryBehindIds { name: "formation", .. })));
+    }
+
+    #[test]
+    fn test_inconsistent_state_can_be_played() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        // a hand-edited state with a card in two places
+        let card = game.state.all_cards()[0].clone();
+        let region_id = game.state.regions[1].id;
+        game.state.find_region_mut(region_id).unwrap().players[0].hand.add(card);
+        assert!(!game.state.validate(&game.cards_db).is_empty());
+
+        let concede = game.valid_actions().into_iter()
+            .filter(|a| matches!(a.action_type(), ActionType::Concede(_)))
+            .min_by_key(|a| a.issuer_player_id().0)
+            .expect("a player who can concede");
+        game.apply_action(concede).unwrap();
+    }
+}
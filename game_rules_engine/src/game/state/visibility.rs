@@ -0,0 +1,189 @@
+use std::sync::Arc;
+use crate::game::state::card::Card;
+use crate::game::state::card_collection::CardCollectionId;
+use crate::game::state::mutation::create_card::CreateCardMutation;
+use crate::game::state::mutation::move_card::{MoveCardMutation, To};
+use crate::game::state::mutation::StaticStateMutation;
+use crate::game::state::player::PlayerId;
+use crate::game::state::rng::{AlgomancerRng, AlgomancerRngSeed};
+use crate::game::state::State;
+
+impl State {
+    /// The state as the player is allowed to see it, for sending to the player's client.
+    ///
+    /// Every other player's hand and pack, and every deck including the player's own, has its cards
+    /// replaced with [crate::game::state::card::Card::hidden] ones, so that only their number is
+    /// known. The rng is reset as well, as its state would give away how the decks are shuffled.
+    /// Everything in play and in the discard piles is left as it is.
+    pub fn visible_to(&self, player_id: PlayerId) -> State {
+        self.visible_to_viewer(Some(player_id))
+    }
+
+    /// The state as spectators are allowed to see it, like [State::visible_to] but with every
+    /// player's hand and pack hidden.
+    pub fn visible_to_spectators(&self) -> State {
+        self.visible_to_viewer(None)
+    }
+
+    fn visible_to_viewer(&self, player_id: Option<PlayerId>) -> State {
+        let mut state = self.clone();
+        state.rand = AlgomancerRng::new(AlgomancerRngSeed::default());
+
+        if let Some(deck) = &mut state.common_deck {
+            deck.hide();
+        }
+
+        for region in &mut state.regions {
+            let region = Arc::make_mut(region);
+            for player in &mut region.players {
+                if let Some(deck) = &mut player.own_deck {
+                    deck.hide();
+                }
+
+                if Some(player.id) != player_id {
+                    player.hand.hide();
+                    if let Some(pack) = &mut player.pack {
+                        pack.hide();
+                    }
+                }
+            }
+        }
+
+        state
+    }
+}
+
+impl StaticStateMutation {
+    /// The mutation as the player is allowed to see it, by the same rules as [State::visible_to].
+    ///
+    /// A card moved from one collection the player can't see to another, or created in one, is
+    /// replaced with a [Card::hidden] one, as its id would tell the player which card it is. The
+    /// card is left as it is when it comes from or goes to somewhere the player can see it.
+    pub fn visible_to(&self, player_id: PlayerId) -> StaticStateMutation {
+        self.visible_to_viewer(Some(player_id))
+    }
+
+    /// The mutation as spectators are allowed to see it, like [StaticStateMutation::visible_to]
+    /// but with every player's hand and pack hidden.
+    pub fn visible_to_spectators(&self) -> StaticStateMutation {
+        self.visible_to_viewer(None)
+    }
+
+    fn visible_to_viewer(&self, player_id: Option<PlayerId>) -> StaticStateMutation {
+        match self {
+            StaticStateMutation::MoveCard(m) => {
+                let to = match m.to {
+                    To::Ordered(id, _) | To::Unordered(id) => id,
+                };
+
+                if m.from.is_visible_to(player_id) || to.is_visible_to(player_id) {
+                    return self.clone();
+                }
+
+                StaticStateMutation::MoveCard(MoveCardMutation {
+                    card_id: Card::hidden().card_id,
+                    ..m.clone()
+                })
+            }
+            StaticStateMutation::CreateCard(m) if !m.card_collection_id.is_visible_to(player_id) => {
+                StaticStateMutation::CreateCard(CreateCardMutation {
+                    card_collection_id: m.card_collection_id,
+                    card: Card::hidden(),
+                })
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
+impl CardCollectionId {
+    /// whether the viewer, or spectators when there's none, can see the collection's cards
+    fn is_visible_to(&self, player_id: Option<PlayerId>) -> bool {
+        match self.0 {
+            // every deck, the common one included
+            [_, _, _, 'D'] => false,
+            ['p', _, _, 'h' | 'p'] => {
+                player_id.is_some_and(|p| *self == CardCollectionId::new_hand(p) || *self == CardCollectionId::new_pack(p))
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::ActionType;
+    use crate::game::state::card::CardId;
+    use crate::game::state::GameMode;
+    use crate::game::state::mutation::StaticStateMutation;
+
+    #[test]
+    fn test_visible_to() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let player_id = game.state.regions[0].players[0].id;
+        let opponent_id = game.state.regions[1].players[0].id;
+
+        let state = game.state.visible_to(player_id);
+        let player = state.find_player(player_id).unwrap();
+        let opponent = state.find_player(opponent_id).unwrap();
+
+        assert!(!player.hand.is_empty() && player.hand.iter().all(|c| !c.is_hidden()));
+        assert_eq!(opponent.hand.len(), game.state.find_player(opponent_id).unwrap().hand.len());
+        assert!(opponent.hand.iter().all(|c| c.is_hidden()));
+
+        let common_deck = state.common_deck.as_ref().unwrap();
+        assert_eq!(common_deck.len(), game.state.common_deck.as_ref().unwrap().len());
+        assert!(common_deck.iter().all(|c| c.is_hidden()));
+
+        // the original is untouched
+        assert!(game.state.find_player(opponent_id).unwrap().hand.iter().all(|c| !c.is_hidden()));
+    }
+
+    #[test]
+    fn test_visible_to_spectators() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        let state = game.state.visible_to_spectators();
+        for player in state.players() {
+            assert!(!player.hand.is_empty() && player.hand.iter().all(|c| c.is_hidden()));
+        }
+    }
+
+    #[test]
+    fn test_mutations_visible_to() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let player_id = game.state.regions[0].players[0].id;
+        let opponent_id = game.state.regions[1].players[0].id;
+
+        // pass priority until the draft step
+        let draft = loop {
+            let actions = game.valid_actions();
+            if let Some(draft) = actions.iter().find(|a| a.issuer_player_id() == player_id && matches!(a.action_type(), ActionType::Draft(_))) {
+                break draft.clone();
+            }
+
+            let pass = actions.into_iter().find(|a| matches!(a.action_type(), ActionType::PassPriority(_))).unwrap();
+            game.apply_action(pass).unwrap();
+        };
+        let mutations = game.apply_action(draft).unwrap();
+
+        let moved_card_ids = |mutations: Vec<StaticStateMutation>| -> Vec<CardId> {
+            mutations.iter()
+                .filter_map(|m| match m {
+                    StaticStateMutation::MoveCard(m) => Some(m.card_id),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // the cards the player drafted move from their hand to their pack, which only they can see
+        let drafted = moved_card_ids(mutations.clone());
+        assert!(!drafted.is_empty() && !drafted.contains(&CardId(0)));
+        assert_eq!(moved_card_ids(mutations.iter().map(|m| m.visible_to(player_id)).collect()), drafted);
+
+        let hidden = vec![CardId(0); drafted.len()];
+        assert_eq!(moved_card_ids(mutations.iter().map(|m| m.visible_to(opponent_id)).collect()), hidden);
+        assert_eq!(moved_card_ids(mutations.iter().map(|m| m.visible_to_spectators()).collect()), hidden);
+    }
+}
@@ -1,23 +1,34 @@
-use crate::game::state::mutation::player_mutations::{UpdatePlayerAliveMutation, UpdatePlayerResourcesPlayedMutation};
+use database::CardPrototypeDatabase;
+use crate::game::state::mutation::player_mutations::UpdatePlayerAliveMutation;
+use crate::game::state::mutation::turn_mutations::{ResetTurnRecordMutation, SetTurnMutation};
 use crate::game::state::mutation::{StateMutation};
+use crate::game::state::error::StateError;
 use crate::game::state::stack::Next;
 use crate::game::state::State;
 use crate::{sm_static};
 use crate::game::state::mutation::set_resource_tapped::SetResourceTappedMutation;
+use crate::game::state::mutation::stack_resolve_effect::StackResolveEffectMutation;
 use crate::game::state::permanent::Permanent;
+use crate::game::state::player::Player;
+use crate::game::state::game_result::GameOverReason;
+use crate::game::state::mutation::set_game_result::SetGameResultMutation;
+use crate::game::state::mutation::stack_pass_priority::StackPassPriorityMutation;
+use crate::game::state::region::{Region, DRAW_STEP_CARD_COUNT};
+use crate::game::state::mutation::deal_damage::{DamageTarget, DealDamageMutation};
 use crate::game::state::progression::{BattlePhaseStep, DeploymentPhaseStep, Phase, PlanningPhaseStep};
 
 impl State {
-    pub fn generate_state_based_mutations(&self) -> Vec<StateMutation> {
+    pub fn generate_state_based_mutations(&self, db: &CardPrototypeDatabase) -> Result<Vec<StateMutation>, StateError> {
         let mut mutations = Vec::new();
 
         mutations = add_sba_player(self, mutations);
-        mutations = add_sba_damage(self, mutations);
+        mutations = add_sba_damage(self, db, mutations);
         mutations = add_sba_refresh(self, mutations);
         mutations = add_sba_regroup(self, mutations);
+        mutations = add_sba_resolve_effect(self, db, mutations)?;
         mutations = add_sba_transition(self, mutations);
 
-        mutations
+        Ok(mutations)
     }
 }
 
@@ -39,9 +50,19 @@ fn add_sba_transition(state: &State, mut mutations: Vec<StateMutation>) -> Vec<S
                     });
 
                     if all_players_on_team_passed_priority {
+                        // a new turn starts as the regions move back into the refresh step
+                        if r.step.get_next_phase(&state.game_mode) == Phase::PlanningPhase(PlanningPhaseStep::Refresh) {
+                            mutations.push(sm_static!(SetTurn, SetTurnMutation {
+                                turn: state.turn + 1
+                            }));
+                        }
+
                         for r in &state.regions {
                             mutations.push(state.generate_mutation_for_phase_transition(r.id));
                         }
+
+                        // every region has been transitioned
+                        break;
                     }
                 } else {
                     mutations.push(state.generate_mutation_for_phase_transition(r.id));
@@ -49,8 +70,9 @@ fn add_sba_transition(state: &State, mut mutations: Vec<StateMutation>) -> Vec<S
             }
             Next::PassPriority(_) => {
                 if r.step.is_priority_window() {
-                    // skip priority windows when there's no other player in the region
-                    if r.players.len() < 2 {
+                    // skip priority windows when there's no other player in the region,
+                    // unless there are effects waiting to be resolved
+                    if r.players.len() < 2 && r.stack.effects().is_empty() {
                         mutations.push(state.generate_mutation_for_phase_transition(r.id));
                     }
                 }
@@ -62,34 +84,87 @@ fn add_sba_transition(state: &State, mut mutations: Vec<StateMutation>) -> Vec<S
     mutations
 }
 
-fn add_sba_player(state: &State, mutations: Vec<StateMutation>) -> Vec<StateMutation> {
+/// Once every player in a region has passed priority, the top effect of the stack resolves.
+fn add_sba_resolve_effect(state: &State, db: &CardPrototypeDatabase, mut mutations: Vec<StateMutation>) -> Result<Vec<StateMutation>, StateError> {
+    for r in &state.regions {
+        if let Next::ResolveEffect(effect) = r.stack.next() {
+            mutations.push(sm_static!(StackResolveEffect, StackResolveEffectMutation {
+                region_id: r.id
+            }));
+            mutations.extend(effect.resolve(r.id, db)?);
+        }
+    }
+
+    Ok(mutations)
+}
+
+/// Players die when they run out of health, or when their deck can't supply the cards the
+/// upcoming draw step requires. If that leaves fewer than two teams standing, the game ends.
+fn add_sba_player(state: &State, mut mutations: Vec<StateMutation>) -> Vec<StateMutation> {
+    if state.is_over() {
+        return mutations;
+    }
+
+    let mut dying = Vec::new();
+    let mut reason = None;
+
     for r in &state.regions {
         for p in &r.players {
             if !p.is_alive {
-                // if the player is already dead they can't do anything
+                // dead players can't do anything, so they pass priority whenever it reaches them
+                if let Next::PassPriority(player_id) = r.stack.next() {
+                    if player_id == p.id {
+                        mutations.push(sm_static!(StackPassPriority, StackPassPriorityMutation {
+                            region_id: r.id
+                        }));
+                    }
+                }
                 continue;
             }
 
-            if p.health == 0 {
-                sm_static!(UpdatePlayerAlive, UpdatePlayerAliveMutation {
-                    player_id: p.id,
-                    new_value: false,
-                });
+            let death_reason = if p.health <= 0 {
+                GameOverReason::Health
+            } else if r.step == Phase::PlanningPhase(PlanningPhaseStep::Refresh) && !deck_can_supply_draw_step(state, p) {
+                GameOverReason::DeckOut
+            } else {
+                continue;
+            };
 
-                //todo: remove the players possessions from the game?
+            mutations.push(sm_static!(UpdatePlayerAlive, UpdatePlayerAliveMutation {
+                player_id: p.id,
+                new_value: false,
+            }));
 
-                // if the player died we don't really care what else happens to them
-                continue;
-            }
+            //todo: remove the players possessions from the game?
+
+            dying.push(p.id);
+            reason.get_or_insert(death_reason);
+        }
+    }
+
+    if let Some(reason) = reason {
+        if let Some(result) = state.result_if_players_die(&dying, reason) {
+            mutations.push(sm_static!(SetGameResult, SetGameResultMutation {
+                result
+            }));
         }
     }
 
     mutations
 }
 
-fn add_sba_damage(state: &State, mut mutations: Vec<StateMutation>) -> Vec<StateMutation> {
+/// True if the player's deck holds enough cards for everyone who draws from it in the draw step,
+/// in live-draft every player draws from the same common deck.
+fn deck_can_supply_draw_step(state: &State, player: &Player) -> bool {
+    let deck = player.deck(state);
+    let drawing_players = state.players().filter(|p| p.deck(state).id == deck.id).count();
+    deck.len() >= DRAW_STEP_CARD_COUNT * drawing_players
+}
+
+fn add_sba_damage(state: &State, db: &CardPrototypeDatabase, mut mutations: Vec<StateMutation>) -> Vec<StateMutation> {
     for r in &state.regions {
         if let Phase::BattlePhaseA(BattlePhaseStep::Damage) | Phase::BattlePhaseB(BattlePhaseStep::Damage) = r.step {
+            mutations.extend(combat_damage(state, db, r));
             mutations.push(state.generate_mutation_for_phase_transition(r.id));
         }
     }
@@ -97,6 +172,49 @@ fn add_sba_damage(state: &State, mut mutations: Vec<StateMutation>) -> Vec<State
     mutations
 }
 
+/// Combat damage is dealt by the front rows: each attacker and the blocker in its column deal damage
+/// to each other equal to their power, and an unblocked attacker deals its damage to each opponent
+/// in the region.
+fn combat_damage(state: &State, db: &CardPrototypeDatabase, region: &Region) -> Vec<StateMutation> {
+    let mut mutations = Vec::new();
+    let Some(attacking_formation) = &region.attacking_formation else {
+        return mutations;
+    };
+
+    let blockers = region.defending_formation.as_ref().map_or(&[][..], |f| f.formation.front_row());
+    let mut deal = |source: &Permanent, target| {
+        let amount = source.power(db);
+        if amount > 0 {
+            mutations.push(sm_static!(DealDamage, DealDamageMutation {
+                source_permanent_id: source.common().permanent_id,
+                target,
+                amount,
+            }));
+        }
+    };
+
+    for (col, attacker) in attacking_formation.front_row().iter().enumerate() {
+        let Some(attacker) = attacker else {
+            continue
+        };
+
+        match blockers.get(col).and_then(|b| b.as_ref()) {
+            Some(blocker) => {
+                deal(attacker, DamageTarget::Permanent(blocker.common().permanent_id));
+                deal(blocker, DamageTarget::Permanent(attacker.common().permanent_id));
+            }
+            None => {
+                let team_id = state.find_player(attacker.common().controller_player_id).expect("a player").team_id;
+                for p in region.players.iter().filter(|p| p.team_id != team_id && p.is_alive) {
+                    deal(attacker, DamageTarget::Player(p.id));
+                }
+            }
+        }
+    }
+
+    mutations
+}
+
 fn add_sba_regroup(state: &State, mut mutations: Vec<StateMutation>) -> Vec<StateMutation> {
     for r in &state.regions {
         if let Phase::DeploymentPhase(DeploymentPhaseStep::Regroup) = r.step {
@@ -124,13 +242,12 @@ fn add_sba_refresh(state: &State, mut mutations: Vec<StateMutation>) -> Vec<Stat
                 }
             }
 
-            // reset the number of resources played this turn for each player
+            // start a fresh turn record for each player
             for player in &region.players {
-                if player.resources_played_this_turn != 0 {
+                if !player.this_turn.is_empty() {
                     mutations.push(
-                        sm_static!(UpdatePlayerResourcesPlayed, UpdatePlayerResourcesPlayedMutation {
+                        sm_static!(ResetTurnRecord, ResetTurnRecordMutation {
                             player_id: player.id,
-                            new_value: 0,
                         }));
                 }
             }
@@ -138,4 +255,161 @@ fn add_sba_refresh(state: &State, mut mutations: Vec<StateMutation>) -> Vec<Stat
     }
 
     mutations
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::game::{Game, GameOptions};
+    use crate::game::action::{Action, ActionType};
+    use crate::game::state::GameMode;
+    use crate::game::state::game_result::GameOverReason;
+    use crate::game::state::mutation::StaticStateMutation;
+    use crate::game::state::progression::{Phase, PlanningPhaseStep, Team};
+    use crate::game::state::region::DRAW_STEP_CARD_COUNT;
+    use crate::game::state::card::{Card, CardId};
+    use crate::game::state::formation::{DefensiveFormation, Formation, FormationId, FormationPos};
+    use crate::game::state::mutation::deal_damage::DamageTarget;
+    use crate::game::state::permanent::{Permanent, PermanentCommon, PermanentId};
+    use crate::game::state::player::PlayerId;
+    use crate::game::state::progression::BattlePhaseStep;
+    use crate::game::event::Event;
+
+    #[test]
+    fn test_new_turn_resets_turn_records() {
+        let mut game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        assert_eq!(game.state.turn, 1);
+
+        // the opening hand and resources count towards the first turn
+        assert!(game.state.players().all(|p| !p.this_turn.is_empty()));
+
+        for _ in 0..500 {
+            if game.state.turn == 2 {
+                break;
+            }
+
+            let mut actions: Vec<Action> = game.valid_actions().into_iter()
+                .filter(|a| !matches!(a.action_type(), ActionType::Concede(_)))
+                .collect();
+            actions.sort_by_key(|a| format!("{:?}", a));
+
+            let action = actions.first().expect("an action").clone();
+            game.apply_action(action).unwrap();
+        }
+
+        // the new turn starts in the refresh step, with nothing done yet
+        assert_eq!(game.state.turn, 2);
+        for player in game.state.players() {
+            let region = game.state.find_region_containing_player(player.id).unwrap();
+            assert_eq!(region.step, Phase::PlanningPhase(PlanningPhaseStep::Refresh));
+            assert!(player.this_turn.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_deck_out_counts_every_drawing_player() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        // enough cards for one player's draw, but not for both players drawing from the common deck
+        let mut state = game.state.clone();
+        let deck = state.common_deck.as_mut().expect("a common deck");
+        while deck.len() > DRAW_STEP_CARD_COUNT + 1 {
+            deck.draw().unwrap();
+        }
+        for region in &mut state.regions {
+            Arc::make_mut(region).step = Phase::PlanningPhase(PlanningPhaseStep::Refresh);
+        }
+
+        let mut deck_out = false;
+        for mutation in state.generate_state_based_mutations(&game.cards_db).unwrap() {
+            for m in mutation.to_static(&state).unwrap() {
+                if let StaticStateMutation::SetGameResult(m) = &m {
+                    deck_out = m.result.reason == GameOverReason::DeckOut;
+                }
+            }
+        }
+        assert!(deck_out);
+    }
+
+    #[test]
+    fn test_team_sync_transitions_each_region_once() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+
+        // the initiative team has passed priority in a team sync step, which every region sees
+        let mut state = game.state.clone();
+        for region in &mut state.regions {
+            let region = Arc::make_mut(region);
+            region.step = Phase::PlanningPhase(PlanningPhaseStep::Mana(Team::IT));
+            region.stack.clear_priority();
+        }
+
+        let mut transitions = Vec::new();
+        for mutation in state.generate_state_based_mutations(&game.cards_db).unwrap() {
+            for m in mutation.to_static(&state).unwrap() {
+                if let StaticStateMutation::PhaseTransition(m) = &m {
+                    transitions.push(m.region_id);
+                }
+            }
+        }
+
+        // all the regions move together, once, rather than once for every region that saw it
+        let mut region_ids: Vec<_> = state.regions.iter().map(|r| r.id).collect();
+        transitions.sort_by_key(|r| r.0);
+        region_ids.sort_by_key(|r| r.0);
+        assert_eq!(transitions, region_ids);
+    }
+
+    #[test]
+    fn test_combat_damage() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let rocks = game.cards_db.prototypes.values().find(|p| p.name == "A Fast Pile of Rocks").expect("a fast pile of rocks");
+        assert_eq!(rocks.power, 2);
+
+        let unit = |id: usize, controller_player_id: PlayerId| Permanent::Unit {
+            common: PermanentCommon { permanent_id: PermanentId(id), controller_player_id },
+            card: Card { card_id: CardId(id), prototype_id: rocks.prototype_id },
+            mods: Vec::new(),
+        };
+
+        // two attackers, one of which is blocked
+        let mut state = game.state.clone();
+        let defender = state.regions[0].players[0].id;
+        let attacker = state.regions[1].players[0].id;
+        let defender_health = state.find_player(defender).unwrap().health;
+
+        let mut attacking = Formation::new(FormationId(0), attacker);
+        attacking.insert_at(FormationPos::FrontRow(0), unit(100, attacker)).unwrap();
+        attacking.insert_at(FormationPos::FrontRow(2), unit(101, attacker)).unwrap();
+        attacking.commit().unwrap();
+
+        let mut defending = DefensiveFormation::from_attacking_formation(FormationId(1), defender, &attacking).unwrap();
+        defending.formation.insert_at(FormationPos::FrontRow(0), unit(200, defender)).unwrap();
+
+        let region = Arc::make_mut(&mut state.regions[0]);
+        region.attacking_formation = Some(attacking);
+        region.defending_formation = Some(defending);
+        region.step = Phase::BattlePhaseA(BattlePhaseStep::Damage);
+
+        let mut damage = Vec::new();
+        let mut events = Vec::new();
+        for mutation in state.generate_state_based_mutations(&game.cards_db).unwrap() {
+            for m in mutation.to_static(&state).unwrap() {
+                if let StaticStateMutation::DealDamage(d) = &m {
+                    damage.push((d.source_permanent_id, d.target, d.amount));
+                }
+                events.extend(m.events(&state));
+                state = state.mutate(&game.cards_db, &m).unwrap();
+            }
+        }
+
+        assert_eq!(damage, vec![
+            (PermanentId(100), DamageTarget::Permanent(PermanentId(200)), 2),
+            (PermanentId(200), DamageTarget::Permanent(PermanentId(100)), 2),
+            (PermanentId(101), DamageTarget::Player(defender), 2),
+        ]);
+        assert_eq!(state.find_player(defender).unwrap().health, defender_health - 2);
+        assert!(events.iter().any(|e| matches!(e, Event::DamageDealt { target: DamageTarget::Permanent(PermanentId(200)), .. })));
+        assert!(events.iter().any(|e| matches!(e, Event::PlayerLostLife { player_id, amount: 2 } if *player_id == defender)));
+        assert_eq!(state.regions[0].step, Phase::BattlePhaseA(BattlePhaseStep::AfterCombatPriorityWindow));
+    }
+}
@@ -0,0 +1,252 @@
+use algocore::Trigger;
+use database::CardPrototypeDatabase;
+use crate::game::event::Event;
+use crate::game::state::mutation::deal_damage::DamageTarget;
+use crate::game::state::mutation::stack_add_priority::StackAddPriorityMutation;
+use crate::game::state::mutation::stack_clear_priority::StackClearPriorityMutation;
+use crate::game::state::mutation::stack_push_effect::StackPushEffectMutation;
+use crate::game::state::mutation::StateMutation;
+use crate::game::state::permanent::Permanent;
+use crate::game::state::progression::{BattlePhaseStep, Phase, PlanningPhaseStep};
+use crate::game::state::region::RegionId;
+use crate::game::state::stack::Effect;
+use crate::game::state::State;
+use crate::sm_static;
+
+impl State {
+    /// Matches the triggered abilities of the permanents in play against the events, and generates
+    /// the mutations that push the resulting effects onto the stack of the source's region.
+    ///
+    /// Effects are pushed in APNAP order, the initiative team's effects go on the stack first, so
+    /// the non-initiative team's effects resolve first.
+    pub fn generate_trigger_mutations(&self, db: &CardPrototypeDatabase, events: &[Event]) -> Vec<StateMutation> {
+        if events.is_empty() {
+            return Vec::new();
+        }
+
+        // units that died are out of play, but their 'when I die' abilities still look back at them
+        let mut sources: Vec<(RegionId, &Permanent)> = self.regions.iter()
+            .flat_map(|r| r.permanents().into_iter().map(move |p| (r.id, p)))
+            .collect();
+
+        for event in events {
+            if let Event::UnitDied { region_id, permanent } = event {
+                sources.push((*region_id, permanent));
+            }
+        }
+
+        let mut effects: Vec<(RegionId, Effect)> = Vec::new();
+        for event in events {
+            for (region_id, permanent) in &sources {
+                for prototype_id in permanent.ability_prototype_ids() {
+                    let proto = db.prototypes.get(&prototype_id).expect("a card prototype");
+                    for ability in &proto.triggers {
+                        if !trigger_matches(&ability.trigger, event, *region_id, permanent) {
+                            continue
+                        }
+
+                        effects.push((*region_id, Effect {
+                            source_permanent_id: permanent.common().permanent_id,
+                            controller_player_id: permanent.common().controller_player_id,
+                            ability: ability.clone(),
+                        }));
+                    }
+                }
+            }
+        }
+
+        let initiative_team = self.initiative_team();
+        effects.sort_by_key(|(_, effect)| {
+            let controller = self.find_player(effect.controller_player_id).expect("a player");
+            controller.team_id != initiative_team
+        });
+
+        let mut region_ids: Vec<RegionId> = Vec::new();
+        let mut mutations: Vec<StateMutation> = Vec::new();
+        for (region_id, effect) in effects {
+            if !region_ids.contains(&region_id) {
+                region_ids.push(region_id);
+            }
+
+            mutations.push(sm_static!(StackPushEffect, StackPushEffectMutation {
+                region_id,
+                effect
+            }));
+        }
+
+        // in a priority window, every player in the region gets priority again so they can respond
+        // before the effects resolve, even if they had already passed. Outside of priority windows
+        // there's nothing to respond with, and the effects resolve once the step's priority is passed.
+        for region_id in region_ids {
+            let region = self.find_region(region_id).expect("a region");
+            if !region.step.is_priority_window() {
+                continue
+            }
+
+            mutations.push(sm_static!(StackClearPriority, StackClearPriorityMutation {
+                region_id
+            }));
+
+            for p in self.players_in_region(region_id).expect("players") {
+                mutations.push(sm_static!(StackAddPriority, StackAddPriorityMutation {
+                    region_id,
+                    player_id: p.id
+                }));
+            }
+        }
+
+        mutations
+    }
+}
+
+/// True if `trigger`, belonging to `permanent` in `region_id`, is satisfied by `event`.
+fn trigger_matches(trigger: &Trigger, event: &Event, region_id: RegionId, permanent: &Permanent) -> bool {
+    let me = permanent.common();
+
+    match (trigger, event) {
+        (Trigger::SelfSpawns, Event::UnitSpawned { permanent: spawned, .. }) => {
+            spawned.common().permanent_id == me.permanent_id
+        }
+        (Trigger::SelfDies, Event::UnitDied { permanent: died, .. }) => {
+            died.common().permanent_id == me.permanent_id
+        }
+        (Trigger::SelfModded, Event::Modded { host_permanent_id, .. }) => {
+            *host_permanent_id == me.permanent_id
+        }
+        (Trigger::SelfDealtDamage, Event::DamageDealt { target: DamageTarget::Permanent(target), .. }) => {
+            *target == me.permanent_id
+        }
+        (Trigger::UnitDies { another, nontoken }, Event::UnitDied { permanent: died, .. }) => {
+            let is_me = died.common().permanent_id == me.permanent_id;
+            (!*another || !is_me) && (!*nontoken || !died.is_token())
+        }
+        (Trigger::AllySpawns { another, nontoken }, Event::UnitSpawned { permanent: spawned, .. }) => {
+            let is_me = spawned.common().permanent_id == me.permanent_id;
+            let is_ally = spawned.common().controller_player_id == me.controller_player_id;
+            is_ally && (!*another || !is_me) && (!*nontoken || !spawned.is_token())
+        }
+        (Trigger::PlayerLosesLife, Event::PlayerLostLife { .. }) => true,
+        (Trigger::AfterCombat, Event::PhaseEntered { region_id: entered, phase }) => {
+            *entered == region_id && matches!(
+                phase,
+                Phase::BattlePhaseA(BattlePhaseStep::AfterCombatPriorityWindow) |
+                Phase::BattlePhaseB(BattlePhaseStep::AfterCombatPriorityWindow)
+            )
+        }
+
+        // the turn ends as the region moves from the deployment phase back into the refresh step
+        (Trigger::EndOfTurn, Event::PhaseEntered { region_id: entered, phase }) => {
+            *entered == region_id && *phase == Phase::PlanningPhase(PlanningPhaseStep::Refresh)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use algocore::Trigger;
+    use database::CardPrototypeId;
+    use crate::game::{Game, GameOptions};
+    use crate::game::event::Event;
+    use crate::game::state::mutation::deal_damage::DamageTarget;
+    use crate::game::state::GameMode;
+    use crate::game::state::card::{Card, CardId};
+    use crate::game::state::permanent::{Permanent, PermanentCommon, PermanentId};
+    use crate::game::state::player::PlayerId;
+    use crate::game::state::progression::{BattlePhaseStep, Phase};
+    use crate::game::state::region::RegionId;
+    use crate::game::trigger::trigger_matches;
+
+    fn unit(permanent_id: usize, controller: u8) -> Permanent {
+        Permanent::Unit {
+            common: PermanentCommon {
+                permanent_id: PermanentId(permanent_id),
+                controller_player_id: PlayerId(controller),
+            },
+            card: Card { card_id: CardId(permanent_id), prototype_id: CardPrototypeId(1) },
+            mods: Vec::new(),
+        }
+    }
+
+    fn token(permanent_id: usize, controller: u8) -> Permanent {
+        Permanent::UnitToken {
+            common: PermanentCommon {
+                permanent_id: PermanentId(permanent_id),
+                controller_player_id: PlayerId(controller),
+            },
+            card_prototype_id: CardPrototypeId(2),
+        }
+    }
+
+    #[test]
+    fn test_trigger_matches() {
+        let region_id = RegionId(1);
+        let me = unit(1, 1);
+
+        let spawned = |permanent| Event::UnitSpawned { region_id, permanent };
+        let died = |permanent| Event::UnitDied { region_id, permanent };
+
+        assert!(trigger_matches(&Trigger::SelfSpawns, &spawned(unit(1, 1)), region_id, &me));
+        assert!(!trigger_matches(&Trigger::SelfSpawns, &spawned(unit(2, 1)), region_id, &me));
+        assert!(trigger_matches(&Trigger::SelfDies, &died(unit(1, 1)), region_id, &me));
+
+        let another_nontoken_ally = Trigger::AllySpawns { another: true, nontoken: true };
+        assert!(trigger_matches(&another_nontoken_ally, &spawned(unit(2, 1)), region_id, &me));
+        assert!(!trigger_matches(&another_nontoken_ally, &spawned(unit(1, 1)), region_id, &me));
+        assert!(!trigger_matches(&another_nontoken_ally, &spawned(token(2, 1)), region_id, &me));
+        assert!(!trigger_matches(&another_nontoken_ally, &spawned(unit(2, 2)), region_id, &me));
+
+        let nontoken_unit_dies = Trigger::UnitDies { another: false, nontoken: true };
+        assert!(trigger_matches(&nontoken_unit_dies, &died(unit(2, 2)), region_id, &me));
+        assert!(!trigger_matches(&nontoken_unit_dies, &died(token(2, 2)), region_id, &me));
+
+        let after_combat = Event::PhaseEntered { region_id, phase: Phase::BattlePhaseA(BattlePhaseStep::AfterCombatPriorityWindow) };
+        assert!(trigger_matches(&Trigger::AfterCombat, &after_combat, region_id, &me));
+        assert!(!trigger_matches(&Trigger::AfterCombat, &after_combat, RegionId(2), &me));
+        assert!(!trigger_matches(&Trigger::EndOfTurn, &after_combat, region_id, &me));
+
+        let damaged = |target| Event::DamageDealt { source: PermanentId(3), target, amount: 2 };
+        assert!(trigger_matches(&Trigger::SelfDealtDamage, &damaged(DamageTarget::Permanent(PermanentId(1))), region_id, &me));
+        assert!(!trigger_matches(&Trigger::SelfDealtDamage, &damaged(DamageTarget::Permanent(PermanentId(2))), region_id, &me));
+        assert!(!trigger_matches(&Trigger::SelfDealtDamage, &damaged(DamageTarget::Player(PlayerId(1))), region_id, &me));
+        assert!(!trigger_matches(&Trigger::PlayerLosesLife, &damaged(DamageTarget::Player(PlayerId(1))), region_id, &me));
+    }
+
+    #[test]
+    fn test_trigger_gives_priority() {
+        let game = Game::new(&GameOptions { seed: [0; 16], game_mode: GameMode::new_player_mode() }).unwrap();
+        let proto = game.cards_db.prototypes.values()
+            .find(|p| p.triggers.iter().any(|t| t.trigger == Trigger::SelfSpawns))
+            .expect("a card with a 'when I spawn' ability");
+
+        for (step, gives_priority) in [
+            (Phase::BattlePhaseA(BattlePhaseStep::AfterAttackPriorityWindow), true),
+            (Phase::BattlePhaseA(BattlePhaseStep::Damage), false),
+        ] {
+            let mut state = game.state.clone();
+            let region = Arc::make_mut(&mut state.regions[0]);
+            let region_id = region.id;
+            let player_id = region.players[0].id;
+            region.step = step;
+            region.stack.clear_priority();
+
+            let permanent = Permanent::Unit {
+                common: PermanentCommon { permanent_id: PermanentId(100), controller_player_id: player_id },
+                card: Card { card_id: CardId(100), prototype_id: proto.prototype_id },
+                mods: Vec::new(),
+            };
+            region.unformed_permanents.push(permanent.clone());
+
+            for mutation in state.generate_trigger_mutations(&game.cards_db, &[Event::UnitSpawned { region_id, permanent }]) {
+                for m in mutation.to_static(&state).unwrap() {
+                    state = state.mutate(&game.cards_db, &m).unwrap();
+                }
+            }
+
+            let stack = &state.regions[0].stack;
+            assert!(!stack.effects().is_empty());
+            assert_eq!(stack.priority().contains(&player_id), gives_priority);
+        }
+    }
+}
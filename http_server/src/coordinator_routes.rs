@@ -22,6 +22,16 @@ pub async fn lobbies(coordinator: &State<Arc<RwLock<Coordinator>>>) -> Json<Vec<
     Json(lobby_models)
 }
 
+#[get("/games")]
+pub async fn games(coordinator: &State<Arc<RwLock<Coordinator>>>, runners: &State<Arc<RwLock<Vec<algomanserver::Runner>>>>) -> Json<Vec<models::RunningGameModel>> {
+    let coordinator = coordinator.read().await;
+    let runners = runners.read().await;
+
+    let game_models = services::coordinator_service::running_games(coordinator.deref(), runners.as_slice());
+
+    Json(game_models)
+}
+
 #[post("/register", format = "json", data = "<data>")]
 pub async fn register(coordinator: &State<Arc<RwLock<Coordinator>>>, data: Json<models::RegistrationRequest>) -> Result<Json<models::RegistrationResponse>, Error> {
     let mut coordinator = coordinator.write().await;
@@ -35,10 +45,31 @@ pub async fn register(coordinator: &State<Arc<RwLock<Coordinator>>>, data: Json<
 }
 
 
-#[get("/lobby/create")]
-pub async fn lobby_create(ws: WebSocket, coordinator: &State<Arc<RwLock<Coordinator>>>, runners: &State<Arc<RwLock<Vec<algomanserver::Runner>>>>) -> ws::Channel<'static> {
+#[post("/lobby/<lobby_id>/options", format = "json", data = "<data>")]
+pub async fn lobby_options(coordinator: &State<Arc<RwLock<Coordinator>>>, lobby_id: u64, data: Json<models::LobbyOptionsRequest>) -> Result<Json<models::LobbyModel>, Error> {
+    let (lobby_model, event) = {
+        let mut coordinator = coordinator.write().await;
+
+        services::coordinator_service::update_lobby_options(
+            coordinator.deref_mut(),
+            LobbyId(lobby_id),
+            data.into_inner()
+        )?
+    };
+
+    // the settings have changed either way, an agent that stopped listening just isn't told
+    if let Err(err) = event.send().await {
+        tracing::warn!(%err, "failed to tell a lobby its settings changed");
+    }
+
+    Ok(Json(lobby_model))
+}
+
+#[get("/lobby/create?<name>")]
+pub async fn lobby_create(ws: WebSocket, coordinator: &State<Arc<RwLock<Coordinator>>>, runners: &State<Arc<RwLock<Vec<algomanserver::Runner>>>>, name: Option<String>) -> ws::Channel<'static> {
     let coordinator = coordinator.inner().clone();
     let runners = runners.inner().clone();
+    let name = name.unwrap_or_else(|| "Lobby".to_string());
 
     ws.channel(move |stream| {
         Box::pin(async move {
@@ -51,7 +82,7 @@ pub async fn lobby_create(ws: WebSocket, coordinator: &State<Arc<RwLock<Coordina
 
             let lobby_id = {
                 let mut coordinator = coordinator.write().await;
-                match coordinator.create_lobby_with_host(agent_key, "Lobby").await {
+                match coordinator.create_lobby_with_host(agent_key, &name).await {
                     Ok(lobby_id) => lobby_id,
                     Err(err) => {
                         ws_close_with_error(tx, format!("{}", err)).await;
@@ -74,10 +105,7 @@ pub async fn lobby_create(ws: WebSocket, coordinator: &State<Arc<RwLock<Coordina
 
             {
                 let mut coordinator = coordinator.write().await;
-                match coordinator.leave_current_lobby(agent_key).await {
-                    Ok(_) => {}
-                    Err(_) => {}
-                }
+                let _ = coordinator.leave_current_lobby(agent_key).await;
             }
 
             Ok(())
@@ -99,7 +127,7 @@ pub async fn lobby_join(ws: WebSocket, coordinator: &State<Arc<RwLock<Coordinato
             let agent_key = match ws_request_agent_key(&mut tx, &mut rx).await {
                 Ok(agent_key) => agent_key,
                 Err(err) => {
-                    eprintln!("{err}");
+                    tracing::warn!(%err, "failed to get an agent key");
                     ws_close_with_error(tx, err.to_string()).await;
                     return Ok(())
                 }
@@ -142,10 +170,7 @@ pub async fn lobby_join(ws: WebSocket, coordinator: &State<Arc<RwLock<Coordinato
             // if the agent stops listening to the lobby, they must leave the lobby
             {
                 let mut coordinator = coordinator.write().await;
-                match coordinator.leave_current_lobby(agent_key).await {
-                    Ok(_) => {}
-                    Err(_) => {}
-                }
+                let _ = coordinator.leave_current_lobby(agent_key).await;
             }
 
             Ok(())
@@ -27,6 +27,18 @@ pub enum Error {
 
     #[response(status = 400)]
     LobbyIsFull(String),
+
+    #[response(status = 403)]
+    NotLobbyHost(String),
+
+    #[response(status = 400)]
+    InvalidSeat(String),
+
+    #[response(status = 400)]
+    InvalidCapacity(String),
+
+    #[response(status = 400)]
+    InvalidEntropy(String),
 }
 
 
@@ -66,6 +78,19 @@ impl From<algomanserver::coordinator::Error> for Error {
             algomanserver::coordinator::Error::LobbyIsFull(_) => {
                 Error::LobbyIsFull(value.to_string())
             }
+            algomanserver::coordinator::Error::NotLobbyHost(_) => {
+                Error::NotLobbyHost(value.to_string())
+            }
+            algomanserver::coordinator::Error::InvalidSeat(_) |
+            algomanserver::coordinator::Error::SeatTaken(_) => {
+                Error::InvalidSeat(value.to_string())
+            }
+            algomanserver::coordinator::Error::InvalidCapacity { .. } => {
+                Error::InvalidCapacity(value.to_string())
+            }
+            algomanserver::coordinator::Error::InvalidEntropy(_) => {
+                Error::InvalidEntropy(value.to_string())
+            }
         }
     }
 }
\ No newline at end of file
@@ -6,11 +6,15 @@ mod runner_routes;
 mod error;
 mod services;
 
+#[cfg(test)]
+mod schema;
+
 #[macro_use]
 extern crate rocket;
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing_subscriber::EnvFilter;
 use algomanserver::Coordinator;
 
 
@@ -18,6 +22,11 @@ use algomanserver::Coordinator;
 #[tokio::main]
 async fn rocket() -> _ {
 
+    // the log level is taken from RUST_LOG, e.g. RUST_LOG=algomancer_gre=trace for every mutation
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
     let coordinator = Coordinator::new();
     let coordinator_rwl = RwLock::new(coordinator);
     let coordinator_arc: Arc<RwLock<Coordinator>> = Arc::new(coordinator_rwl);
@@ -34,11 +43,14 @@ async fn rocket() -> _ {
         .mount("/coordinator/", routes![
             coordinator_routes::register,
             coordinator_routes::lobbies,
+            coordinator_routes::games,
             coordinator_routes::lobby_create,
             coordinator_routes::lobby_join,
+            coordinator_routes::lobby_options,
         ])
         .mount("/runner/", routes![
             runner_routes::runner_connect,
+            runner_routes::runner_spectate,
         ])
 }
 
@@ -55,7 +67,8 @@ fn generate_test_lobbies(coordinator_arc: &Arc<RwLock<Coordinator>>) {
 
             let lobby_id = coordinator.create_lobby_with_host(agent_key, format!("Lobby {i}").as_str()).await.unwrap();
 
-            for _a in 1..=(rand::random::<u64>() % 4) {
+            let capacity = coordinator.try_get_lobby(lobby_id).unwrap().capacity as u64;
+            for _a in 1..=(rand::random::<u64>() % capacity) {
                 let (_agent_id, agent_key) = coordinator.create_new_agent(format!("Agent {}", a_id).as_str()).await.unwrap();
                 a_id += 1;
 
@@ -1,9 +1,16 @@
 use rocket::serde::Serialize;
 use rocket::serde::Deserialize;
+use schemars::JsonSchema;
+use algomancer_gre::game::action::Action;
+use algomancer_gre::game::fairness::SeedReveal;
+use algomancer_gre::game::state::game_result::GameResult;
+use algomancer_gre::game::state::mutation::StaticStateMutation;
+use algomancer_gre::game::state::player::PlayerId;
+use algomancer_gre::game::state::State;
 
-use crate::models::{AgentModel, LobbyModel, MigrationInfoModel};
+use crate::models::{AgentModel, LobbyModel, LobbyOptionsRequest, MigrationInfoModel};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WsMessage {
     ServerRequest { value: ServerRequest },
@@ -14,7 +21,7 @@ pub enum WsMessage {
     Error { value: String },
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ServerRequest {
     AgentKeyRequest,
@@ -31,8 +38,9 @@ impl ServerRequest {
 }
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ServerResponse {
     StartGameResponse,
     LobbyCreated {
@@ -40,7 +48,7 @@ pub enum ServerResponse {
     },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ServerEvent {
     AgentJoinedLobby {
@@ -53,31 +61,96 @@ pub enum ServerEvent {
     NewHost {
         agent_id: String,
     },
+
+    /// the host changed the lobby's capacity, game options or seating
+    LobbySettingsChanged {
+        lobby: LobbyModel,
+    },
     Migrate {
         agent_id: String,
         migration_info: MigrationInfoModel
     },
+
+    /// the game as the client's player sees it, sent when the game starts and after every action
+    GameState {
+        player_id: PlayerId,
+        state: State,
+        valid_actions: Vec<Action>,
+    },
+
+    /// the game as spectators see it, sent when a spectator starts watching and after every action
+    SpectatorState {
+        state: State,
+    },
+
+    /// the changes an action made to the state, sent to every client in the game
+    Mutations {
+        mutations: Vec<StaticStateMutation>,
+    },
+    /// the player's client lost its connection, the game waits for the player until it reconnects
+    PlayerAway {
+        player_id: PlayerId,
+    },
+    PlayerReturned {
+        player_id: PlayerId,
+    },
+
+    /// the end of the game, along with every action taken in it and what its seed was made from, so
+    /// that the game can be replayed to check it was dealt fairly
+    GameOver {
+        result: GameResult,
+        actions: Vec<Action>,
+        seed_reveal: Option<SeedReveal>,
+    },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
 pub enum ClientRequest {
     StartGameRequest {
         agent_key: String,
-        lobby_id: String
+        lobby_id: String,
+
+        /// a saved game (a serialized game) to resume rather than starting a new one
+        #[serde(default)]
+        saved_game: Option<String>,
+    },
+
+    /// entropy of the agent's own to mix into the game's seed, at most 64 bytes
+    ContributeEntropyRequest {
+        agent_key: String,
+        lobby_id: String,
+        entropy: Vec<u8>,
+    },
+
+    /// changes to the lobby's settings, which only its host can make
+    UpdateLobbyRequest {
+        lobby_id: String,
+        options: LobbyOptionsRequest,
+    },
+
+    /// an action for the client's player to take, sent to the runner once the game has started
+    ActionRequest {
+        action: Action,
     },
 }
 
 impl ClientRequest {
+    #[allow(dead_code)]
     pub fn is_correct_response_type(&self, res: &ServerResponse) -> bool {
         match self {
-            ClientRequest::StartGameRequest { .. } => matches!(res, ServerResponse::StartGameResponse {..}),
+            ClientRequest::StartGameRequest { .. } => matches!(res, ServerResponse::StartGameResponse),
+            // the rest have no response, their results are sent as events
+            ClientRequest::ContributeEntropyRequest { .. } |
+            ClientRequest::UpdateLobbyRequest { .. } |
+            ClientRequest::ActionRequest { .. } => false,
         }
     }
 }
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ClientResponse {
     AgentKeyResponse {
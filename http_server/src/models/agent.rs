@@ -1,7 +1,8 @@
 use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use algomanserver::Agent;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct AgentModel {
     pub id: String,
     pub username: String,
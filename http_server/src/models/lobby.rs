@@ -1,26 +1,48 @@
 use rocket::serde::Serialize;
+use schemars::JsonSchema;
 use serde::Deserialize;
-use algomanserver::{Coordinator, Lobby, LobbyId};
+use algomancer_gre::game::fairness::SeedCommitment;
+use algomancer_gre::game::state::GameMode;
+use algomanserver::{Coordinator, Lobby, LobbyId, Seating};
 
 use crate::models::AgentModel;
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct LobbyModel {
     pub id: LobbyId,
     pub name: String,
     pub capacity: u8,
     pub mode: String,
+    pub game_mode: GameMode,
+    pub seating: Seating,
+
+    /// the hash of the server's part of the game's seed, revealed once the game is over
+    pub seed_commitment: SeedCommitment,
     pub agents: Vec<AgentModel>
 }
 
+/// Changes to a lobby's settings, which only its host can make. What's left out stays as it is, and
+/// the game mode is applied before the capacity, as changing it resets it. Either every change is
+/// made or, if any of them is invalid, none is.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct LobbyOptionsRequest {
+    pub agent_key: String,
+    pub game_mode: Option<GameMode>,
+    pub capacity: Option<u8>,
+    pub seating: Option<Seating>,
+}
+
 impl LobbyModel {
     pub fn from_coordinator_lobby(coordinator: &Coordinator, lobby: &Lobby) -> Self {
         Self {
             id: lobby.id,
             name: lobby.name.clone(),
             capacity: lobby.capacity,
-            mode: lobby.game_options.game_mode.to_string(),
+            mode: lobby.game_mode.to_string(),
+            game_mode: lobby.game_mode.clone(),
+            seating: lobby.seating.clone(),
+            seed_commitment: lobby.seed_commitment.clone(),
             agents: lobby.agent_ids.iter().filter(|a| coordinator.try_get_agent(**a).is_ok()).map(|a| {
                 let agent = coordinator.try_get_agent(*a).expect("missing agents already should be filtered out");
 
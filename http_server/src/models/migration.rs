@@ -1,8 +1,20 @@
 use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use algomancer_gre::game::fairness::SeedCommitment;
+use algomancer_gre::game::state::player::PlayerId;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct MigrationInfoModel {
     pub runner_id: String,
     pub agent_key: String,
     pub client_key: String,
+
+    /// the player the agent plays as once the game starts
+    pub player_id: PlayerId,
+
+    /// the hash of the server's part of the game's seed, revealed once the game is over
+    pub seed_commitment: Option<SeedCommitment>,
+
+    /// the hash of everything the game's seed is made from, including each agent's contribution
+    pub seed_inputs_commitment: Option<SeedCommitment>,
 }
\ No newline at end of file
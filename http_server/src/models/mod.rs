@@ -5,17 +5,21 @@ mod lobby;
 mod agent;
 mod registration;
 mod migration;
+mod running_game;
 
-pub use lobby::LobbyModel;
+pub use lobby::{LobbyModel, LobbyOptionsRequest};
 pub use agent::AgentModel;
 pub use migration::MigrationInfoModel;
+pub use running_game::RunningGameModel;
 pub use registration::{RegistrationRequest, RegistrationResponse};
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize)]
 pub struct AgentKeyRequest {
     pub agent_key: String,
 }
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize)]
 pub struct AgentLobbyRequest {
     pub agent_key: AgentKey,
@@ -1,12 +1,13 @@
 use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use algomanserver::{AgentId};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct RegistrationRequest {
     pub username: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct RegistrationResponse {
     pub agent_id: AgentId,
     pub agent_key: String,
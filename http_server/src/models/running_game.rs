@@ -0,0 +1,41 @@
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use algomancer_gre::game::state::player::PlayerId;
+use algomanserver::{Coordinator, Runner, RunnerId};
+
+use crate::models::AgentModel;
+
+/// A game being played, which spectators can watch through the runner's spectate endpoint.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct RunningGameModel {
+    pub runner_id: RunnerId,
+    pub mode: String,
+    pub turn: u32,
+    pub seats: Vec<SeatModel>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SeatModel {
+    pub player_id: PlayerId,
+    pub agent: AgentModel,
+}
+
+impl RunningGameModel {
+    pub fn from_runner(coordinator: &Coordinator, runner: &Runner) -> Self {
+        let state = &runner.game().state;
+
+        Self {
+            runner_id: runner.runner_id,
+            mode: state.game_mode.to_string(),
+            turn: state.turn,
+            seats: runner.seats().iter().filter_map(|seat| {
+                let agent = coordinator.try_get_agent(seat.agent_id).ok()?;
+
+                Some(SeatModel {
+                    player_id: seat.player_id,
+                    agent: agent.into(),
+                })
+            }).collect(),
+        }
+    }
+}
@@ -1,19 +1,27 @@
-
-
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Duration;
 
 use rocket::State;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
-use ws::{WebSocket};
-use algomanserver::{RunnerId};
+use tokio::time::Instant;
+use ws::{Message, WebSocket};
+use algomanserver::{ClientConnection, GameOver, Runner, RunnerEvent, RunnerId, SpectatorEvent};
 
 
-use crate::ws::{ws_close_with_error, ws_request_response};
+use crate::ws::{RequestResponseError, RX, TX, ws_close_normally, ws_close_with_error, ws_request_response, ws_send_err, ws_send_json};
 use rocket::futures::{StreamExt};
 use algomanserver::client::ClientKey;
 
-use crate::messages::{ServerRequest, ClientResponse};
+use crate::messages::{ServerRequest, ClientResponse, ClientRequest, ServerEvent, WsMessage};
+
+/// the longest a spectator's view of a game can be delayed by, in seconds
+const MAX_SPECTATOR_DELAY: u64 = 15 * 60;
 
+/// the most events a delayed spectator can have waiting to be sent, past which the oldest updates
+/// are dropped, as the state sent with the next one brings the spectator up to date anyway
+const MAX_PENDING_SPECTATOR_EVENTS: usize = 256;
 
 #[get("/connect")]
 pub async fn runner_connect(ws: WebSocket, runners: &State<Arc<RwLock<Vec<algomanserver::Runner>>>>) -> ws::Channel<'static> {
@@ -21,48 +29,170 @@ pub async fn runner_connect(ws: WebSocket, runners: &State<Arc<RwLock<Vec<algoma
 
     ws.channel(move |stream| {
         Box::pin(async move {
-            println!("someone connected");
+            tracing::debug!("a client connected to the runner endpoint");
             let (mut tx, mut rx) = stream.split();
 
-            match ws_request_response(&mut tx, &mut rx, ServerRequest::MigrationInfoRequest).await {
-                Ok(response) => {
-                    match response {
-                        ClientResponse::MigrationInfoResponse { info } => {
-                            println!("they have info");
+            let info = match ws_request_response(&mut tx, &mut rx, ServerRequest::MigrationInfoRequest).await {
+                Ok(ClientResponse::MigrationInfoResponse { info }) => info,
+                Ok(_) => {
+                    ws_close_with_error(tx, "expected migration info response".to_string()).await;
+                    return Ok(());
+                }
+                Err(err) => {
+                    ws_close_with_error(tx, err.to_string()).await;
+                    return Ok(());
+                }
+            };
+
+            let (runner_id, client_key): (RunnerId, ClientKey) = match (info.runner_id.parse::<u64>(), info.client_key.parse::<u64>()) {
+                (Ok(runner_id), Ok(client_key)) => (runner_id.into(), client_key.into()),
+                _ => {
+                    ws_close_with_error(tx, "could not parse the migration info".to_string()).await;
+                    return Ok(());
+                }
+            };
 
-                            let runner_id: RunnerId = info.runner_id.parse::<u64>().unwrap().into();
+            // the lock is released before waiting, so that the other clients can connect
+            let (mut connection, started) = {
+                let mut runners = runners.write().await;
+                let Some(runner) = runners.iter_mut().find(|r| r.runner_id == runner_id) else {
+                    ws_close_with_error(tx, "runner not found".to_string()).await;
+                    return Ok(());
+                };
 
-                            {
-                                let mut runners = runners.write().await;
-                                if let Some(runner) = runners.iter_mut().find(|r| r.runner_id == runner_id) {
-                                    let client_key: ClientKey = info.client_key.parse::<u64>().unwrap().into();
-                                    let mut ready_rx = runner.connect_client(client_key);
+                match runner.connect_client(client_key) {
+                    Ok(connection) => (connection, runner.has_started()),
+                    Err(err) => {
+                        ws_close_with_error(tx, err.to_string()).await;
+                        return Ok(());
+                    }
+                }
+            };
 
-                                    println!("connected");
+            // a reconnecting client rejoins the game as it is
+            if !started && connection.game_over.is_none() {
+                tracing::info!(%runner_id, "client joined the runner, waiting for the game to start");
 
-                                    while let Ok(_) = ready_rx.recv().await {
-                                        println!("msg");
-                                        break;
-                                    }
+                loop {
+                    tokio::select! {
+                        event = connection.events_rx.recv() => {
+                            match event {
+                                Ok(RunnerEvent::Started) => break,
+                                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                                Err(RecvError::Closed) => return Ok(()),
+                            }
+                        }
+                        message = rx.next() => {
+                            if let Some(Ok(Message::Close(_))) | Some(Err(_)) | None = message {
+                                disconnect(&runners, runner_id, client_key).await;
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
 
-                                    println!("running");
+                tracing::info!(%runner_id, "the game has started");
+            }
+
+            play(runners, runner_id, client_key, tx, rx, connection).await;
+
+            Ok(())
+        })
+    })
+}
+
+/// Streams a game to a spectator, as spectators see it: the state when they start watching, then
+/// the mutations of every action taken and the state after it. With a delay (in seconds), each is
+/// sent that long after it happened, so that what spectators see can't be relayed to the players
+/// as they play, though only the latest [MAX_PENDING_SPECTATOR_EVENTS] are kept waiting. Spectators
+/// can't take actions, anything they send is rejected.
+#[get("/<runner_id>/spectate?<delay>")]
+pub async fn runner_spectate(ws: WebSocket, runners: &State<Arc<RwLock<Vec<algomanserver::Runner>>>>, runner_id: u64, delay: Option<u64>) -> ws::Channel<'static> {
+    let runners = runners.inner().clone();
+    let runner_id = RunnerId(runner_id);
+    let delay = Duration::from_secs(delay.unwrap_or(0).min(MAX_SPECTATOR_DELAY));
+
+    ws.channel(move |stream| {
+        Box::pin(async move {
+            let (mut tx, mut rx) = stream.split();
+
+            let spectating = {
+                let runners = runners.read().await;
+                runners.iter()
+                    .find(|r| r.runner_id == runner_id && r.is_spectatable())
+                    .map(|runner| runner.spectate())
+            };
+
+            let Some((state, mut events_rx)) = spectating else {
+                ws_close_with_error(tx, "no game is being played by that runner".to_string()).await;
+                return Ok(());
+            };
+
+            tracing::info!(%runner_id, ?delay, "a spectator is watching the game");
+
+            // what's yet to be sent, in order, along with when it happened
+            let mut pending: VecDeque<(Instant, ServerEvent)> = VecDeque::new();
+            pending.push_back((Instant::now(), ServerEvent::SpectatorState { state }));
+            let mut game_over = false;
+
+            loop {
+                let next_send = pending.front().map(|(at, _)| *at + delay);
+
+                tokio::select! {
+                    _ = tokio::time::sleep_until(next_send.unwrap_or_else(Instant::now)), if next_send.is_some() => {
+                        let (_, event) = pending.pop_front().expect("a pending event");
+                        let is_game_over = matches!(event, ServerEvent::GameOver { .. });
+
+                        if let Err(err) = ws_send_json(&mut tx, &WsMessage::ServerEvent { value: event }).await {
+                            tracing::debug!(%runner_id, %err, "failed to send a spectator the game");
+                            break;
+                        }
 
+                        if is_game_over {
+                            ws_close_normally(&mut tx).await;
+                            break;
+                        }
+                    }
+                    event = events_rx.recv(), if !game_over => {
+                        let now = Instant::now();
+                        match event {
+                            Ok(SpectatorEvent::Update { mutations, state }) => {
+                                pending.push_back((now, ServerEvent::Mutations { mutations }));
+                                pending.push_back((now, ServerEvent::SpectatorState { state }));
 
-                                } else {
-                                    ws_close_with_error(tx, "runner not found".to_string()).await;
-                                    return Ok(());
+                                // the spectator picks up from a whole state, so mutations aren't left
+                                // at the front without the state they apply to
+                                if pending.len() > MAX_PENDING_SPECTATOR_EVENTS {
+                                    while pending.len() > MAX_PENDING_SPECTATOR_EVENTS || matches!(pending.front(), Some((_, ServerEvent::Mutations { .. }))) {
+                                        pending.pop_front();
+                                    }
                                 }
                             }
+                            Ok(SpectatorEvent::GameOver { result, actions, seed_reveal }) => {
+                                pending.push_back((now, ServerEvent::GameOver { result, actions, seed_reveal }));
+                                game_over = true;
+                            }
+
+                            // the next update's state brings the spectator up to date
+                            Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => game_over = true,
                         }
-                        _ => {
-                            ws_close_with_error(tx, "expected migration info response".to_string()).await;
-                            return Ok(());
+                    }
+                    message = rx.next() => {
+                        match message {
+                            Some(Ok(Message::Text(_))) => {
+                                ws_send_err(&mut tx, "spectators can't take actions".to_string()).await.ok();
+                            }
+                            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                            Some(Ok(_)) => {}
                         }
                     }
                 }
-                Err(err) => {
-                    ws_close_with_error(tx, err.to_string()).await;
-                    return Ok(());
+
+                // the runner went away without the game ending
+                if game_over && pending.is_empty() {
+                    ws_close_normally(&mut tx).await;
+                    break;
                 }
             }
 
@@ -70,3 +200,133 @@ pub async fn runner_connect(ws: WebSocket, runners: &State<Arc<RwLock<Vec<algoma
         })
     })
 }
+
+/// Serves a client for the rest of the game: the client is sent the game as its player sees it,
+/// then the mutations of every action taken and the state after it, both as its player sees
+/// them, until the game is over.
+/// Meanwhile the actions the client submits are applied. A reconnecting client is first sent the
+/// mutations it missed while it was away, and if the game ended in the meantime, how it ended.
+async fn play(runners: Arc<RwLock<Vec<Runner>>>, runner_id: RunnerId, client_key: ClientKey, mut tx: TX, mut rx: RX, connection: ClientConnection) {
+    let ClientConnection { player_id, mut events_rx, missed_mutations, game_over } = connection;
+
+    if !missed_mutations.is_empty() {
+        let event = ServerEvent::Mutations { mutations: missed_mutations };
+        if let Err(err) = ws_send_json(&mut tx, &WsMessage::ServerEvent { value: event }).await {
+            tracing::warn!(%runner_id, %err, "failed to send a reconnecting client the mutations it missed");
+        }
+    }
+
+    if let Err(err) = send_game_state(&runners, runner_id, client_key, &mut tx).await {
+        ws_close_with_error(tx, err.to_string()).await;
+        return;
+    }
+
+    if let Some(GameOver { result, actions, seed_reveal }) = game_over {
+        ws_send_json(&mut tx, &WsMessage::ServerEvent { value: ServerEvent::GameOver { result, actions, seed_reveal } }).await.ok();
+        ws_close_normally(&mut tx).await;
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = events_rx.recv() => {
+                let sent = match event {
+                    Ok(RunnerEvent::Mutations(mut mutations)) => {
+                        let mutations = mutations.remove(&player_id).unwrap_or_default();
+                        match ws_send_json(&mut tx, &WsMessage::ServerEvent { value: ServerEvent::Mutations { mutations } }).await {
+                            Ok(_) => send_game_state(&runners, runner_id, client_key, &mut tx).await,
+                            Err(err) => Err(RequestResponseError::ErrorSendingJson(Box::new(err))),
+                        }
+                    }
+                    Ok(RunnerEvent::GameOver(result, seed_reveal)) => {
+                        let actions = match runners.read().await.iter().find(|r| r.runner_id == runner_id) {
+                            Some(runner) => runner.game().action_history.clone(),
+                            None => Vec::new(),
+                        };
+
+                        ws_send_json(&mut tx, &WsMessage::ServerEvent { value: ServerEvent::GameOver { result, actions, seed_reveal } }).await.ok();
+                        ws_close_normally(&mut tx).await;
+                        break;
+                    }
+                    Ok(RunnerEvent::PlayerAway(player_id)) => {
+                        ws_send_json(&mut tx, &WsMessage::ServerEvent { value: ServerEvent::PlayerAway { player_id } }).await
+                            .map_err(|err| RequestResponseError::ErrorSendingJson(Box::new(err)))
+                    }
+                    Ok(RunnerEvent::PlayerReturned(player_id)) => {
+                        ws_send_json(&mut tx, &WsMessage::ServerEvent { value: ServerEvent::PlayerReturned { player_id } }).await
+                            .map_err(|err| RequestResponseError::ErrorSendingJson(Box::new(err)))
+                    }
+                    Ok(RunnerEvent::Started) => Ok(()),
+
+                    // the client missed some mutations, the whole state brings it up to date
+                    Err(RecvError::Lagged(_)) => send_game_state(&runners, runner_id, client_key, &mut tx).await,
+                    Err(RecvError::Closed) => break,
+                };
+
+                if let Err(err) = sent {
+                    tracing::warn!(%runner_id, %err, "failed to send the game to a client");
+                }
+            }
+            message = rx.next() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Err(err) = apply_client_action(&runners, runner_id, client_key, &text).await {
+                            tracing::debug!(%runner_id, %err, "rejected a client's action");
+                            ws_send_err(&mut tx, err.to_string()).await.ok();
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        disconnect(&runners, runner_id, client_key).await;
+                        break;
+                    }
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+/// the client keeps its seat, and can connect again with the same client key
+async fn disconnect(runners: &RwLock<Vec<Runner>>, runner_id: RunnerId, client_key: ClientKey) {
+    if let Some(runner) = runners.write().await.iter_mut().find(|r| r.runner_id == runner_id) {
+        if let Err(err) = runner.disconnect_client(client_key) {
+            tracing::warn!(%runner_id, %err, "failed to disconnect a client");
+        }
+    }
+}
+
+async fn send_game_state(runners: &RwLock<Vec<Runner>>, runner_id: RunnerId, client_key: ClientKey, tx: &mut TX) -> Result<(), RequestResponseError> {
+    let view = {
+        let runners = runners.read().await;
+        let runner = runners.iter().find(|r| r.runner_id == runner_id)
+            .ok_or_else(|| RequestResponseError::InvalidRequest("runner not found".to_string()))?;
+
+        runner.client_view(client_key).map_err(|err| RequestResponseError::InvalidRequest(err.to_string()))?
+    };
+
+    let event = ServerEvent::GameState {
+        player_id: view.player_id,
+        state: view.state,
+        valid_actions: view.valid_actions,
+    };
+
+    ws_send_json(tx, &WsMessage::ServerEvent { value: event }).await
+        .map_err(|err| RequestResponseError::ErrorSendingJson(Box::new(err)))
+}
+
+async fn apply_client_action(runners: &RwLock<Vec<Runner>>, runner_id: RunnerId, client_key: ClientKey, text: &str) -> Result<(), RequestResponseError> {
+    let action = match serde_json::from_str::<WsMessage>(text) {
+        Ok(WsMessage::ClientRequest { value: ClientRequest::ActionRequest { action } }) => action,
+        Ok(_) => return Err(RequestResponseError::InvalidRequest("expected an action request".to_string())),
+        Err(err) => return Err(RequestResponseError::ErrorDeserializingMessage(err)),
+    };
+
+    let mut runners = runners.write().await;
+    let runner = runners.iter_mut().find(|r| r.runner_id == runner_id)
+        .ok_or_else(|| RequestResponseError::InvalidRequest("runner not found".to_string()))?;
+
+    // the mutations reach every client, this one included, through the runner's events
+    runner.apply_client_action(client_key, action)
+        .map(|_| ())
+        .map_err(|err| RequestResponseError::ActionRejected(err.to_string()))
+}
@@ -0,0 +1,101 @@
+//! The json schema and typescript definitions of the public data model, for clients written in
+//! other languages: the games, states, actions and mutations of the rules engine, the cards of the
+//! database and the messages of the server.
+//!
+//! Both are checked in under `schema/` at the root of the repository, and the test fails if they no
+//! longer match the types. After changing the data model, regenerate them with
+//! `UPDATE_SCHEMA=1 cargo test -p http_server schema` and review the diff.
+
+mod typescript;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use schemars::schema::{Metadata, RootSchema, SchemaObject};
+
+use algomancer_gre::game::{Game, GameOptions};
+use algomancer_gre::game::action::Action;
+use algomancer_gre::game::diff::StateDiff;
+use algomancer_gre::game::event::Event;
+use algomancer_gre::game::game_builder::scenario::Scenario;
+use algomancer_gre::game::state::mutation::StaticStateMutation;
+use algomancer_gre::game::state::State;
+use crate::messages::WsMessage;
+use crate::models::{LobbyModel, LobbyOptionsRequest, RegistrationRequest, RegistrationResponse, RunningGameModel};
+
+const SCHEMA_FILE: &str = "algomancer.schema.json";
+const TYPESCRIPT_FILE: &str = "algomancer.d.ts";
+
+fn schema_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("schema")
+}
+
+/// adds a type, and every type it's made of, to the definitions
+fn define<T: JsonSchema>(gen: &mut SchemaGenerator) {
+    gen.subschema_for::<T>();
+}
+
+/// A schema with a definition for every type of the data model, but no root type of its own.
+pub fn data_model_schema() -> RootSchema {
+    let mut gen = SchemaSettings::draft07().into_generator();
+
+    // the engine, along with the cards database and its types from algocore
+    define::<Game>(&mut gen);
+    define::<GameOptions>(&mut gen);
+    define::<State>(&mut gen);
+    define::<Action>(&mut gen);
+    define::<StaticStateMutation>(&mut gen);
+    define::<Event>(&mut gen);
+    define::<StateDiff>(&mut gen);
+    define::<Scenario>(&mut gen);
+
+    // the server
+    define::<WsMessage>(&mut gen);
+    define::<RegistrationRequest>(&mut gen);
+    define::<RegistrationResponse>(&mut gen);
+    define::<LobbyModel>(&mut gen);
+    define::<LobbyOptionsRequest>(&mut gen);
+    define::<RunningGameModel>(&mut gen);
+
+    RootSchema {
+        meta_schema: gen.settings().meta_schema.clone(),
+        schema: SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                title: Some("Algomancer".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+        definitions: gen.take_definitions(),
+    }
+}
+
+#[test]
+fn test_schema_is_up_to_date() {
+    let schema = data_model_schema();
+    let generated = [
+        (SCHEMA_FILE, serde_json::to_string_pretty(&schema).unwrap() + "\n"),
+        (TYPESCRIPT_FILE, typescript::to_typescript(&schema)),
+    ];
+
+    if std::env::var_os("UPDATE_SCHEMA").is_some() {
+        fs::create_dir_all(schema_dir()).unwrap();
+        for (file, contents) in &generated {
+            fs::write(schema_dir().join(file), contents).unwrap();
+        }
+        return;
+    }
+
+    let stale: Vec<&str> = generated.iter()
+        .filter(|(file, contents)| fs::read_to_string(schema_dir().join(file)).ok().as_ref() != Some(contents))
+        .map(|(file, _)| *file)
+        .collect();
+
+    assert!(
+        stale.is_empty(),
+        "the checked in schema is out of date ({}), regenerate it with UPDATE_SCHEMA=1 cargo test -p http_server schema",
+        stale.join(", ")
+    );
+}
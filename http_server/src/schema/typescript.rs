@@ -0,0 +1,200 @@
+//! Typescript definitions generated from the json schema, a type for each of its definitions.
+//! Only the parts of json schema which schemars generates for the data model are supported.
+
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+
+const HEADER: &str = "// generated from algomancer.schema.json, don't edit by hand\n\n";
+const INDENT: &str = "    ";
+
+/// a typescript type, and what it has to be wrapped in to be part of a bigger one
+struct Type {
+    text: String,
+    precedence: Precedence,
+}
+
+/// how tightly a type binds, unions and intersections need parentheses inside arrays and each other
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    Union,
+    Intersection,
+    Single,
+}
+
+impl Type {
+    fn single(text: impl Into<String>) -> Type {
+        Type { text: text.into(), precedence: Precedence::Single }
+    }
+
+    /// the type as written within a type which binds at least as tightly as the given precedence
+    fn within(&self, precedence: Precedence) -> String {
+        if self.precedence < precedence {
+            format!("({})", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    fn combine(types: Vec<Type>, precedence: Precedence, separator: &str) -> Type {
+        if types.len() == 1 {
+            return types.into_iter().next().unwrap();
+        }
+
+        let mut texts: Vec<String> = Vec::new();
+        for t in &types {
+            let text = t.within(precedence);
+            if !texts.contains(&text) {
+                texts.push(text);
+            }
+        }
+
+        Type { text: texts.join(separator), precedence }
+    }
+
+    fn union(types: Vec<Type>) -> Type {
+        Type::combine(types, Precedence::Union, " | ")
+    }
+
+    fn intersection(types: Vec<Type>) -> Type {
+        Type::combine(types, Precedence::Intersection, " & ")
+    }
+}
+
+pub fn to_typescript(root: &RootSchema) -> String {
+    let mut ts = HEADER.to_string();
+
+    for (name, schema) in &root.definitions {
+        if let Schema::Object(object) = schema {
+            ts += &doc_comment(object, "");
+        }
+
+        ts += &format!("export type {} = {};\n\n", name, type_of(schema, 0).text);
+    }
+
+    ts.truncate(ts.trim_end().len());
+    ts + "\n"
+}
+
+fn type_of(schema: &Schema, depth: usize) -> Type {
+    match schema {
+        Schema::Bool(true) => Type::single("unknown"),
+        Schema::Bool(false) => Type::single("never"),
+        Schema::Object(object) => object_type_of(object, depth),
+    }
+}
+
+fn object_type_of(schema: &SchemaObject, depth: usize) -> Type {
+    if let Some(reference) = &schema.reference {
+        return Type::single(reference.trim_start_matches("#/definitions/"));
+    }
+
+    if let Some(value) = &schema.const_value {
+        return Type::single(value.to_string());
+    }
+
+    if let Some(values) = &schema.enum_values {
+        return Type::union(values.iter().map(|v| Type::single(v.to_string())).collect());
+    }
+
+    let mut parts = Vec::new();
+
+    if let Some(instance_types) = &schema.instance_type {
+        let instance_types = match instance_types {
+            SingleOrVec::Single(instance_type) => vec![**instance_type],
+            SingleOrVec::Vec(instance_types) => instance_types.clone(),
+        };
+
+        parts.push(Type::union(instance_types.into_iter().map(|t| instance_type_of(schema, t, depth)).collect()));
+    }
+
+    if let Some(subschemas) = &schema.subschemas {
+        if let Some(all_of) = &subschemas.all_of {
+            parts.extend(all_of.iter().map(|s| type_of(s, depth)));
+        }
+
+        for one_of in [&subschemas.one_of, &subschemas.any_of].into_iter().flatten() {
+            parts.push(Type::union(one_of.iter().map(|s| type_of(s, depth)).collect()));
+        }
+    }
+
+    if parts.is_empty() {
+        return Type::single("unknown");
+    }
+
+    Type::intersection(parts)
+}
+
+fn instance_type_of(schema: &SchemaObject, instance_type: InstanceType, depth: usize) -> Type {
+    match instance_type {
+        InstanceType::Null => Type::single("null"),
+        InstanceType::Boolean => Type::single("boolean"),
+        InstanceType::Integer | InstanceType::Number => Type::single("number"),
+        InstanceType::String => Type::single("string"),
+        InstanceType::Array => {
+            let items = schema.array.as_ref().and_then(|a| a.items.as_ref());
+            match items {
+                Some(SingleOrVec::Single(item)) => Type::single(format!("{}[]", type_of(item, depth).within(Precedence::Single))),
+                Some(SingleOrVec::Vec(items)) => {
+                    let items: Vec<String> = items.iter().map(|s| type_of(s, depth).text).collect();
+                    Type::single(format!("[{}]", items.join(", ")))
+                }
+                None => Type::single("unknown[]"),
+            }
+        }
+        InstanceType::Object => object_literal_of(schema, depth),
+    }
+}
+
+fn object_literal_of(schema: &SchemaObject, depth: usize) -> Type {
+    let Some(object) = &schema.object else {
+        return Type::single("{ [key: string]: unknown }");
+    };
+
+    if object.properties.is_empty() {
+        let values = object.additional_properties.as_ref()
+            .map(|s| type_of(s, depth).text)
+            .unwrap_or_else(|| "unknown".to_string());
+
+        return Type::single(format!("{{ [key: string]: {} }}", values));
+    }
+
+    let indent = INDENT.repeat(depth + 1);
+    let mut ts = "{\n".to_string();
+    for (name, property) in &object.properties {
+        if let Schema::Object(property) = property {
+            ts += &doc_comment(property, &indent);
+        }
+
+        let optional = if object.required.contains(name) { "" } else { "?" };
+        ts += &format!("{}{}{}: {};\n", indent, property_name(name), optional, type_of(property, depth + 1).text);
+    }
+    ts += &INDENT.repeat(depth);
+    ts += "}";
+
+    Type::single(ts)
+}
+
+/// the name as it can be written in an object literal, quoted unless it's an identifier
+fn property_name(name: &str) -> String {
+    let is_identifier = name.chars().enumerate().all(|(i, c)| c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+
+    if is_identifier && !name.is_empty() {
+        name.to_string()
+    } else {
+        serde_json::to_string(name).unwrap()
+    }
+}
+
+/// the schema's description as a doc comment, if it has one
+fn doc_comment(schema: &SchemaObject, indent: &str) -> String {
+    let Some(description) = schema.metadata.as_ref().and_then(|m| m.description.as_ref()) else {
+        return String::new();
+    };
+
+    let mut ts = format!("{}/**\n", indent);
+    for line in description.lines() {
+        ts += &format!("{} * {}\n", indent, line).replace(" * \n", " *\n");
+    }
+    ts += &format!("{} */\n", indent);
+
+    ts
+}
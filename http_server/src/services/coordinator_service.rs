@@ -1,16 +1,24 @@
-use algomanserver::Coordinator;
+use algomanserver::{AgentKey, Coordinator, LobbyId, LobbySettingsUpdate, PendingLobbyEvent, Runner};
 use crate::error::Error;
 use crate::models;
-use crate::models::LobbyModel;
+use crate::models::{LobbyModel, LobbyOptionsRequest, RunningGameModel};
 
 pub fn lobbies(coordinator: &Coordinator) -> Vec<LobbyModel> {
     coordinator.lobbies().map(|l| LobbyModel::from_coordinator_lobby(coordinator, l)).collect()
 }
 
+/// the games which have started and aren't over yet, which can be spectated
+pub fn running_games(coordinator: &Coordinator, runners: &[Runner]) -> Vec<RunningGameModel> {
+    runners.iter()
+        .filter(|r| r.is_spectatable())
+        .map(|r| RunningGameModel::from_runner(coordinator, r))
+        .collect()
+}
+
 pub async fn register(coordinator: &mut Coordinator, username: &str) -> Result<models::RegistrationResponse, Error> {
     let (agent_id, agent_key) = coordinator.create_new_agent(username).await?;
 
-    println!("registered agent {agent_key}");
+    tracing::info!(%agent_id, "registered agent");
 
     let model = models::RegistrationResponse {
         agent_id,
@@ -18,4 +26,21 @@ pub async fn register(coordinator: &mut Coordinator, username: &str) -> Result<m
     };
 
     Ok(model)
-}
\ No newline at end of file
+}
+
+/// Applies the changes to the lobby's settings, all of them or none. The lobby's agents are told of
+/// them by the returned event, which is to be sent once the coordinator has been released.
+pub fn update_lobby_options(coordinator: &mut Coordinator, lobby_id: LobbyId, request: LobbyOptionsRequest) -> Result<(LobbyModel, PendingLobbyEvent), Error> {
+    let agent_key: AgentKey = request.agent_key.parse()
+        .map_err(|_| Error::AgentDoesNotExistWithKey("failed to parse agent key".to_string()))?;
+
+    let update = LobbySettingsUpdate {
+        game_mode: request.game_mode,
+        capacity: request.capacity,
+        seating: request.seating,
+    };
+    let event = coordinator.update_settings(agent_key, lobby_id, update)?;
+
+    let lobby = coordinator.try_get_lobby(lobby_id)?;
+    Ok((LobbyModel::from_coordinator_lobby(coordinator, lobby), event))
+}
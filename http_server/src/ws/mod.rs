@@ -11,13 +11,14 @@ use tokio::sync::RwLock;
 use tokio::time::timeout;
 use ws::frame::CloseFrame;
 use ws::Message;
-use algomanserver::{AgentKey, Coordinator, LobbyEvent, LobbyId, Runner};
+use algomanserver::{AgentKey, Coordinator, LobbyEvent, LobbyId, PendingLobbyEvent, Runner};
 use crate::messages::{ServerEvent, WsMessage, ServerRequest, ClientResponse, ClientRequest};
 use crate::models::{AgentModel, LobbyModel, MigrationInfoModel};
+use crate::services;
 
 
-type TX = SplitSink<ws::stream::DuplexStream, Message>;
-type RX = SplitStream<ws::stream::DuplexStream>;
+pub type TX = SplitSink<ws::stream::DuplexStream, Message>;
+pub type RX = SplitStream<ws::stream::DuplexStream>;
 
 
 fn default_timeout_duration() -> Duration {
@@ -74,7 +75,7 @@ pub async fn ws_send_json<T: Serialize>(tx: &mut TX, var: &T) -> Result<(), Send
         }
     };
 
-    println!("sending json: {json}");
+    tracing::trace!(%json, "sending json");
 
     if let Err(err) = ws_send_text(tx, json.as_str()).await {
         return Err(SendJsonError::WebsocketError(err));
@@ -84,11 +85,12 @@ pub async fn ws_send_json<T: Serialize>(tx: &mut TX, var: &T) -> Result<(), Send
 }
 
 pub enum RequestResponseError {
-    ErrorSendingJson(SendJsonError),
+    ErrorSendingJson(Box<SendJsonError>),
     ErrorDeserializingMessage(serde_json::Error),
     InvalidResponse(String),
     InvalidRequest(String),
     ErrorStartingGame(String),
+    ActionRejected(String),
     ConnectionClosed,
     MessageNotText,
     Timeout,
@@ -104,6 +106,7 @@ impl Display for RequestResponseError {
             RequestResponseError::MessageNotText => write!(f, "received message was not a text frame"),
             RequestResponseError::InvalidRequest(reason) => write!(f, "received an invalid request: {reason}"),
             RequestResponseError::ErrorStartingGame(reason) => write!(f, "error starting game: {reason}"),
+            RequestResponseError::ActionRejected(reason) => write!(f, "action rejected: {reason}"),
             RequestResponseError::Timeout => write!(f, "client did not send response within the allotted timeframe"),
         }
     }
@@ -120,12 +123,12 @@ pub async fn ws_request_response_within(tx: &mut TX, rx: &mut RX, request_varian
 
 pub async fn ws_request_response(tx: &mut TX, rx: &mut RX, request_variant: ServerRequest) -> Result<ClientResponse, RequestResponseError> {
     if let Err(err) = ws_send_json(tx, &WsMessage::ServerRequest { value: request_variant.clone() }).await {
-        return Err(RequestResponseError::ErrorSendingJson(err));
+        return Err(RequestResponseError::ErrorSendingJson(Box::new(err)));
     }
 
     while let Some(message) = rx.next().await {
         if let Ok(message) = message {
-            println!("received: {message}");
+            tracing::trace!(%message, "received");
             let result = match message {
                 Message::Text(text) => {
                     match serde_json::from_str::<WsMessage>(&text) {
@@ -169,7 +172,7 @@ pub async fn ws_close_normally(tx: &mut TX) {
 }
 
 pub async fn ws_close_with_error(mut tx: TX, err_msg: String) {
-    eprintln!("{err_msg}");
+    tracing::warn!(%err_msg, "closing websocket with an error");
     tx.send(Message::Text(err_msg.to_string())).await.ok();
     tx.send(Message::Close(Some(CloseFrame {
         code: ws::frame::CloseCode::Normal,
@@ -178,7 +181,9 @@ pub async fn ws_close_with_error(mut tx: TX, err_msg: String) {
 }
 
 
-async fn respond_to_client_request(text: &str, _tx: &mut TX, runners: &mut Vec<Runner>, coordinator: &mut Coordinator) -> Result<(), RequestResponseError> {
+/// Responds to a request made in a lobby. What the request has the lobby's agents told is returned
+/// rather than sent, to be sent once the coordinator has been released (see [PendingLobbyEvent]).
+async fn respond_to_client_request(text: &str, _tx: &mut TX, runners: &mut Vec<Runner>, coordinator: &mut Coordinator) -> Result<Option<PendingLobbyEvent>, RequestResponseError> {
     let request = match serde_json::from_str::<WsMessage>(text) {
         Ok(value) => {
             match value {
@@ -192,7 +197,7 @@ async fn respond_to_client_request(text: &str, _tx: &mut TX, runners: &mut Vec<R
     };
 
     match request {
-        ClientRequest::StartGameRequest { agent_key, lobby_id } => {
+        ClientRequest::StartGameRequest { agent_key, lobby_id, saved_game } => {
             let agent_key: AgentKey = match agent_key.parse::<u64>() {
                 Ok(agent_key) => agent_key.into(),
                 Err(_) => return Err(RequestResponseError::InvalidRequest("failed to parse agent key".to_string()))
@@ -203,14 +208,50 @@ async fn respond_to_client_request(text: &str, _tx: &mut TX, runners: &mut Vec<R
                 Err(_) => return Err(RequestResponseError::InvalidRequest("failed to parse lobby id".to_string()))
             };
 
-            match coordinator.start_game(agent_key, lobby_id).await {
+            let runner = match saved_game {
+                Some(saved_game) => coordinator.resume_game(agent_key, lobby_id, &saved_game).await,
+                None => coordinator.start_game(agent_key, lobby_id).await,
+            };
+
+            match runner {
                 Ok(runner) => {
                     runners.push(runner)
                 }
-                Err(err) => return Err(RequestResponseError::ErrorStartingGame(format!("{:?}", err)))
+                // e.g. the saved game is newer than this server supports
+                Err(err) => return Err(RequestResponseError::ErrorStartingGame(err.to_string()))
             }
 
-            Ok(())
+            Ok(None)
+        }
+        ClientRequest::ContributeEntropyRequest { agent_key, lobby_id, entropy } => {
+            let agent_key: AgentKey = match agent_key.parse::<u64>() {
+                Ok(agent_key) => agent_key.into(),
+                Err(_) => return Err(RequestResponseError::InvalidRequest("failed to parse agent key".to_string()))
+            };
+
+            let lobby_id: LobbyId = match lobby_id.parse::<u64>() {
+                Ok(lobby_id) => lobby_id.into(),
+                Err(_) => return Err(RequestResponseError::InvalidRequest("failed to parse lobby id".to_string()))
+            };
+
+            coordinator.contribute_entropy(agent_key, lobby_id, entropy)
+                .map(|_| None)
+                .map_err(|err| RequestResponseError::InvalidRequest(err.to_string()))
+        }
+        ClientRequest::UpdateLobbyRequest { lobby_id, options } => {
+            let lobby_id: LobbyId = match lobby_id.parse::<u64>() {
+                Ok(lobby_id) => lobby_id.into(),
+                Err(_) => return Err(RequestResponseError::InvalidRequest("failed to parse lobby id".to_string()))
+            };
+
+            // everyone in the lobby, the host included, is sent the new settings as a lobby event
+            match services::coordinator_service::update_lobby_options(coordinator, lobby_id, options) {
+                Ok((_, event)) => Ok(Some(event)),
+                Err(err) => Err(RequestResponseError::InvalidRequest(format!("{:?}", err)))
+            }
+        }
+        ClientRequest::ActionRequest { .. } => {
+            Err(RequestResponseError::InvalidRequest("actions are sent to the game's runner, not the lobby".to_string()))
         }
     }
 }
@@ -250,15 +291,22 @@ pub async fn ws_lobby_listen(
                                 runner_id: migration_info.runner_id.to_string(),
                                 agent_key: migration_info.agent_key.to_string(),
                                 client_key: migration_info.client_key.to_string(),
+                                player_id: migration_info.player_id,
+                                seed_commitment: migration_info.seed_commitment,
+                                seed_inputs_commitment: migration_info.seed_inputs_commitment,
                             },
                         },
                         LobbyEvent::AgentJoined(_agent_id) => {
                             let coordinator = coordinator.read().await;
-                            let agent: AgentModel = coordinator.get_agent_by_key(agent_key).expect("an agent").into();
-                            let lobby = LobbyModel::from_coordinator_lobby(coordinator.deref(), coordinator.try_get_lobby(lobby_id).expect("a lobby"));
+
+                            // the agent or the lobby may have gone by now, leaving nothing to tell
+                            let (Ok(agent), Ok(lobby)) = (coordinator.get_agent_by_key(agent_key), coordinator.try_get_lobby(lobby_id)) else {
+                                continue;
+                            };
+
                             ServerEvent::AgentJoinedLobby {
-                                agent,
-                                lobby,
+                                agent: AgentModel::from(agent),
+                                lobby: LobbyModel::from_coordinator_lobby(coordinator.deref(), lobby),
                             }
                         }
                         LobbyEvent::AgentLeft(agent_id) => ServerEvent::AgentLeftLobby {
@@ -267,6 +315,18 @@ pub async fn ws_lobby_listen(
                         LobbyEvent::NewHost(agent_id) => ServerEvent::AgentLeftLobby {
                             agent_id: agent_id.to_string(),
                         },
+                        LobbyEvent::SettingsChanged => {
+                            let coordinator = coordinator.read().await;
+
+                            // the lobby may have closed by now, leaving nothing to tell
+                            let Ok(lobby) = coordinator.try_get_lobby(lobby_id) else {
+                                continue;
+                            };
+
+                            ServerEvent::LobbySettingsChanged {
+                                lobby: LobbyModel::from_coordinator_lobby(coordinator.deref(), lobby),
+                            }
+                        }
                         LobbyEvent::Whisper(_, _, _) => unimplemented!(),
                     };
 
@@ -274,7 +334,7 @@ pub async fn ws_lobby_listen(
                     match ws_send_json(tx.lock().await.deref_mut(), &WsMessage::ServerEvent { value: event }).await {
                         Ok(_) => {}
                         Err(err) => {
-                            eprintln!("{err}")
+                            tracing::error!(%err, "failed to send a lobby event")
                         }
                     }
                 }
@@ -287,14 +347,25 @@ pub async fn ws_lobby_listen(
             if let Ok(message) = message {
                 match message {
                     Message::Text(text) => {
-                        let mut coordinator = coordinator.write().await;
-                        let mut runners = runners.write().await;
-                        let mut tx = tx.lock().await;
-                        match respond_to_client_request(text.as_str(), tx.deref_mut(), runners.deref_mut(), coordinator.deref_mut()).await {
-                            Ok(_) => {}
-                            Err(err) => {
-                                eprintln!("{err}");
-                                ws_send_err(tx.deref_mut(), err.to_string()).await.ok();
+                        let event = {
+                            let mut coordinator = coordinator.write().await;
+                            let mut runners = runners.write().await;
+                            let mut tx = tx.lock().await;
+                            match respond_to_client_request(text.as_str(), tx.deref_mut(), runners.deref_mut(), coordinator.deref_mut()).await {
+                                Ok(event) => event,
+                                Err(err) => {
+                                    tracing::warn!(%err, "failed to respond to a client request");
+                                    ws_send_err(tx.deref_mut(), err.to_string()).await.ok();
+                                    None
+                                }
+                            }
+                        };
+
+                        // this listener's own send task takes the coordinator to make the event's
+                        // message, so it's only sent once the coordinator has been released
+                        if let Some(event) = event {
+                            if let Err(err) = event.send().await {
+                                tracing::warn!(%err, "failed to send a lobby event");
                             }
                         }
                     }
@@ -0,0 +1,993 @@
+// generated from algomancer.schema.json, don't edit by hand
+
+/**
+ * What a triggered ability does when it resolves, for the abilities the engine understands.
+ */
+export type AbilityEffect = {
+    amount: number;
+    token_std_name: string;
+    type: "CreateToken";
+} | {
+    type: "SacrificeMe";
+} | {
+    amount: number;
+    type: "DamageEachOpponent";
+};
+
+export type Action = {
+    issuer_player_id: PlayerId;
+} & ({
+    type: "PassPriority";
+} | {
+    cards_to_keep: CardId[];
+    type: "Draft";
+} | {
+    card_id: CardId;
+    resource_type: ResourceType;
+    type: "RecycleForResource";
+} | {
+    card_id: CardId;
+    type: "PlayCard";
+} | {
+    formation: Formation_for_PermanentId;
+    home_region_id: RegionId;
+    type: "Attack";
+    under_attack_region_id: RegionId;
+} | {
+    card_id: CardId;
+    host_permanent_id: PermanentId;
+    type: "ApplyMod";
+} | {
+    permanent_id: PermanentId;
+    type: "CastSpellToken";
+} | {
+    type: "Concede";
+});
+
+export type Affinity = {
+    faction: Faction;
+    quantity: number;
+};
+
+export type AgentId = number;
+
+export type AgentModel = {
+    id: string;
+    username: string;
+};
+
+export type AlgomancerRng = {
+    state: number;
+};
+
+export type Card = {
+    card_id: CardId;
+    prototype_id: CardPrototypeId;
+};
+
+export type CardCollectionId = string;
+
+export type CardId = number;
+
+export type CardPrototype = {
+    card_type: CardType;
+    costs: Cost;
+    mod_type?: ModType | null;
+    name: string;
+    /**
+     * the printed power of a unit, 0 for other cards and for a variable power like 'X'
+     */
+    power?: number;
+    prototype_id: CardPrototypeId;
+    std_name: string;
+    text: string;
+    triggers?: TriggeredAbility[];
+};
+
+export type CardPrototypeDatabase = {
+    prototypes: { [key: string]: CardPrototype };
+};
+
+export type CardPrototypeId = number;
+
+export type CardType = "UnitToken" | "SpellToken" | {
+    Resource: ResourceType;
+} | {
+    Unit: Timing;
+} | {
+    Spell: Timing;
+} | {
+    Meta: MetaCardType;
+};
+
+export type ClientRequest = {
+    agent_key: string;
+    lobby_id: string;
+    /**
+     * a saved game (a serialized game) to resume rather than starting a new one
+     */
+    saved_game?: string | null;
+    type: "start_game_request";
+} | {
+    agent_key: string;
+    entropy: number[];
+    lobby_id: string;
+    type: "contribute_entropy_request";
+} | {
+    lobby_id: string;
+    options: LobbyOptionsRequest;
+    type: "update_lobby_request";
+} | {
+    action: Action;
+    type: "action_request";
+};
+
+export type ClientResponse = {
+    agent_key: string;
+    type: "agent_key_response";
+} | {
+    info: MigrationInfoModel;
+    type: "migration_info_response";
+};
+
+/**
+ * Entropy an agent contributed to a game's seed, so that each player can find their own in the reveal.
+ */
+export type Contribution = {
+    /**
+     * the id of the agent on the server that ran the game
+     */
+    agent_id: number;
+    entropy: number[];
+};
+
+export type Cost = {
+    Standard: {
+        cost: number;
+        threshold: Affinity[];
+    };
+} | {
+    X: {
+        threshold: Affinity[];
+    };
+};
+
+/**
+ * What damage is dealt to.
+ */
+export type DamageTarget = {
+    Player: PlayerId;
+} | {
+    Permanent: PermanentId;
+};
+
+/**
+ * An ordered pile of cards. The cards are shared between clones of the deck until one of them changes, so cloning a state doesn't copy every deck in it.
+ */
+export type Deck = {
+    cards: Card[];
+    id: CardCollectionId;
+};
+
+export type DefensiveFormation_for_Permanent = {
+    attacking_formation_id: FormationId;
+    formation: Formation_for_Permanent;
+};
+
+/**
+ * One way two states differ, see [State::diff].
+ */
+export type Difference = {
+    from: number;
+    to: number;
+    type: "Depth";
+} | {
+    from: number;
+    to: number;
+    type: "Turn";
+} | {
+    from: PlayerId;
+    to: PlayerId;
+    type: "InitiativePlayer";
+} | {
+    from?: GameResult | null;
+    to?: GameResult | null;
+    type: "Result";
+} | {
+    type: "Rng";
+} | {
+    from: number;
+    name: string;
+    to: number;
+    type: "IdFactory";
+} | {
+    region_id: RegionId;
+    type: "RegionAdded";
+} | {
+    region_id: RegionId;
+    type: "RegionRemoved";
+} | {
+    from: Phase;
+    region_id: RegionId;
+    to: Phase;
+    type: "Step";
+} | {
+    from: PlayerId[];
+    region_id: RegionId;
+    to: PlayerId[];
+    type: "Priority";
+} | {
+    added: Effect[];
+    region_id: RegionId;
+    removed: Effect[];
+    type: "Stack";
+} | {
+    region_id: RegionId;
+    type: "Formation";
+} | {
+    permanent: Permanent;
+    region_id: RegionId;
+    type: "PermanentAdded";
+} | {
+    permanent: Permanent;
+    region_id: RegionId;
+    type: "PermanentRemoved";
+} | {
+    from: Permanent;
+    region_id: RegionId;
+    to: Permanent;
+    type: "PermanentChanged";
+} | {
+    player_id: PlayerId;
+    type: "PlayerAdded";
+} | {
+    player_id: PlayerId;
+    type: "PlayerRemoved";
+} | {
+    from: number;
+    player_id: PlayerId;
+    to: number;
+    type: "Health";
+} | {
+    from: boolean;
+    player_id: PlayerId;
+    to: boolean;
+    type: "Alive";
+} | {
+    from: TurnRecord;
+    player_id: PlayerId;
+    to: TurnRecord;
+    type: "TurnRecord";
+} | {
+    player_id: PlayerId;
+    type: "PackCreated";
+} | {
+    player_id: PlayerId;
+    type: "PackRemoved";
+} | {
+    card: Card;
+    from: CardCollectionId;
+    to: CardCollectionId;
+    to_index?: number | null;
+    type: "CardMoved";
+} | {
+    card: Card;
+    type: "CardAdded";
+    zone: CardCollectionId;
+} | {
+    card: Card;
+    type: "CardRemoved";
+    zone: CardCollectionId;
+} | {
+    type: "DeckReordered";
+    zone: CardCollectionId;
+};
+
+/**
+ * An effect waiting on the stack to be resolved, created when a permanent's triggered ability matched an event.
+ */
+export type Effect = {
+    ability: TriggeredAbility;
+    controller_player_id: PlayerId;
+    source_permanent_id: PermanentId;
+};
+
+/**
+ * Events are the things that happened as a result of a mutation, which cards can react to. They mirror the triggers named by the card grammar, e.g. 'when I die' or 'after combat'.
+ */
+export type Event = {
+    permanent: Permanent;
+    region_id: RegionId;
+    type: "UnitSpawned";
+} | {
+    permanent: Permanent;
+    region_id: RegionId;
+    type: "UnitDied";
+} | {
+    amount: number;
+    source: PermanentId;
+    target: DamageTarget;
+    type: "DamageDealt";
+} | {
+    amount: number;
+    player_id: PlayerId;
+    type: "PlayerLostLife";
+} | {
+    phase: Phase;
+    region_id: RegionId;
+    type: "PhaseEntered";
+} | {
+    host_permanent_id: PermanentId;
+    mod_card_id: CardId;
+    type: "Modded";
+};
+
+export type Faction = "Fire" | "Earth" | "Water" | "Metal" | "Wood";
+
+export type FormationId = number;
+
+export type Formation_for_Permanent = {
+    bot_row: (Permanent | null)[];
+    committed: boolean;
+    formation_id: FormationId;
+    owner_player_id: PlayerId;
+    padding_cells_enabled: boolean;
+    top_row: (Permanent | null)[];
+};
+
+export type Formation_for_PermanentId = {
+    bot_row: (PermanentId | null)[];
+    committed: boolean;
+    formation_id: FormationId;
+    owner_player_id: PlayerId;
+    padding_cells_enabled: boolean;
+    top_row: (PermanentId | null)[];
+};
+
+export type Game = {
+    action_history: Action[];
+    cards_db: CardPrototypeDatabase;
+    state: State;
+};
+
+export type GameMode = {
+    LiveDraft: {
+        selected_deck_types: Faction[];
+        team_configuration: TeamConfiguration;
+    };
+} | {
+    PreDraft: {
+        team_configuration: TeamConfiguration;
+    };
+} | {
+    TeamDraft: {
+        team_configuration: TeamConfiguration;
+    };
+} | {
+    Constructed: {
+        team_configuration: TeamConfiguration;
+    };
+};
+
+export type GameOptions = {
+    game_mode: GameMode;
+    seed: number[];
+};
+
+export type GameOverReason = "Health" | "Concession" | "DeckOut" | "Timeout";
+
+/**
+ * The outcome of a finished game, recorded in the state once the game ends.
+ */
+export type GameResult = {
+    depth: number;
+    reason: GameOverReason;
+    /**
+     * the turn and depth of the state when the game ended
+     */
+    turn: number;
+    /**
+     * the teams that won the game, empty if the game was a draw
+     */
+    winning_team_ids: TeamId[];
+};
+
+export type IdFactory = number;
+
+export type LobbyId = number;
+
+export type LobbyModel = {
+    agents: AgentModel[];
+    capacity: number;
+    game_mode: GameMode;
+    id: LobbyId;
+    mode: string;
+    name: string;
+    seating: Seating;
+    /**
+     * the hash of the server's part of the game's seed, revealed once the game is over
+     */
+    seed_commitment: SeedCommitment;
+};
+
+/**
+ * Changes to a lobby's settings, which only its host can make. What's left out stays as it is, and the game mode is applied before the capacity, as changing it resets it. Either every change is made or, if any of them is invalid, none is.
+ */
+export type LobbyOptionsRequest = {
+    agent_key: string;
+    capacity?: number | null;
+    game_mode?: GameMode | null;
+    seating?: Seating | null;
+};
+
+export type MetaCardType = "Trigger" | "StolenCard";
+
+export type MigrationInfoModel = {
+    agent_key: string;
+    client_key: string;
+    /**
+     * the player the agent plays as once the game starts
+     */
+    player_id: PlayerId;
+    runner_id: string;
+    /**
+     * the hash of the server's part of the game's seed, revealed once the game is over
+     */
+    seed_commitment?: SeedCommitment | null;
+    /**
+     * the hash of everything the game's seed is made from, including each agent's contribution
+     */
+    seed_inputs_commitment?: SeedCommitment | null;
+};
+
+/**
+ * Describes how a card can be attached to a unit as a 'mod' instead of being played normally.
+ */
+export type ModType = {
+    once: boolean;
+    type: "Augment";
+} | {
+    limit?: number | null;
+    type: "Graft";
+};
+
+/**
+ * Something in play. Tokens only exist as permanents, they cease to exist when they leave play rather than going to a discard pile.
+ */
+export type Permanent = {
+    card: Card;
+    common: PermanentCommon;
+    /**
+     * cards attached to this unit as augments or grafts
+     */
+    mods: Card[];
+    type: "Unit";
+} | {
+    card_prototype_id: CardPrototypeId;
+    common: PermanentCommon;
+    tapped: boolean;
+    type: "Resource";
+} | {
+    card_prototype_id: CardPrototypeId;
+    common: PermanentCommon;
+    type: "SpellToken";
+} | {
+    card_prototype_id: CardPrototypeId;
+    common: PermanentCommon;
+    type: "UnitToken";
+};
+
+export type PermanentCommon = {
+    controller_player_id: PlayerId;
+    permanent_id: PermanentId;
+};
+
+export type PermanentId = number;
+
+export type Phase = {
+    phase: "PlanningPhase";
+} & ({
+    step: "Refresh";
+} | {
+    step: "Draw";
+} | {
+    step: "Draft";
+} | {
+    step: "PassPack";
+} | {
+    step: "Mana";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+}) | {
+    step: "Haste";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+})) | {
+    phase: "BattlePhaseA";
+} & ({
+    step: "Attack";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+}) | {
+    step: "AfterAttackPriorityWindow";
+} | {
+    step: "Block";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+}) | {
+    step: "AfterBlockPriorityWindow";
+} | {
+    step: "Damage";
+} | {
+    step: "AfterCombatPriorityWindow";
+}) | {
+    phase: "BattlePhaseB";
+} & ({
+    step: "Attack";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+}) | {
+    step: "AfterAttackPriorityWindow";
+} | {
+    step: "Block";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+}) | {
+    step: "AfterBlockPriorityWindow";
+} | {
+    step: "Damage";
+} | {
+    step: "AfterCombatPriorityWindow";
+}) | {
+    phase: "DeploymentPhase";
+} & ({
+    step: "Regroup";
+} | {
+    step: "Deployment";
+} & ({
+    team: "IT";
+} | {
+    team: "NIT";
+}));
+
+export type Placement = "OnTop" | "OnBottom" | {
+    ToIndex: number;
+};
+
+export type Player = {
+    discard: UnorderedCards;
+    hand: UnorderedCards;
+    health: number;
+    id: PlayerId;
+    is_alive: boolean;
+    own_deck?: Deck | null;
+    pack?: UnorderedCards | null;
+    team_id: TeamId;
+    this_turn: TurnRecord;
+};
+
+export type PlayerId = number;
+
+export type Region = {
+    attacking_formation?: Formation_for_Permanent | null;
+    defending_formation?: DefensiveFormation_for_Permanent | null;
+    id: RegionId;
+    owner_player_id: PlayerId;
+    players: Player[];
+    stack: Stack;
+    step: Phase;
+    unformed_permanents: Permanent[];
+};
+
+export type RegionId = number;
+
+export type RegistrationRequest = {
+    username: string;
+};
+
+export type RegistrationResponse = {
+    agent_id: AgentId;
+    agent_key: string;
+};
+
+export type ResourceType = "Fire" | "Earth" | "Water" | "Metal" | "Wood" | "Shard" | "Prismite" | "Dormant";
+
+export type RunnerId = number;
+
+/**
+ * A game being played, which spectators can watch through the runner's spectate endpoint.
+ */
+export type RunningGameModel = {
+    mode: string;
+    runner_id: RunnerId;
+    seats: SeatModel[];
+    turn: number;
+};
+
+/**
+ * A description of a specific board state, written by hand (usually as JSON or TOML) to set up a game part way through, e.g. for tests or to reproduce a bug.
+ *
+ * Cards are referred to by name. Every player gets their own region, with the same id as the player, and players are numbered from 1 in the order they're listed.
+ */
+export type Scenario = {
+    /**
+     * the cards players draw from in live draft, top first
+     */
+    common_deck?: string[];
+    /**
+     * the game mode, defaults to constructed with teams made up from the players' teams
+     */
+    game_mode?: GameMode | null;
+    initiative_player?: PlayerId;
+    players: ScenarioPlayer[];
+    seed?: number[];
+    /**
+     * the step every region is in
+     */
+    step?: Phase;
+    turn?: number;
+};
+
+export type ScenarioPlayer = {
+    /**
+     * the units and tokens the player controls, outside of any formation
+     */
+    battlefield?: string[];
+    /**
+     * the player's own deck, top first, only used outside of live draft
+     */
+    deck?: string[];
+    discard?: string[];
+    hand?: string[];
+    health?: number;
+    pack?: string[] | null;
+    resources?: ScenarioResource[];
+    /**
+     * how many resources the player has played this turn
+     */
+    resources_played?: number;
+    /**
+     * players without a team are each on a team of their own, numbered after the named teams
+     */
+    team?: number | null;
+};
+
+export type ScenarioResource = {
+    name: string;
+    tapped?: boolean;
+};
+
+export type SeatModel = {
+    agent: AgentModel;
+    player_id: PlayerId;
+};
+
+/**
+ * Which player of the game each agent in the lobby plays as.
+ */
+export type Seating = "Random" | {
+    Chosen: { [key: string]: PlayerId };
+};
+
+/**
+ * The sha-256 hash of what a game's seed is made from, as lowercase hex, published before it's revealed.
+ */
+export type SeedCommitment = string;
+
+/**
+ * What a game's seed was made from, revealed once the game is over.
+ */
+export type SeedReveal = {
+    /**
+     * the entropy the agents contributed, in the order it's hashed in
+     */
+    contributions: Contribution[];
+    server_seed: number[];
+};
+
+export type ServerEvent = {
+    agent: AgentModel;
+    lobby: LobbyModel;
+    type: "agent_joined_lobby";
+} | {
+    agent_id: string;
+    type: "agent_left_lobby";
+} | {
+    agent_id: string;
+    type: "new_host";
+} | {
+    lobby: LobbyModel;
+    type: "lobby_settings_changed";
+} | {
+    agent_id: string;
+    migration_info: MigrationInfoModel;
+    type: "migrate";
+} | {
+    player_id: PlayerId;
+    state: State;
+    type: "game_state";
+    valid_actions: Action[];
+} | {
+    state: State;
+    type: "spectator_state";
+} | {
+    mutations: StaticStateMutation[];
+    type: "mutations";
+} | {
+    player_id: PlayerId;
+    type: "player_away";
+} | {
+    player_id: PlayerId;
+    type: "player_returned";
+} | {
+    actions: Action[];
+    result: GameResult;
+    seed_reveal?: SeedReveal | null;
+    type: "game_over";
+};
+
+export type ServerRequest = {
+    type: "agent_key_request";
+} | {
+    type: "migration_info_request";
+};
+
+export type ServerResponse = {
+    type: "start_game_response";
+} | {
+    lobby: LobbyModel;
+    type: "lobby_created";
+};
+
+export type Stack = {
+    priority: PlayerId[];
+    stack: Effect[];
+};
+
+export type State = {
+    card_id_factory: IdFactory;
+    common_deck?: Deck | null;
+    depth: number;
+    formation_id_factory: IdFactory;
+    game_mode: GameMode;
+    initiative_player: PlayerId;
+    permanent_id_factory: IdFactory;
+    rand: AlgomancerRng;
+    /**
+     * regions are shared between clones of the state until one of them changes the region, which then gets its own copy (see [Arc::make_mut]), so cloning a state per action stays cheap
+     */
+    regions: Region[];
+    /**
+     * set once the game has ended, after which no more actions can be taken
+     */
+    result?: GameResult | null;
+    /**
+     * the version of the layout the state is serialized in, older documents are upgraded to the current one as they're loaded (see [crate::game::schema])
+     */
+    schema_version: number;
+    /**
+     * the current turn, starting at 1. A new turn starts when the regions enter the refresh step
+     */
+    turn: number;
+};
+
+/**
+ * The structural differences between two states, from the first to the second.
+ */
+export type StateDiff = {
+    differences: Difference[];
+};
+
+/**
+ * State mutations are an instruction to make the smallest meaningful change in state. Actions, the next level up, generate a list state mutations, which are then applied to the state in order.
+ *
+ * This list of individual small changes in state can be serialized and sent to clients so that they can coherently display what happened between the application of the last action and the next state.
+ */
+export type StaticStateMutation = {
+    region_id: RegionId;
+    type: "StackClearPriority";
+} | {
+    player_id: PlayerId;
+    region_id: RegionId;
+    type: "StackAddPriority";
+} | {
+    region_id: RegionId;
+    type: "StackPassPriority";
+} | {
+    region_id: RegionId;
+    to_phase: Phase;
+    type: "PhaseTransition";
+} | {
+    card_id: CardId;
+    from: CardCollectionId;
+    to: To;
+    type: "MoveCard";
+} | {
+    player_id: PlayerId;
+    type: "CreatePackForPlayer";
+} | {
+    card: Card;
+    card_collection_id: CardCollectionId;
+    type: "CreateCard";
+} | {
+    card_id: CardId;
+    type: "RemoveCard";
+} | {
+    new_value: number;
+    player_id: PlayerId;
+    type: "UpdatePlayerHealth";
+} | {
+    new_value: boolean;
+    player_id: PlayerId;
+    type: "UpdatePlayerAlive";
+} | {
+    new_value: number;
+    player_id: PlayerId;
+    type: "UpdatePlayerResourcesPlayed";
+} | {
+    permanent: Permanent;
+    region_id: RegionId;
+    type: "CreatePermanent";
+} | {
+    resource_permanent_id: PermanentId;
+    tapped: boolean;
+    type: "SetResourceTapped";
+} | {
+    card: Card;
+    host_permanent_id: PermanentId;
+    type: "AttachMod";
+} | {
+    permanent_id: PermanentId;
+    type: "DestroyPermanent";
+} | {
+    amount: number;
+    source_permanent_id: PermanentId;
+    target: DamageTarget;
+    type: "DealDamage";
+} | {
+    effect: Effect;
+    region_id: RegionId;
+    type: "StackPushEffect";
+} | {
+    region_id: RegionId;
+    type: "StackResolveEffect";
+} | {
+    result: GameResult;
+    type: "SetGameResult";
+} | {
+    turn: number;
+    type: "SetTurn";
+} | {
+    player_id: PlayerId;
+    type: "ResetTurnRecord";
+} | {
+    permanent_ids: PermanentId[];
+    player_id: PlayerId;
+    type: "RecordUnitsAttacked";
+};
+
+export type TeamConfiguration = {
+    Ffa: {
+        num_players: number;
+    };
+} | {
+    Teams: {
+        teams_of_players: number[];
+    };
+};
+
+export type TeamId = number;
+
+export type Timing = {
+    timing: "Default";
+} | {
+    timing: "Haste";
+} | {
+    timing: "Battle";
+} | {
+    timing: "Virus";
+};
+
+export type To = {
+    Ordered: [CardCollectionId, Placement];
+} | {
+    Unordered: CardCollectionId;
+};
+
+/**
+ * The condition that causes a triggered ability to go on the stack. These are named after the phrases the card grammar uses, where 'I' or 'me' is the permanent that has the ability.
+ */
+export type Trigger = {
+    type: "SelfSpawns";
+} | {
+    type: "SelfDies";
+} | {
+    type: "SelfModded";
+} | {
+    type: "SelfDealtDamage";
+} | {
+    another: boolean;
+    nontoken: boolean;
+    type: "UnitDies";
+} | {
+    another: boolean;
+    nontoken: boolean;
+    type: "AllySpawns";
+} | {
+    type: "PlayerLosesLife";
+} | {
+    type: "AfterCombat";
+} | {
+    type: "EndOfTurn";
+};
+
+/**
+ * A triggered ability printed on a card, e.g. 'When I die, draw a card.'
+ */
+export type TriggeredAbility = {
+    /**
+     * the parsed effect_text, if the engine knows how to resolve it
+     */
+    effect?: AbilityEffect | null;
+    /**
+     * the text of the ability that follows the trigger, e.g. 'draw a card'
+     */
+    effect_text: string;
+    trigger: Trigger;
+};
+
+/**
+ * What a player has done during the current turn, for card conditions and displays that care about 'this turn'. Every player's record is cleared during the refresh step.
+ */
+export type TurnRecord = {
+    cards_drawn: number;
+    resources_played: number;
+    /**
+     * the units the player attacked with
+     */
+    units_attacked: PermanentId[];
+    /**
+     * the units the player controlled which died, including tokens
+     */
+    units_died: PermanentId[];
+};
+
+/**
+ * A hand, discard or pack. Like [crate::game::state::deck::Deck], the cards are shared between clones until one of them changes.
+ */
+export type UnorderedCards = {
+    cards: Card[];
+    id: CardCollectionId;
+};
+
+export type WsMessage = {
+    type: "server_request";
+    value: ServerRequest;
+} | {
+    type: "server_response";
+    value: ServerResponse;
+} | {
+    type: "server_event";
+    value: ServerEvent;
+} | {
+    type: "client_request";
+    value: ClientRequest;
+} | {
+    type: "client_response";
+    value: ClientResponse;
+} | {
+    type: "error";
+    value: string;
+};